@@ -54,8 +54,9 @@ use terracotta::{
 		errors::AppError,
 		init::{load_config, setup_logging},
 		state::StateProvider as _,
+		utility::shutdown_signal,
 	},
-	stats::worker::start as start_stats_processor,
+	stats::worker::{start as start_stats_processor, stop as stop_stats_processor},
 };
 use tracing::info;
 use utoipa::OpenApi as _;
@@ -86,7 +87,13 @@ async fn main() -> Result<(), AppError> {
 	let state  = Arc::new(AppState::new(config));
 	start_stats_processor(&state).await;
 	let app    = create_app(&state, routes(), ApiDoc::openapi());
-	let server = create_server(app, &*state).await?;
+	let server = create_server(app, &*state, {
+		let appstate = Arc::clone(&state);
+		async move {
+			shutdown_signal().await;
+			stop_stats_processor(&*appstate).await;
+		}
+	}).await?;
 	info!("Listening on {}", state.address().expect("Server address not set"));
 	server.await.unwrap()
 }
@@ -5,7 +5,10 @@
 //		Packages																										
 
 use crate::config::Config;
-use core::net::{IpAddr, SocketAddr};
+use core::{
+	net::{IpAddr, SocketAddr},
+	time::Duration,
+};
 use parking_lot::RwLock;
 use terracotta::{
 	app::state::StateProvider as AppStateProvider,
@@ -82,6 +85,11 @@ impl AppStateProvider for AppState {
 		*self.address.write() = address;
 	}
 	
+	//		shutdown_timeout													
+	fn shutdown_timeout(&self) -> Duration {
+		Duration::from_secs(self.config.shutdown_timeout)
+	}
+	
 	//		title																
 	fn title(&self) -> &String {
 		&self.config.title
@@ -4,6 +4,7 @@
 
 //		Packages																										
 
+use core::convert::Infallible;
 use serde::{Deserialize, Serialize};
 use terracotta::auth::{
 	middleware::{User as AuthUser, Credentials as AuthCredentials, UserProvider as AuthUserProvider},
@@ -65,29 +66,31 @@ impl AuthUser for User {
 //󰭅		AuthUserProvider														
 impl AuthUserProvider for User {
 	type Credentials = Credentials;
+	type Error       = Infallible;
 	type User        = Self;
 	
 	//		find_by_credentials													
-	fn find_by_credentials<SP: AuthStateProvider>(
+	async fn find_by_credentials<SP: AuthStateProvider>(
 		state:       &SP,
 		credentials: &Self::Credentials,
-	) -> Option<Self> {
-		state
+	) -> Result<Option<Self>, Self::Error> {
+		Ok(state
 			.users()
-			.get(&credentials.username)
-			.filter(|&pass| pass == &credentials.password)
-			.map(|_| Self { username: credentials.username.clone() })
+			.verify(&credentials.username, &credentials.password)
+			.then(|| Self { username: credentials.username.clone() })
+		)
 	}
 	
 	//		find_by_id															
-	fn find_by_id<SP: AuthStateProvider>(
+	async fn find_by_id<SP: AuthStateProvider>(
 		state: &SP,
 		id:    &<Self::User as AuthUser>::Id,
-	) -> Option<Self> {
-		state
+	) -> Result<Option<Self>, Self::Error> {
+		Ok(state
 			.users()
-			.get(id)
-			.map(|_| Self { username: id.to_owned() })
+			.contains(id)
+			.then(|| Self { username: id.to_owned() })
+		)
 	}
 }
 
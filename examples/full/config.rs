@@ -7,10 +7,13 @@
 use core::net::IpAddr;
 use serde::{Deserialize, Serialize};
 use smart_default::SmartDefault;
-use std::collections::HashMap;
 use terracotta::{
 	app::config::HtmlTemplates,
 	assets::config::Config as AssetsConfig,
+	auth::{
+		config::Config as AuthConfig,
+		users::ConfigUserStore,
+	},
 	stats::config::Config as StatsConfig,
 };
 
@@ -25,33 +28,40 @@ pub struct Config {
 	//		Public properties													
 	/// The host to listen on.
 	#[default(IpAddr::from([127, 0, 0, 1]))]
-	pub host:   IpAddr,
+	pub host:             IpAddr,
 	
 	/// The port to listen on.
 	#[default = 8000]
-	pub port:   u16,
+	pub port:             u16,
+	
+	/// The maximum time to wait for open connections to drain when shutting
+	/// down, in seconds.
+	#[default = 30]
+	pub shutdown_timeout: u64,
 	
 	/// The directory to store log files in.
 	#[default = "log"]
-	pub logdir: String,
+	pub logdir:           String,
 	
 	/// The title of the application.
 	#[default = "Terracotta"]
-	pub title:  String,
+	pub title:            String,
 	
 	/// Loading configuration for HTML templates.
 	#[serde(rename = "html_templates")]
-	pub html:   HtmlTemplates,
+	pub html:             HtmlTemplates,
 	
 	/// The configuration options for serving static assets.
-	pub assets: AssetsConfig,
+	pub assets:           AssetsConfig,
+	
+	/// The configuration options for authentication.
+	pub auth:             AuthConfig,
 	
 	/// The configuration options for gathering and processing statistics.
-	pub stats:  StatsConfig,
+	pub stats:            StatsConfig,
 	
-	/// A list of users and their passwords.
-	#[default(HashMap::new())]
-	pub users:  HashMap<String, String>,
+	/// A list of users and their password hashes.
+	pub users:            ConfigUserStore,
 }
 
 
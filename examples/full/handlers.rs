@@ -4,12 +4,19 @@
 
 //		Packages																										
 
-use crate::state::AppState;
+use crate::{
+	auth::User,
+	state::AppState,
+};
 use axum::{
 	extract::State,
 	response::Html,
 };
 use std::sync::Arc;
+use terracotta::{
+	app::errors::AppError,
+	auth::middleware::Context,
+};
 use tera::Context as Template;
 
 
@@ -19,15 +26,26 @@ use tera::Context as Template;
 //		get_index																
 /// Shows the index page.
 /// 
+/// The page is rendered using the authentication context, so that the layout
+/// can show who is logged in.
+/// 
 /// # Parameters
 /// 
 /// * `state` - The application state.
+/// * `auth`  - The authentication context.
+/// 
+/// # Errors
+/// 
+/// If there is an error rendering the page, an error will be returned.
 /// 
-pub async fn get_index(State(state): State<Arc<AppState>>) -> Html<String> {
+pub async fn get_index(
+	State(state): State<Arc<AppState>>,
+	auth:         Context<User>,
+) -> Result<Html<String>, AppError> {
 	let mut template = Template::new();
 	template.insert("Title",   &state.config.title);
 	template.insert("Content", "Index");
-	Html(state.tera.render("index", &template).unwrap())
+	Ok(Html(auth.render(&*state, "index", template).await?))
 }
 
 
@@ -41,7 +41,7 @@ pub fn public() -> Vec<(&'static str, MethodRouter<Arc<AppState>>)> {
 		("/api/stats/history", get(get_stats_history)),
 		("/api/stats/feed",    get(get_stats_feed)),
 		("/login",             post(post_login::<_, Credentials, User, User>)),
-		("/logout",            get(get_logout::<User>)),
+		("/logout",            get(get_logout::<_, User>)),
 		("/css/{*path}",       get(get_public_static_asset)),
 		("/img/{*path}",       get(get_public_static_asset)),
 		("/js/{*path}",        get(get_public_static_asset)),
@@ -5,13 +5,13 @@
 //		Packages																										
 
 use crate::config::Config;
-use core::net::{IpAddr, SocketAddr};
+use core::{
+	net::{IpAddr, SocketAddr},
+	time::Duration,
+};
 use include_dir::{Dir, include_dir};
 use parking_lot::RwLock;
-use std::{
-	collections::HashMap,
-	sync::Arc,
-};
+use std::sync::Arc;
 use tera::{Context, Tera};
 use terracotta::{
 	app::{
@@ -23,15 +23,25 @@ use terracotta::{
 	},
 	assets::{
 		config::Config as AssetsConfig,
+		manifest::{ASSET_URL_FUNCTION, AssetManifest},
 		state::StateProvider as AssetsStateProvider,
 	},
-	auth::state::StateProvider as AuthStateProvider,
+	auth::{
+		audit::TracingAuditSink,
+		config::Config as AuthConfig,
+		lockout::LockoutTracker,
+		remember::MemoryRememberStore,
+		revocation::MemoryRevocationStore,
+		state::StateProvider as AuthStateProvider,
+		users::ConfigUserStore,
+	},
 	stats::{
 		config::Config as StatsConfig,
 		state::{State as StatsState, StateProvider as StatsStateProvider},
 	},
 };
 use tokio::sync::RwLock as AsyncRwLock;
+use tower_sessions::MemoryStore as SessionMemoryStore;
 
 
 
@@ -47,22 +57,37 @@ use tokio::sync::RwLock as AsyncRwLock;
 pub struct AppState {
 	//		Public properties													
 	/// The address the server is running on.
-	pub address:     RwLock<Option<SocketAddr>>,
+	pub address:        RwLock<Option<SocketAddr>>,
+	
+	/// The manifest of content hashes for the static assets.
+	pub asset_manifest: AssetManifest,
 	
 	/// The directory containing the static assets.
-	pub assets_dir:  Arc<Dir<'static>>,
+	pub assets_dir:     Arc<Dir<'static>>,
 	
 	/// The application configuration.
-	pub config:      Config,
+	pub config:         Config,
 	
 	/// The directory containing the Markdown content.
-	pub content_dir: Arc<Dir<'static>>,
+	pub content_dir:    Arc<Dir<'static>>,
+	
+	/// The tracker for failed login attempts.
+	pub lockout:        LockoutTracker,
+	
+	/// The remember-me token store.
+	pub remember:       MemoryRememberStore,
+	
+	/// The store of users' revocation generations.
+	pub revocation:     MemoryRevocationStore,
+	
+	/// The session store.
+	pub sessions:       SessionMemoryStore,
 	
 	/// The application statistics.
-	pub stats:       AsyncRwLock<StatsState>,
+	pub stats:          AsyncRwLock<StatsState>,
 	
 	/// The Tera template engine.
-	pub tera:        Tera,
+	pub tera:           Tera,
 }
 
 //󰭅		AppState																
@@ -118,6 +143,11 @@ impl AppStateProvider for AppState {
 		*self.address.write() = address;
 	}
 	
+	//		shutdown_timeout													
+	fn shutdown_timeout(&self) -> Duration {
+		Duration::from_secs(self.config.shutdown_timeout)
+	}
+	
 	//		tera																
 	fn tera(&self) -> &Tera {
 		&self.tera
@@ -145,12 +175,52 @@ impl AssetsStateProvider for AppState {
 	fn content_dir(&self) -> Arc<Dir<'static>> {
 		Arc::clone(&self.content_dir)
 	}
+	
+	//		asset_manifest														
+	fn asset_manifest(&self) -> &AssetManifest {
+		&self.asset_manifest
+	}
 }
 
 //󰭅		AuthStateProvider														
 impl AuthStateProvider for AppState {
+	type AuditSink       = TracingAuditSink;
+	type RememberStore   = MemoryRememberStore;
+	type RevocationStore = MemoryRevocationStore;
+	type SessionStore    = SessionMemoryStore;
+	
+	//		audit_sink															
+	fn audit_sink(&self) -> &TracingAuditSink {
+		&TracingAuditSink
+	}
+	
+	//		config																
+	fn config(&self) -> &AuthConfig {
+		&self.config.auth
+	}
+	
+	//		lockout																
+	fn lockout(&self) -> &LockoutTracker {
+		&self.lockout
+	}
+	
+	//		remember_store														
+	fn remember_store(&self) -> &MemoryRememberStore {
+		&self.remember
+	}
+	
+	//		revocation_store													
+	fn revocation_store(&self) -> &MemoryRevocationStore {
+		&self.revocation
+	}
+	
+	//		session_store														
+	fn session_store(&self) -> SessionMemoryStore {
+		self.sessions.clone()
+	}
+	
 	//		users																
-	fn users(&self) -> &HashMap<String, String> {
+	fn users(&self) -> &ConfigUserStore {
 		&self.config.users
 	}
 }
@@ -159,15 +229,24 @@ impl AuthStateProvider for AppState {
 impl Default for AppState {
 	//		default																
 	fn default() -> Self {
+		let assets_dir     = Arc::new(include_dir!("examples/resources/static"));
+		let asset_manifest = AssetManifest::new(&assets_dir);
+		let mut tera       = setup_tera(&Arc::new(include_dir!("examples/resources/html")))
+			.expect("Error loading templates")
+		;
+		tera.register_function(ASSET_URL_FUNCTION, asset_manifest.clone());
 		Self {
-			address:     RwLock::new(None),
-			assets_dir:  Arc::new(include_dir!("examples/resources/static")),
-			config:      Config::default(),
-			content_dir: Arc::new(include_dir!("examples/resources/content")),
-			stats:       AsyncRwLock::new(StatsState::default()),
-			tera:        setup_tera(&Arc::new(include_dir!("examples/resources/html")))
-				.expect("Error loading templates")
-			,
+			address:        RwLock::new(None),
+			asset_manifest,
+			assets_dir,
+			config:         Config::default(),
+			content_dir:    Arc::new(include_dir!("examples/resources/content")),
+			lockout:        LockoutTracker::default(),
+			remember:       MemoryRememberStore::default(),
+			revocation:     MemoryRevocationStore::default(),
+			sessions:       SessionMemoryStore::default(),
+			stats:          AsyncRwLock::new(StatsState::default()),
+			tera,
 		}
 	}
 }
@@ -0,0 +1,46 @@
+//! Terracotta: Password hashing helper
+//! 
+//! Generates an Argon2id password hash suitable for use in the `[users]`
+//! section of the configuration file. The password is read from standard
+//! input, so that it does not end up in the shell history, and the hash is
+//! written to standard output.
+//! 
+//! ```sh
+//! cargo run --example hash_password --features=auth
+//! ```
+//! 
+
+
+
+//		Global configuration																							
+
+//	Customisations of the standard linting configuration
+#![allow(unreachable_pub,                 reason = "Not useful in binaries")]
+#![allow(unused_crate_dependencies,       reason = "Not relevant to examples")]
+#![allow(clippy::doc_markdown,            reason = "Too many false positives")]
+#![allow(clippy::multiple_crate_versions, reason = "Cannot resolve all these")]
+
+
+
+//		Packages																										
+
+use std::io::{Error as IoError, ErrorKind as IoErrorKind, Write as _, stdin, stdout};
+use terracotta::auth::password::hash_password;
+
+
+
+//		Functions																										
+
+//		main																	
+fn main() -> Result<(), IoError> {
+	let mut input = String::new();
+	_ = stdin().read_line(&mut input)?;
+	let password  = input.trim_end_matches(['\r', '\n']);
+	if password.is_empty() {
+		return Err(IoError::new(IoErrorKind::InvalidInput, "No password supplied"));
+	}
+	let hash      = hash_password(password).map_err(IoError::other)?;
+	writeln!(stdout(), "{hash}")
+}
+
+
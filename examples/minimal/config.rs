@@ -20,23 +20,28 @@ pub struct Config {
 	//		Public properties													
 	/// The host to listen on.
 	#[default(IpAddr::from([127, 0, 0, 1]))]
-	pub host:   IpAddr,
+	pub host:             IpAddr,
 	
 	/// The port to listen on.
 	#[default = 8000]
-	pub port:   u16,
+	pub port:             u16,
+	
+	/// The maximum time to wait for open connections to drain when shutting
+	/// down, in seconds.
+	#[default = 30]
+	pub shutdown_timeout: u64,
 	
 	/// The directory to store log files in.
 	#[default = "log"]
-	pub logdir: String,
+	pub logdir:           String,
 	
 	/// The title of the application.
 	#[default = "Terracotta"]
-	pub title:  String,
+	pub title:            String,
 	
 	/// Loading configuration for HTML templates.
 	#[serde(rename = "html_templates")]
-	pub html:   HtmlTemplates,
+	pub html:             HtmlTemplates,
 }
 
 
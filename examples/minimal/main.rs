@@ -50,6 +50,7 @@ use terracotta::app::{
 	errors::AppError,
 	init::{load_config, setup_logging},
 	state::StateProvider as _,
+	utility::shutdown_signal,
 };
 use tracing::info;
 
@@ -78,7 +79,7 @@ async fn main() -> Result<(), AppError> {
 	let _guard = setup_logging(&config.logdir);
 	let state  = Arc::new(AppState::new(config));
 	let app    = create_app(&state, routes());
-	let server = create_server(app, &*state).await?;
+	let server = create_server(app, &*state, shutdown_signal()).await?;
 	info!("Listening on {}", state.address().expect("Server address not set"));
 	server.await.unwrap()
 }
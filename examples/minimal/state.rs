@@ -5,7 +5,10 @@
 //		Packages																										
 
 use crate::config::Config;
-use core::net::{IpAddr, SocketAddr};
+use core::{
+	net::{IpAddr, SocketAddr},
+	time::Duration,
+};
 use include_dir::include_dir;
 use parking_lot::RwLock;
 use std::sync::Arc;
@@ -94,6 +97,11 @@ impl AppStateProvider for AppState {
 		*self.address.write() = address;
 	}
 	
+	//		shutdown_timeout													
+	fn shutdown_timeout(&self) -> Duration {
+		Duration::from_secs(self.config.shutdown_timeout)
+	}
+	
 	//		tera																
 	fn tera(&self) -> &Tera {
 		&self.tera
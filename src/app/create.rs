@@ -2,6 +2,14 @@
 
 
 
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/create.rs"]
+mod tests;
+
+
+
 //		Packages																										
 
 use super::{
@@ -9,12 +17,19 @@ use super::{
 	state::StateProvider,
 };
 use axum::Router;
-use core::net::SocketAddr;
+use core::{
+	future::{Future, IntoFuture as _, pending},
+	net::SocketAddr,
+};
 use tokio::{
 	net::TcpListener,
+	select,
+	sync::oneshot,
 	task::JoinHandle as TaskHandle,
 	spawn as spawn_async,
+	time::sleep,
 };
+use tracing::{info, warn};
 
 #[cfg(all(feature = "auth", feature = "stats"))]
 use crate::{
@@ -80,6 +95,7 @@ where
 		.fallback(no_route)
 		.add_protected_error_catcher::<_, U>(state)
 		.add_error_template(state)
+		.add_csrf_protection::<_, U>(state)
 		.add_authentication::<_, U, UP>(state)
 		.add_stats_gathering(state)
 		.with_state(Arc::clone(state))
@@ -123,28 +139,72 @@ where
 //		server																	
 /// Creates the application server.
 /// 
+/// The server will run until the `shutdown` future completes, at which point it
+/// will stop accepting new connections and wait for in-flight requests to
+/// finish. If they have not finished within the configured
+/// [shutdown timeout](StateProvider::shutdown_timeout()), the server task will
+/// end without waiting for them any further. Note that this does not abort the
+/// remaining connections, which continue to be served until they finish or the
+/// process exits, and so the application should exit once the server task has
+/// ended.
+/// 
+/// The server provides connection info to the application, so that handlers
+/// can obtain the client's address using [`ConnectInfo<SocketAddr>`](axum::extract::ConnectInfo).
+/// 
+/// Long-lived connections such as WebSockets will hold up the drain, so any
+/// such functionality should be told to close as part of the `shutdown`
+/// future — for instance, by calling [`stats::worker::stop()`](crate::stats::worker::stop())
+/// before completing.
+/// 
 /// # Parameters
 /// 
-/// * `app`   - The application router.
-/// * `state` - The application state.
+/// * `app`      - The application router.
+/// * `state`    - The application state.
+/// * `shutdown` - A future that completes when the server should shut down.
+///                Typically this will be [`shutdown_signal()`](super::utility::shutdown_signal()).
 /// 
 /// # Errors
 /// 
 /// If the configured host and port cannot be bound to, or if the server cannot
 /// be started, an error is returned.
 /// 
-pub async fn server<SP>(
-	app:   Router,
-	state: &SP,
+pub async fn server<SP, F>(
+	app:      Router,
+	state:    &SP,
+	shutdown: F,
 ) -> Result<TaskHandle<Result<(), AppError>>, AppError>
 where
 	SP: StateProvider,
+	F:  Future<Output = ()> + Send + 'static,
 {
-	let listener = TcpListener::bind(SocketAddr::from((state.host(), state.port()))).await?;
+	let listener            = TcpListener::bind(SocketAddr::from((state.host(), state.port()))).await?;
 	state.set_address(Some(listener.local_addr()?));
+	let timeout             = state.shutdown_timeout();
+	let (draining, drained) = oneshot::channel::<()>();
 	Ok(spawn_async(async move {
-		axum::serve(listener, app).await
-			.map_err(AppError::CouldNotStartServer)
+		let server   = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+			.with_graceful_shutdown(async move {
+				shutdown.await;
+				info!("Shutting down, waiting up to {timeout:?} for connections to drain");
+				_ = draining.send(());
+			})
+			.into_future()
+		;
+		//	The drain deadline only starts counting once shutdown has begun. If the
+		//	sender is dropped without being used then the server has already ended.
+		let deadline = async move {
+			if drained.await.is_err() {
+				pending::<()>().await;
+			}
+			sleep(timeout).await;
+		};
+		select! {
+			result = server   => result.map_err(AppError::CouldNotStartServer),
+			()     = deadline => {
+				warn!("Timed out waiting for connections to drain, no longer waiting for remaining connections");
+				Ok(())
+			},
+		}
 	}))
 }
 
@@ -4,7 +4,10 @@
 
 //		Packages																										
 
-use core::net::{IpAddr, SocketAddr};
+use core::{
+	net::{IpAddr, SocketAddr},
+	time::Duration,
+};
 
 #[cfg(feature = "tera")]
 use super::{
@@ -19,6 +22,14 @@ use ::{
 
 
 
+//		Constants																										
+
+/// The default time to wait for open connections to drain when the server is
+/// shutting down.
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
+
+
 //		Traits																											
 
 //§		StateProvider															
@@ -69,6 +80,15 @@ pub trait StateProvider: Send + Sync + 'static {
 	/// 
 	fn set_address(&self, address: Option<SocketAddr>);
 	
+	//		shutdown_timeout													
+	/// Gets the maximum time to wait for open connections to drain when the
+	/// server is shutting down. After this time has elapsed, the server stops
+	/// waiting for any remaining connections. Defaults to
+	/// [`DEFAULT_SHUTDOWN_TIMEOUT`].
+	fn shutdown_timeout(&self) -> Duration {
+		DEFAULT_SHUTDOWN_TIMEOUT
+	}
+	
 	//		tera																
 	/// Gets the application's Tera instance.
 	#[cfg(feature = "tera")]
@@ -0,0 +1,149 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use axum::routing::get;
+use core::{
+	net::{IpAddr, Ipv4Addr},
+	time::Duration,
+};
+use parking_lot::RwLock;
+use std::io;
+use tokio::{
+	io::{AsyncReadExt as _, AsyncWriteExt as _},
+	net::TcpStream,
+	time::timeout,
+};
+
+#[cfg(feature = "tera")]
+use crate::app::config::HtmlTemplates;
+#[cfg(feature = "tera")]
+use tera::{Context, Tera};
+
+
+
+//		Structs																											
+
+//		TestState																
+#[derive(Debug, Default)]
+struct TestState {
+	address:          RwLock<Option<SocketAddr>>,
+	shutdown_timeout: Duration,
+	#[cfg(feature = "tera")]
+	templates:        HtmlTemplates,
+	#[cfg(feature = "tera")]
+	tera:             Tera,
+	title:            String,
+}
+
+//󰭅		StateProvider															
+impl StateProvider for TestState {
+	//		address																
+	fn address(&self) -> Option<SocketAddr> {
+		*self.address.read()
+	}
+	
+	//		html_templates_config												
+	#[cfg(feature = "tera")]
+	fn html_templates_config(&self) -> &HtmlTemplates {
+		&self.templates
+	}
+	
+	//		host																
+	fn host(&self) -> IpAddr {
+		IpAddr::V4(Ipv4Addr::LOCALHOST)
+	}
+	
+	//		port																
+	fn port(&self) -> u16 {
+		0
+	}
+	
+	//		render																
+	#[cfg(feature = "tera")]
+	async fn render<T: AsRef<str> + Send>(&self, _template: T, _context: &Context) -> Result<String, AppError> {
+		Ok(String::new())
+	}
+	
+	//		set_address															
+	fn set_address(&self, address: Option<SocketAddr>) {
+		*self.address.write() = address;
+	}
+	
+	//		shutdown_timeout													
+	fn shutdown_timeout(&self) -> Duration {
+		self.shutdown_timeout
+	}
+	
+	//		tera																
+	#[cfg(feature = "tera")]
+	fn tera(&self) -> &Tera {
+		&self.tera
+	}
+	
+	//		title																
+	fn title(&self) -> &String {
+		&self.title
+	}
+}
+
+
+
+//		Functions																										
+
+//		request																	
+async fn request(address: SocketAddr, path: &str) -> io::Result<String> {
+	let mut stream   = TcpStream::connect(address).await?;
+	stream.write_all(format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n").as_bytes()).await?;
+	let mut response = String::new();
+	let _count       = stream.read_to_string(&mut response).await?;
+	Ok(response)
+}
+
+//		start																	
+async fn start(shutdown_timeout: Duration) -> (SocketAddr, oneshot::Sender<()>, TaskHandle<Result<(), AppError>>) {
+	let app             = Router::new()
+		.route("/slow",  get(|| async { sleep(Duration::from_millis(300)).await; "done" }))
+		.route("/stuck", get(|| async { sleep(Duration::from_secs(60)).await;    "done" }))
+	;
+	let state           = TestState { shutdown_timeout, ..Default::default() };
+	let (stop, stopped) = oneshot::channel::<()>();
+	let handle          = server(app, &state, async move { _ = stopped.await; }).await.unwrap();
+	(state.address().unwrap(), stop, handle)
+}
+
+
+
+//		Tests																											
+
+//		server__drains_in_flight_requests										
+#[tokio::test]
+async fn server__drains_in_flight_requests() {
+	let (address, stop, handle) = start(Duration::from_secs(5)).await;
+	let in_flight               = spawn_async(async move { request(address, "/slow").await });
+	sleep(Duration::from_millis(100)).await;
+	stop.send(()).unwrap();
+	let response                = in_flight.await.unwrap().unwrap();
+	assert!(response.starts_with("HTTP/1.1 200 OK"));
+	assert!(response.ends_with("done"));
+	assert!(timeout(Duration::from_secs(2), handle).await.unwrap().unwrap().is_ok());
+	//	New connections are no longer accepted
+	assert!(request(address, "/slow").await.is_err());
+}
+
+//		server__stops_waiting_after_deadline									
+#[tokio::test]
+async fn server__stops_waiting_after_deadline() {
+	let (address, stop, handle) = start(Duration::from_millis(200)).await;
+	let in_flight               = spawn_async(async move { request(address, "/stuck").await });
+	sleep(Duration::from_millis(100)).await;
+	stop.send(()).unwrap();
+	//	The server task ends once the deadline has passed, even though the
+	//	request is still being handled
+	assert!(timeout(Duration::from_secs(2), handle).await.unwrap().unwrap().is_ok());
+	assert!(!in_flight.is_finished());
+	in_flight.abort();
+}
+
+
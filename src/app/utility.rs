@@ -4,6 +4,12 @@
 
 //		Packages																										
 
+use core::future::pending;
+use tokio::{select, signal::ctrl_c};
+use tracing::{error, info};
+
+#[cfg(unix)]
+use tokio::signal::unix::{SignalKind, signal};
 #[cfg(feature = "tera")]
 use super::{
 	config::LoadingBehavior,
@@ -73,4 +79,41 @@ where
 	})
 }
 
+//		shutdown_signal															
+/// Waits for a shutdown signal.
+/// 
+/// This completes when the process receives either `Ctrl+C` (`SIGINT`) or, on
+/// Unix systems, `SIGTERM`. It is intended to be passed to
+/// [`server()`](super::create::server()) to trigger a graceful shutdown.
+/// 
+/// If a signal handler cannot be installed, the error is logged and that
+/// signal is ignored.
+/// 
+pub async fn shutdown_signal() {
+	let interrupt = async {
+		if let Err(err) = ctrl_c().await {
+			error!("Failed to listen for Ctrl+C: {err}");
+			pending::<()>().await;
+		}
+	};
+	#[cfg(unix)]
+	let terminate = async {
+		match signal(SignalKind::terminate()) {
+			Ok(mut stream) => {
+				_ = stream.recv().await;
+			},
+			Err(err) => {
+				error!("Failed to listen for SIGTERM: {err}");
+				pending::<()>().await;
+			},
+		}
+	};
+	#[cfg(not(unix))]
+	let terminate = pending::<()>();
+	select! {
+		() = interrupt => info!("Received Ctrl+C, shutting down"),
+		() = terminate => info!("Received SIGTERM, shutting down"),
+	}
+}
+
 
@@ -7,6 +7,7 @@
 pub mod config;
 pub mod errors;
 pub mod handlers;
+pub mod manifest;
 pub mod state;
 
 
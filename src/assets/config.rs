@@ -4,10 +4,21 @@
 
 
 
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/config.rs"]
+mod tests;
+
+
+
 //		Packages																										
 
 use crate::app::config::LoadingBehavior;
-use serde::{Deserialize, Serialize};
+use axum::http::HeaderValue;
+use core::str::FromStr;
+use glob::{Pattern, PatternError};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as DeError, ser::Error as SerError};
 use smart_default::SmartDefault;
 use std::path::PathBuf;
 
@@ -15,6 +26,154 @@ use std::path::PathBuf;
 
 //		Structs																											
 
+//		CacheControl															
+/// A value for the `Cache-Control` header.
+/// 
+/// This is validated when the configuration is loaded, so that an unusable
+/// value is reported at startup rather than when the first request arrives. An
+/// empty value means that no header is sent.
+/// 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CacheControl(HeaderValue);
+
+//󰭅		CacheControl															
+impl CacheControl {
+	//		from_static															
+	/// Creates a header value from a static string.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `value` - The value of the header.
+	/// 
+	/// # Panics
+	/// 
+	/// If the value contains characters that are not allowed in a header, this
+	/// will panic, in the same way as [`HeaderValue::from_static()`].
+	/// 
+	#[must_use]
+	pub const fn from_static(value: &'static str) -> Self {
+		Self(HeaderValue::from_static(value))
+	}
+	
+	//		to_header															
+	/// Gets the header value to send, if the value is not empty.
+	#[must_use]
+	pub fn to_header(&self) -> Option<HeaderValue> {
+		(!self.0.is_empty()).then(|| self.0.clone())
+	}
+}
+
+//󰭅		Deserialize																
+impl<'de> Deserialize<'de> for CacheControl {
+	//		deserialize															
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		String::deserialize(deserializer)?.parse().map_err(DeError::custom)
+	}
+}
+
+//󰭅		FromStr																	
+impl FromStr for CacheControl {
+	type Err = String;
+	
+	//		from_str															
+	fn from_str(value: &str) -> Result<Self, Self::Err> {
+		HeaderValue::from_str(value)
+			.map(Self)
+			.map_err(|err| format!("Invalid Cache-Control value {value:?}: {err}"))
+	}
+}
+
+//󰭅		Serialize																
+impl Serialize for CacheControl {
+	//		serialize															
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		serializer.serialize_str(self.0.to_str().map_err(SerError::custom)?)
+	}
+}
+
+//		CachePattern															
+/// A glob pattern for matching the paths of static assets.
+/// 
+/// This is compiled when the configuration is loaded, so that an invalid
+/// pattern is reported at startup, and is not compiled again for each request.
+/// 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CachePattern(Pattern);
+
+//󰭅		CachePattern															
+impl CachePattern {
+	//		matches																
+	/// Checks whether a path matches the pattern.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `path` - The path to check.
+	/// 
+	#[must_use]
+	pub fn matches(&self, path: &str) -> bool {
+		self.0.matches(path)
+	}
+}
+
+//󰭅		Deserialize																
+impl<'de> Deserialize<'de> for CachePattern {
+	//		deserialize															
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		String::deserialize(deserializer)?.parse()
+			.map_err(|err: PatternError| DeError::custom(format!("Invalid cache rule pattern: {err}")))
+	}
+}
+
+//󰭅		FromStr																	
+impl FromStr for CachePattern {
+	type Err = PatternError;
+	
+	//		from_str															
+	fn from_str(pattern: &str) -> Result<Self, Self::Err> {
+		Pattern::new(pattern).map(Self)
+	}
+}
+
+//󰭅		Serialize																
+impl Serialize for CachePattern {
+	//		serialize															
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		serializer.serialize_str(self.0.as_str())
+	}
+}
+
+//		CacheRule																
+/// A rule for the `Cache-Control` header to send for matching static assets.
+/// 
+/// For instance, files with a content hash in their names can be cached
+/// indefinitely, using `public, max-age=31536000, immutable`, whereas HTML
+/// files can be marked `no-cache`, so that they are always revalidated.
+/// 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CacheRule {
+	//		Public properties													
+	/// The glob pattern that the path of an asset must match, relative to the
+	/// root of the assets and without a leading slash, for instance
+	/// `css/*.css`. Note that `*` also matches `/`, so `*.html` matches HTML
+	/// files in any directory.
+	pub pattern: CachePattern,
+	
+	/// The value of the `Cache-Control` header to send.
+	pub value:   CacheControl,
+}
+
 //		Config																	
 /// The configuration options for gathering and processing statistics.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, SmartDefault)]
@@ -40,11 +199,23 @@ pub struct ProtectedAssets {
 	/// The loading behaviour for local, non-baked-in protected static assets.
 	/// This allows local assets to be used to complement the baked-in assets.
 	#[default(LoadingBehavior::Deny)]
-	pub behavior:   LoadingBehavior,
+	pub behavior:      LoadingBehavior,
 	
 	/// The path to the local, non-baked-in protected static assets.
 	#[default = "content"]
-	pub local_path: PathBuf,
+	pub local_path:    PathBuf,
+	
+	/// The `Cache-Control` header to send for protected static assets that do
+	/// not match any of the [`cache_rules`](Self::cache_rules). This defaults
+	/// to `private`, so that protected assets are not stored by shared caches
+	/// such as proxies. An empty value means that no header is sent.
+	#[default(Some(CacheControl::from_static("private")))]
+	pub cache_control: Option<CacheControl>,
+	
+	/// Rules for the `Cache-Control` header to send for particular protected
+	/// static assets. The first rule that matches the path of an asset is
+	/// used.
+	pub cache_rules:   Vec<CacheRule>,
 }
 
 //		PublicAssets															
@@ -55,11 +226,30 @@ pub struct PublicAssets {
 	/// The loading behaviour for local, non-baked-in public static assets. This
 	/// allows local assets to be used to complement the baked-in assets.
 	#[default(LoadingBehavior::Deny)]
-	pub behavior:   LoadingBehavior,
+	pub behavior:                  LoadingBehavior,
 	
 	/// The path to the local, non-baked-in public static assets.
 	#[default = "static"]
-	pub local_path: PathBuf,
+	pub local_path:                PathBuf,
+	
+	/// The `Cache-Control` header to send for public static assets that do not
+	/// match any of the [`cache_rules`](Self::cache_rules). If this is not set,
+	/// no header is sent, and caching is left to the client.
+	pub cache_control:             Option<CacheControl>,
+	
+	/// Rules for the `Cache-Control` header to send for particular public
+	/// static assets. The first rule that matches the path of an asset is
+	/// used.
+	pub cache_rules:               Vec<CacheRule>,
+	
+	/// The `Cache-Control` header to send for public static assets requested
+	/// using [fingerprinted](super::manifest) URLs. As these URLs change
+	/// whenever the contents of the files change, the files can be cached
+	/// indefinitely, and so this takes precedence over the
+	/// [`cache_rules`](Self::cache_rules). An empty value means that no header
+	/// is sent.
+	#[default(Some(CacheControl::from_static("public, max-age=31536000, immutable")))]
+	pub fingerprint_cache_control: Option<CacheControl>,
 }
 
 //		StaticFiles																
@@ -70,15 +260,42 @@ pub struct StaticFiles {
 	/// The file size at which to start streaming, in KB. Below this size, the
 	/// file will be read into memory and served all at once.
 	#[default = 1_000]
-	pub stream_threshold: usize,
+	pub stream_threshold:      usize,
 	
 	/// The size of the stream buffer to use when streaming files, in KB.
 	#[default = 256]
-	pub stream_buffer:    usize,
+	pub stream_buffer:         usize,
 	
 	/// The size of the read buffer to use when streaming files, in KB.
 	#[default = 128]
-	pub read_buffer:      usize,
+	pub read_buffer:           usize,
+	
+	/// Whether to support conditional requests. If enabled, responses carry an
+	/// `ETag` header, and a `Last-Modified` header for local files, and
+	/// requests with a matching `If-None-Match` or `If-Modified-Since` header
+	/// receive an empty `304 Not Modified` response, so that browsers do not
+	/// need to download unchanged files again.
+	#[default = true]
+	pub conditional_requests:  bool,
+	
+	/// Whether to serve precompressed files. If enabled, and the client
+	/// accepts the encoding, a file with a `.br` or `.gz` extension alongside
+	/// the requested file is served in its place, with the appropriate
+	/// `Content-Encoding` header. Brotli is preferred over gzip.
+	#[default = true]
+	pub precompressed:         bool,
+	
+	/// Whether to compress text files on the fly, when no precompressed file
+	/// is available and the client accepts Brotli or gzip encoding. Files
+	/// compressed on the fly are always served whole, and so this does not
+	/// apply to requests for byte ranges.
+	#[default = false]
+	pub compression:           bool,
+	
+	/// The file size at which to start compressing files on the fly, in KB.
+	/// Below this size, the saving is not worth the cost of compression.
+	#[default = 1]
+	pub compression_threshold: usize,
 }
 
 
@@ -5,29 +5,77 @@
 
 
 
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/handlers.rs"]
+mod tests;
+
+
+
 //		Packages																										
 
 use crate::app::config::LoadingBehavior;
 use super::{
+	config::{CacheControl, CacheRule, StaticFiles},
 	errors::AssetsError,
 	state::StateProvider,
 };
+use async_compression::{
+	Level,
+	tokio::bufread::{BrotliEncoder, GzipEncoder},
+};
 use axum::{
 	body::Body,
 	extract::State,
-	http::{HeaderValue, StatusCode, Uri, header},
+	http::{HeaderMap, HeaderValue, StatusCode, Uri, header},
 	response::{IntoResponse, Response},
 };
-use mime_guess::{self};
-use std::sync::Arc;
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD as BASE64};
+use core::{
+	hash::{BuildHasher as _, Hasher as _},
+	ops::RangeInclusive,
+	pin::Pin,
+};
+use include_dir::Dir;
+use mime_guess::{Mime, mime};
+use parking_lot::RwLock;
+use sha2::{Digest as _, Sha256};
+use std::{
+	collections::HashMap,
+	fs::Metadata,
+	hash::RandomState,
+	io::{Cursor, SeekFrom},
+	path::{Path, PathBuf},
+	sync::{Arc, LazyLock},
+	time::{SystemTime, UNIX_EPOCH},
+};
 use tokio::{
 	fs::File,
-	io::{AsyncReadExt as _, BufReader},
+	io::{AsyncBufRead, AsyncRead, AsyncReadExt as _, AsyncSeekExt as _, BufReader, Take, empty},
 };
 use tokio_util::io::ReaderStream;
 
 
 
+//		Constants																										
+
+/// The maximum number of ranges that will be served for a single request. If
+/// more are requested, the whole file is served instead, to avoid the cost of
+/// assembling a large number of small parts.
+const MAX_RANGES: usize = 16;
+
+/// The entity tags of packaged files, keyed by the address and length of their
+/// contents. These are calculated when each file is first requested, as the
+/// files cannot change while the application is running. Keying by the
+/// contents rather than the path means that different directories of packaged
+/// files, such as those of different application states, do not share tags.
+static PACKAGED_ETAGS: LazyLock<RwLock<HashMap<(usize, usize), String>>> = LazyLock::new(|| {
+	RwLock::new(HashMap::new())
+});
+
+
+
 //		Enums																											
 
 //		AssetContext															
@@ -44,15 +92,256 @@ pub enum AssetContext {
 
 
 
+//		AssetSource																
+/// The source of a static asset's contents.
+#[derive(Debug)]
+enum AssetSource {
+	/// A file in the local filesystem, opened for reading.
+	Local(File),
+	
+	/// A file baked into the binary.
+	Packaged(&'static [u8]),
+}
+
+//		Encoding																
+/// The content encodings that static assets can be served with.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Encoding {
+	/// Brotli compression.
+	Brotli,
+	
+	/// Gzip compression.
+	Gzip,
+}
+
+//󰭅		Encoding																
+impl Encoding {
+	/// All supported encodings, in order of preference.
+	const ALL: [Self; 2] = [Self::Brotli, Self::Gzip];
+	
+	//		extension															
+	/// The file extension used for precompressed files with this encoding.
+	const fn extension(self) -> &'static str {
+		match self {
+			Self::Brotli => ".br",
+			Self::Gzip   => ".gz",
+		}
+	}
+	
+	//		name																
+	/// The name of this encoding, as used in HTTP headers.
+	const fn name(self) -> &'static str {
+		match self {
+			Self::Brotli => "br",
+			Self::Gzip   => "gzip",
+		}
+	}
+}
+
+//		RangeRequest															
+/// The parts of a file that have been requested.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum RangeRequest {
+	/// The whole file, because no ranges were requested, or the request for
+	/// ranges has been ignored.
+	Whole,
+	
+	/// One or more byte ranges, which are all within the file.
+	Partial(Vec<RangeInclusive<u64>>),
+	
+	/// Byte ranges, none of which are within the file.
+	Unsatisfiable,
+}
+
+
+
+//		Structs																											
+
+//		Asset																	
+/// A static asset that has been located and opened for serving.
+#[derive(Debug)]
+struct Asset {
+	/// The source of the asset's contents.
+	source:        AssetSource,
+	
+	/// The length of the asset, in bytes.
+	length:        u64,
+	
+	/// The entity tag of the asset, if conditional requests are enabled.
+	etag:          Option<String>,
+	
+	/// The time at which the asset was last modified, if it is a local file
+	/// and conditional requests are enabled.
+	last_modified: Option<SystemTime>,
+}
+
+
+
 //		Functions																										
 
+//		accepted_encodings														
+/// Gets the encodings that a client accepts, in order of preference.
+/// 
+/// The `Accept-Encoding` header is parsed, taking account of quality values
+/// and the `*` wildcard, and encodings with a quality of zero are excluded.
+/// Where the client has no preference between encodings, Brotli is preferred
+/// over gzip, as it compresses better. If there is no header, no encodings are
+/// accepted.
+/// 
+/// # Parameters
+/// 
+/// * `headers` - The request headers.
+/// 
+fn accepted_encodings(headers: &HeaderMap) -> Vec<Encoding> {
+	let Some(header) = headers.get(header::ACCEPT_ENCODING).and_then(|value| value.to_str().ok()) else {
+		return vec![];
+	};
+	let preferences = header.split(',').filter_map(|item| {
+		let mut parts = item.split(';');
+		let name      = parts.next()?.trim().to_ascii_lowercase();
+		let quality   = parts
+			.find_map(|param| param.trim().strip_prefix("q="))
+			.map_or(Some(1.0_f32), |value| value.trim().parse::<f32>().ok())?
+		;
+		Some((name, quality))
+	}).collect::<Vec<_>>();
+	let quality_of = |names: &[&str]| preferences.iter()
+		.filter(|preference| names.contains(&preference.0.as_str()))
+		.map(|&(_, quality)| quality)
+		.reduce(f32::max)
+	;
+	let wildcard  = quality_of(&["*"]).unwrap_or(0.0);
+	let mut found = Encoding::ALL.into_iter().filter_map(|encoding| {
+		let quality = match encoding {
+			Encoding::Brotli => quality_of(&["br"]),
+			Encoding::Gzip   => quality_of(&["gzip", "x-gzip"]),
+		}.unwrap_or(wildcard);
+		(quality > 0.0).then_some((encoding, quality))
+	}).collect::<Vec<_>>();
+	found.sort_by(|&(_, a), &(_, b)| b.total_cmp(&a));
+	found.into_iter().map(|(encoding, _)| encoding).collect()
+}
+
+//		add_validators															
+/// Adds the `ETag` and `Last-Modified` headers to a response.
+/// 
+/// # Parameters
+/// 
+/// * `headers`       - The response headers.
+/// * `etag`          - The entity tag of the file, if known.
+/// * `last_modified` - The time at which the file was last modified, if known.
+/// 
+fn add_validators(headers: &mut HeaderMap, etag: Option<&str>, last_modified: Option<SystemTime>) {
+	if let Some(value) = etag.and_then(|tag| HeaderValue::from_str(tag).ok()) {
+		drop(headers.insert(header::ETAG, value));
+	}
+	if let Some(value) = last_modified.and_then(|time| HeaderValue::from_str(&httpdate::fmt_http_date(time)).ok()) {
+		drop(headers.insert(header::LAST_MODIFIED, value));
+	}
+}
+
+//		cache_control															
+/// Gets the `Cache-Control` header to send for an asset.
+/// 
+/// The first rule whose pattern matches the path of the asset is used, and if
+/// none match, the default is used. Empty values mean that no header is sent.
+/// 
+/// # Parameters
+/// 
+/// * `rules`   - The rules to check.
+/// * `default` - The value to use if no rule matches.
+/// * `path`    - The path of the asset, without a leading slash.
+/// 
+fn cache_control(rules: &[CacheRule], default: Option<&CacheControl>, path: &str) -> Option<HeaderValue> {
+	rules.iter()
+		.find(|rule| rule.pattern.matches(path))
+		.map_or(default, |rule| Some(&rule.value))
+		.and_then(CacheControl::to_header)
+}
+
+//		compressed_content														
+/// Creates a `200 OK` response containing a file compressed on the fly.
+/// 
+/// The compressed file is always streamed, as its length is not known in
+/// advance. Brotli compression uses a moderate quality level, as the highest
+/// levels are too slow to be used on every request.
+/// 
+/// # Parameters
+/// 
+/// * `source`       - The source of the file.
+/// * `content_type` - The content type of the file.
+/// * `encoding`     - The encoding to compress the file with.
+/// * `config`       - The configuration for serving static files.
+/// 
+/// # Errors
+/// 
+/// If the response cannot be built, an error will be returned.
+/// 
+fn compressed_content(
+	source:       AssetSource,
+	content_type: HeaderValue,
+	encoding:     Encoding,
+	config:       &StaticFiles,
+) -> Result<Response, AssetsError> {
+	let reader: Pin<Box<dyn AsyncBufRead + Send>> = match source {
+		AssetSource::Local(file)        => Box::pin(BufReader::with_capacity(config.read_buffer.saturating_mul(1_024), file)),
+		AssetSource::Packaged(contents) => Box::pin(contents),
+	};
+	let encoder: Pin<Box<dyn AsyncRead + Send>> = match encoding {
+		Encoding::Brotli => Box::pin(BrotliEncoder::with_quality(reader, Level::Precise(4))),
+		Encoding::Gzip   => Box::pin(GzipEncoder::new(reader)),
+	};
+	Response::builder()
+		.status(StatusCode::OK)
+		.header(header::CONTENT_TYPE, content_type)
+		.body(Body::from_stream(ReaderStream::with_capacity(encoder, config.stream_buffer.saturating_mul(1_024))))
+		.map_err(AssetsError::FailedToBuildResponseBody)
+}
+
+//		encoded_etag															
+/// Creates an entity tag for a file that is compressed on the fly.
+/// 
+/// The tag is derived from that of the uncompressed file, so that each
+/// encoding has a distinct tag. It is a weak validator, as the compressed
+/// output is not guaranteed to be byte-for-byte identical each time.
+/// 
+/// # Parameters
+/// 
+/// * `etag`     - The entity tag of the uncompressed file.
+/// * `encoding` - The encoding used.
+/// 
+fn encoded_etag(etag: &str, encoding: Encoding) -> String {
+	format!(r#"W/"{}-{}""#, etag.trim_start_matches("W/").trim_matches('"'), encoding.name())
+}
+
+//		etag_matches															
+/// Checks whether an `If-None-Match` header matches an entity tag.
+/// 
+/// The header may contain a list of tags, or `*`, which matches any tag. Weak
+/// comparison is used, as is required for `If-None-Match`, meaning that any
+/// `W/` prefix is ignored.
+/// 
+/// # Parameters
+/// 
+/// * `header` - The value of the `If-None-Match` header.
+/// * `etag`   - The entity tag of the file, if known.
+/// 
+fn etag_matches(header: &str, etag: Option<&str>) -> bool {
+	header.split(',').map(str::trim).any(|tag| {
+		tag == "*" || etag.is_some_and(|current| {
+			tag.trim_start_matches("W/") == current.trim_start_matches("W/")
+		})
+	})
+}
+
 //		get_protected_static_asset												
 /// Serves protected static assets.
 /// 
 /// # Parameters
 /// 
-/// * `state` - The application state.
-/// * `uri`   - The URI of the asset.
+/// * `state`   - The application state.
+/// * `uri`     - The URI of the asset.
+/// * `headers` - The request headers.
 /// 
 /// # Errors
 /// 
@@ -62,8 +351,9 @@ pub enum AssetContext {
 pub async fn get_protected_static_asset<SP: StateProvider>(
 	State(state): State<Arc<SP>>,
 	uri:          Uri,
+	headers:      HeaderMap,
 ) -> impl IntoResponse {
-	get_static_asset(state, uri, AssetContext::Protected).await
+	get_static_asset(state, uri, &headers, AssetContext::Protected).await
 }
 
 //		get_public_static_asset													
@@ -71,8 +361,9 @@ pub async fn get_protected_static_asset<SP: StateProvider>(
 /// 
 /// # Parameters
 /// 
-/// * `state` - The application state.
-/// * `uri`   - The URI of the asset.
+/// * `state`   - The application state.
+/// * `uri`     - The URI of the asset.
+/// * `headers` - The request headers.
 /// 
 /// # Errors
 /// 
@@ -82,17 +373,45 @@ pub async fn get_protected_static_asset<SP: StateProvider>(
 pub async fn get_public_static_asset<SP: StateProvider>(
 	State(state): State<Arc<SP>>,
 	uri:          Uri,
+	headers:      HeaderMap,
 ) -> impl IntoResponse {
-	get_static_asset(state, uri, AssetContext::Public).await
+	get_static_asset(state, uri, &headers, AssetContext::Public).await
 }
 
 //		get_static_asset														
 /// Serves static assets.
 /// 
+/// If [conditional requests](super::config::StaticFiles#structfield.conditional_requests)
+/// are enabled, each response carries an `ETag` header, along with a
+/// `Last-Modified` header for local files, and requests that supply a matching
+/// `If-None-Match` or `If-Modified-Since` header receive an empty `304`
+/// response. See [`is_not_modified()`] for details.
+/// 
+/// Byte ranges are supported, as advertised by the `Accept-Ranges` header, so
+/// that clients can resume downloads and seek within media. A single range is
+/// served as a `206` response with a `Content-Range` header, and multiple
+/// ranges as a `multipart/byteranges` body. If none of the ranges are within
+/// the file, a `416` response is returned. See [`range_request()`] for
+/// details.
+/// 
+/// If [precompressed](super::config::StaticFiles#structfield.precompressed)
+/// files are enabled, a `.br` or `.gz` file alongside the requested file is
+/// served in its place, if the client accepts that encoding. Otherwise, if
+/// [compression](super::config::StaticFiles#structfield.compression) is
+/// enabled, text files are compressed on the fly. In both cases the response
+/// carries a `Vary: Accept-Encoding` header, so that caches store each
+/// encoding separately.
+/// 
+/// Public assets can also be requested using their
+/// [fingerprinted](super::manifest) paths, which are resolved back to the real
+/// files using the [`AssetManifest`](super::manifest::AssetManifest), and
+/// served with a long-lived `Cache-Control` header.
+/// 
 /// # Parameters
 /// 
 /// * `state`   - The application state.
 /// * `uri`     - The URI of the asset.
+/// * `headers` - The request headers.
 /// * `context` - The protection context of the asset to serve.
 /// 
 /// # Errors
@@ -103,63 +422,658 @@ pub async fn get_public_static_asset<SP: StateProvider>(
 async fn get_static_asset<SP: StateProvider>(
 	state:   Arc<SP>,
 	uri:     Uri,
+	headers: &HeaderMap,
 	context: AssetContext
-) -> Result<impl IntoResponse, AssetsError> {
-	let path      = uri.path().trim_start_matches('/');
+) -> Result<Response, AssetsError> {
+	let requested             = uri.path().trim_start_matches('/');
+	let (path, fingerprinted) = match context {
+		AssetContext::Public    => state.asset_manifest().original(requested).map_or((requested, false), |original| (original, true)),
+		AssetContext::Protected => (requested, false),
+	};
 	let mime_type = mime_guess::from_path(path).first_or_text_plain();
-	let (basedir, local_path, behavior) = match context {
-		AssetContext::Public => (
-			state.assets_dir(),
-			state.config().public_assets.local_path.join(path),
-			&state.config().public_assets.behavior
-		),
-		AssetContext::Protected => (
-			state.content_dir(),
-			state.config().protected_assets.local_path.join(path),
-			&state.config().protected_assets.behavior
-		),
+	let (basedir, local_path, is_local, cache) = resolve_asset(&*state, context, path, fingerprinted);
+	let config                     = &state.config().static_files;
+	let accepted                   = accepted_encodings(headers);
+	let (mut encoding, mut varies) = if config.precompressed {
+		precompressed_encoding(&accepted, |candidate| if is_local {
+			sibling_path(&local_path, candidate).exists()
+		} else {
+			basedir.get_file(format!("{path}{}", candidate.extension())).is_some()
+		})
+	} else {
+		(None, false)
 	};
-	let is_local = match *behavior {
-		LoadingBehavior::Deny       => false,
-		LoadingBehavior::Supplement => basedir.get_file(path).is_none(),
-		LoadingBehavior::Override   => local_path.exists(),
+	let (served_path, served_local) = match encoding {
+		Some(chosen) => (format!("{path}{}", chosen.extension()), sibling_path(&local_path, chosen)),
+		None         => (path.to_owned(), local_path),
 	};
-	let body = if is_local {
+	let Asset { source, length, mut etag, last_modified } = load_asset(
+		&basedir, &served_path, served_local.clone(), is_local, config.conditional_requests,
+	).await?;
+	let mut compress = None;
+	if encoding.is_none() && config.compression && is_compressible(&mime_type)
+		&& length >= config.compression_threshold.saturating_mul(1_024) as u64
+	{
+		//	The response depends upon the Accept-Encoding header even when it is
+		//	not compressed, so caches must be told to vary on it either way
+		varies = true;
+		if !headers.contains_key(header::RANGE) {
+			compress = accepted.first().copied();
+			encoding = compress;
+			etag     = etag.zip(compress).map(|(tag, chosen)| encoded_etag(&tag, chosen));
+		}
+	}
+	let mut response = if config.conditional_requests && is_not_modified(headers, etag.as_deref(), last_modified) {
+		not_modified(etag.as_deref(), last_modified, cache)?
+	} else {
+		let content_type = HeaderValue::from_str(mime_type.as_ref())
+			.map_err(|_err| AssetsError::InvalidMimeTypeHeader(mime_type))?
+		;
+		let mut response = match (compress, range_request(headers, length, etag.as_deref(), last_modified)) {
+			(Some(chosen), _)                     => compressed_content(source, content_type, chosen, config)?,
+			(None, RangeRequest::Whole)           => whole_content(source, served_local, length, content_type, config).await?,
+			(None, RangeRequest::Partial(ranges)) => partial_content(source, &served_local, &ranges, length, content_type, config).await?,
+			(None, RangeRequest::Unsatisfiable)   => {
+				Response::builder()
+					.status(StatusCode::RANGE_NOT_SATISFIABLE)
+					.header(header::CONTENT_RANGE, format!("bytes */{length}"))
+					.body(Body::empty())
+					.map_err(AssetsError::FailedToBuildResponseBody)?
+			},
+		};
+		let response_headers = response.headers_mut();
+		if compress.is_none() {
+			drop(response_headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes")));
+		}
+		if let Some(chosen) = encoding {
+			drop(response_headers.insert(header::CONTENT_ENCODING, HeaderValue::from_static(chosen.name())));
+		}
+		add_validators(response_headers, etag.as_deref(), last_modified);
+		if let Some(value) = cache {
+			drop(response_headers.insert(header::CACHE_CONTROL, value));
+		}
+		response
+	};
+	if varies {
+		drop(response.headers_mut().insert(header::VARY, HeaderValue::from_static("Accept-Encoding")));
+	}
+	Ok(response)
+}
+
+//		if_range_matches														
+/// Checks whether an `If-Range` header matches the current file.
+/// 
+/// The header contains either an entity tag or a date. Entity tags must match
+/// using strong comparison, meaning that weak tags never match, and dates must
+/// match the file's modification time exactly, to the nearest second. If the
+/// header does not match, the whole file should be served instead of the
+/// requested ranges, as the client's partial copy is out of date.
+/// 
+/// # Parameters
+/// 
+/// * `header`        - The value of the `If-Range` header.
+/// * `etag`          - The entity tag of the file, if known.
+/// * `last_modified` - The time at which the file was last modified, if known.
+/// 
+fn if_range_matches(header: &str, etag: Option<&str>, last_modified: Option<SystemTime>) -> bool {
+	let value = header.trim();
+	if value.starts_with('"') || value.starts_with("W/") {
+		return !value.starts_with("W/") && etag.is_some_and(|current| current == value);
+	}
+	match (httpdate::parse_http_date(value).ok().and_then(unix_seconds), last_modified.and_then(unix_seconds)) {
+		(Some(date), Some(modified)) => date == modified,
+		_                            => false,
+	}
+}
+
+//		is_compressible															
+/// Checks whether a file of a given type is worth compressing on the fly.
+/// 
+/// Text, JavaScript, JSON, and XML files, including types based on JSON or
+/// XML such as SVG images, compress well. Most other types, such as images,
+/// fonts, and archives, are already compressed.
+/// 
+/// # Parameters
+/// 
+/// * `mime_type` - The content type of the file.
+/// 
+fn is_compressible(mime_type: &Mime) -> bool {
+	mime_type.type_() == mime::TEXT
+		|| (mime_type.type_() == mime::APPLICATION && matches!(mime_type.subtype().as_str(), "javascript" | "json" | "xml"))
+		|| mime_type.suffix().is_some_and(|suffix| suffix == mime::JSON || suffix == mime::XML)
+}
+
+//		is_not_modified															
+/// Checks whether a conditional request can be answered with a `304`.
+/// 
+/// If the request has an `If-None-Match` header, the outcome depends solely
+/// upon whether it matches the entity tag, and any `If-Modified-Since` header
+/// is ignored, as required by RFC 9110. Otherwise, if the request has an
+/// `If-Modified-Since` header, the file is considered unmodified if it has not
+/// changed since that time, to the nearest second. Unparseable headers are
+/// ignored.
+/// 
+/// # Parameters
+/// 
+/// * `headers`       - The request headers.
+/// * `etag`          - The entity tag of the file, if known.
+/// * `last_modified` - The time at which the file was last modified, if known.
+/// 
+fn is_not_modified(headers: &HeaderMap, etag: Option<&str>, last_modified: Option<SystemTime>) -> bool {
+	if let Some(if_none_match) = headers.get(header::IF_NONE_MATCH) {
+		return if_none_match.to_str().is_ok_and(|value| etag_matches(value, etag));
+	}
+	let since = headers.get(header::IF_MODIFIED_SINCE)
+		.and_then(|value| value.to_str().ok())
+		.and_then(|value| httpdate::parse_http_date(value).ok())
+	;
+	match (since, last_modified.and_then(unix_seconds)) {
+		(Some(time), Some(modified)) => unix_seconds(time).is_some_and(|seconds| modified <= seconds),
+		_                            => false,
+	}
+}
+
+//		load_asset																
+/// Locates a static asset and opens it for serving.
+/// 
+/// # Parameters
+/// 
+/// * `basedir`              - The directory of packaged files.
+/// * `path`                 - The path of the asset, without a leading slash.
+/// * `local_path`           - The path of the asset in the local filesystem.
+/// * `is_local`             - Whether to serve the local file rather than the
+///                            packaged one.
+/// * `conditional_requests` - Whether to get the validators for the asset.
+/// 
+/// # Errors
+/// 
+/// If the asset is not found, or cannot be opened, an error will be returned.
+/// 
+async fn load_asset(
+	basedir:              &Dir<'static>,
+	path:                 &str,
+	local_path:           PathBuf,
+	is_local:             bool,
+	conditional_requests: bool,
+) -> Result<Asset, AssetsError> {
+	if is_local {
 		if !local_path.exists() {
 			return Err(AssetsError::LocalFileNotFound(local_path));
 		}
-		let mut file = File::open(&local_path).await
+		let file     = File::open(&local_path).await
 			.map_err(|err| AssetsError::FailedToOpenLocalFile(local_path.clone(), err))?
 		;
 		let metadata = file.metadata().await
-			.map_err(|err| AssetsError::FailedToGetLocalFileMetadata(local_path.clone(), err))?
+			.map_err(|err| AssetsError::FailedToGetLocalFileMetadata(local_path, err))?
+		;
+		Ok(Asset {
+			source:        AssetSource::Local(file),
+			length:        metadata.len(),
+			etag:          conditional_requests.then(|| local_etag(&metadata)).flatten(),
+			last_modified: conditional_requests.then(|| metadata.modified().ok()).flatten(),
+		})
+	} else {
+		let contents = basedir.get_file(path)
+			.ok_or_else(|| AssetsError::PackagedFileNotFound(path.to_owned()))?
+			.contents()
 		;
-		let config = &state.config().static_files;
-		if metadata.len() > config.stream_threshold.saturating_mul(1_024) as u64 {
-			let reader = BufReader::with_capacity(config.read_buffer.saturating_mul(1_024), file);
-			let stream = ReaderStream::with_capacity(reader, config.stream_buffer.saturating_mul(1_024));
-			Body::from_stream(stream)
+		Ok(Asset {
+			source:        AssetSource::Packaged(contents),
+			length:        contents.len() as u64,
+			etag:          conditional_requests.then(|| packaged_etag(contents)),
+			last_modified: None,
+		})
+	}
+}
+
+//		local_etag																
+/// Creates an entity tag for a local file.
+/// 
+/// The tag is derived from the file's size and modification time, so that it
+/// can be calculated without reading the file. If the modification time is not
+/// available, there is no tag.
+/// 
+/// # Parameters
+/// 
+/// * `metadata` - The file's metadata.
+/// 
+fn local_etag(metadata: &Metadata) -> Option<String> {
+	let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+	Some(format!(r#""{:x}-{:x}-{:x}""#, modified.as_secs(), modified.subsec_nanos(), metadata.len()))
+}
+
+//		local_range																
+/// Opens a byte range of a local file for reading.
+/// 
+/// # Parameters
+/// 
+/// * `file`  - The file, which is moved to the start of the range.
+/// * `path`  - The path of the file, for error reporting.
+/// * `range` - The range to read.
+/// 
+/// # Errors
+/// 
+/// If the file cannot be read from the start of the range, an error will be
+/// returned.
+/// 
+async fn local_range(mut file: File, path: &Path, range: &RangeInclusive<u64>) -> Result<Take<File>, AssetsError> {
+	let _position = file.seek(SeekFrom::Start(*range.start())).await
+		.map_err(|err| AssetsError::FailedToReadLocalFile(path.to_owned(), err))?
+	;
+	Ok(file.take(range_length(range)))
+}
+
+//		not_modified															
+/// Creates a `304 Not Modified` response.
+/// 
+/// This carries the same validators and `Cache-Control` header as a full
+/// response would, so that the client can update its cached copy.
+/// 
+/// # Parameters
+/// 
+/// * `etag`          - The entity tag of the file, if known.
+/// * `last_modified` - The time at which the file was last modified, if known.
+/// * `cache`         - The `Cache-Control` header for the file, if any.
+/// 
+/// # Errors
+/// 
+/// If the response cannot be built, an error will be returned.
+/// 
+fn not_modified(
+	etag:          Option<&str>,
+	last_modified: Option<SystemTime>,
+	cache:         Option<HeaderValue>,
+) -> Result<Response, AssetsError> {
+	let mut response = Response::builder()
+		.status(StatusCode::NOT_MODIFIED)
+		.body(Body::empty())
+		.map_err(AssetsError::FailedToBuildResponseBody)?
+	;
+	add_validators(response.headers_mut(), etag, last_modified);
+	if let Some(value) = cache {
+		drop(response.headers_mut().insert(header::CACHE_CONTROL, value));
+	}
+	Ok(response)
+}
+
+//		packaged_etag															
+/// Gets the entity tag for a packaged file.
+/// 
+/// The tag is a hash of the file's contents, and so is a strong validator. It
+/// is calculated when the file is first requested, and then cached.
+/// 
+/// # Parameters
+/// 
+/// * `contents` - The contents of the file.
+/// 
+fn packaged_etag(contents: &'static [u8]) -> String {
+	let key = (contents.as_ptr().addr(), contents.len());
+	if let Some(etag) = PACKAGED_ETAGS.read().get(&key) {
+		return etag.clone();
+	}
+	let etag = format!(r#""{}""#, BASE64.encode(Sha256::digest(contents)));
+	drop(PACKAGED_ETAGS.write().insert(key, etag.clone()));
+	etag
+}
+
+//		packaged_range															
+/// Gets a byte range of a packaged file.
+/// 
+/// # Parameters
+/// 
+/// * `contents` - The contents of the file.
+/// * `range`    - The range to get, which must be within the file.
+/// 
+fn packaged_range(contents: &'static [u8], range: &RangeInclusive<u64>) -> &'static [u8] {
+	usize::try_from(*range.start()).ok()
+		.zip(usize::try_from(*range.end()).ok())
+		.and_then(|(start, end)| contents.get(start..=end))
+		.unwrap_or_default()
+}
+
+//		parse_ranges															
+/// Parses the value of a `Range` header.
+/// 
+/// Only byte ranges are supported. Each range may be given as `first-last`,
+/// `first-`, meaning from that byte to the end of the file, or `-count`,
+/// meaning the final number of bytes. Ranges that extend beyond the end of the
+/// file are shortened, and ranges that start beyond it are discarded.
+/// 
+/// If the header is not valid, uses another unit, or requests more than
+/// [`MAX_RANGES`] ranges, it is ignored, and the whole file is requested, as
+/// allowed by RFC 9110.
+/// 
+/// # Parameters
+/// 
+/// * `header` - The value of the `Range` header.
+/// * `length` - The length of the file.
+/// 
+fn parse_ranges(header: &str, length: u64) -> RangeRequest {
+	let Some((unit, specs)) = header.split_once('=') else {
+		return RangeRequest::Whole;
+	};
+	if !unit.trim().eq_ignore_ascii_case("bytes") {
+		return RangeRequest::Whole;
+	}
+	let mut ranges = vec![];
+	for (count, spec) in specs.split(',').map(str::trim).filter(|spec| !spec.is_empty()).enumerate() {
+		if count >= MAX_RANGES {
+			return RangeRequest::Whole;
+		}
+		let Some((first, last)) = spec.split_once('-') else {
+			return RangeRequest::Whole;
+		};
+		let range = if first.is_empty() {
+			let Ok(suffix) = last.parse::<u64>() else {
+				return RangeRequest::Whole;
+			};
+			(suffix > 0 && length > 0).then(|| length.saturating_sub(suffix)..=length.saturating_sub(1))
 		} else {
-			let mut contents = vec![];
-			let _count       = file.read_to_end(&mut contents).await
-				.map_err(|err| AssetsError::FailedToReadLocalFile(local_path, err))?
-			;
-			Body::from(contents)
+			let Ok(start) = first.parse::<u64>() else {
+				return RangeRequest::Whole;
+			};
+			let end = if last.is_empty() {
+				u64::MAX
+			} else {
+				match last.parse::<u64>() {
+					Ok(end) if end >= start => end,
+					_                       => return RangeRequest::Whole,
+				}
+			};
+			(start < length).then(|| start..=end.min(length.saturating_sub(1)))
+		};
+		ranges.extend(range);
+	}
+	if ranges.is_empty() {
+		//	A header that names no ranges at all is invalid, rather than unsatisfiable
+		if specs.split(',').all(|spec| spec.trim().is_empty()) {
+			return RangeRequest::Whole;
 		}
-	} else {
-		Body::from(
-			basedir.get_file(path)
-				.ok_or_else(|| AssetsError::PackagedFileNotFound(path.to_owned()))?
-				.contents()
-		)
+		return RangeRequest::Unsatisfiable;
+	}
+	RangeRequest::Partial(ranges)
+}
+
+//		partial_content															
+/// Creates a `206 Partial Content` response.
+/// 
+/// A single range is served as the body of the response, described by the
+/// `Content-Range` header. Multiple ranges are served as a
+/// `multipart/byteranges` body, with each part carrying its own
+/// `Content-Type` and `Content-Range` headers. Ranges of local files are
+/// streamed, and ranges of packaged files are served from memory.
+/// 
+/// # Parameters
+/// 
+/// * `source`       - The source of the file.
+/// * `path`         - The path of the file, if local.
+/// * `ranges`       - The ranges to serve, which must all be within the file.
+/// * `length`       - The length of the file.
+/// * `content_type` - The content type of the file.
+/// * `config`       - The configuration for serving static files.
+/// 
+/// # Errors
+/// 
+/// If the file cannot be read, or the response cannot be built, an error will
+/// be returned.
+/// 
+async fn partial_content(
+	source:       AssetSource,
+	path:         &Path,
+	ranges:       &[RangeInclusive<u64>],
+	length:       u64,
+	content_type: HeaderValue,
+	config:       &StaticFiles,
+) -> Result<Response, AssetsError> {
+	let read_buffer   = config.read_buffer.saturating_mul(1_024);
+	let stream_buffer = config.stream_buffer.saturating_mul(1_024);
+	if let (Some(range), 1) = (ranges.first(), ranges.len()) {
+		let body = match source {
+			AssetSource::Local(file)        => {
+				let reader = BufReader::with_capacity(read_buffer, local_range(file, path, range).await?);
+				Body::from_stream(ReaderStream::with_capacity(reader, stream_buffer))
+			},
+			AssetSource::Packaged(contents) => Body::from(packaged_range(contents, range)),
+		};
+		return Response::builder()
+			.status(StatusCode::PARTIAL_CONTENT)
+			.header(header::CONTENT_TYPE,  content_type)
+			.header(header::CONTENT_RANGE, format!("bytes {}-{}/{length}", range.start(), range.end()))
+			.body(body)
+			.map_err(AssetsError::FailedToBuildResponseBody)
+		;
+	}
+	let boundary   = format!("{:016x}", RandomState::new().build_hasher().finish());
+	let part_type  = String::from_utf8_lossy(content_type.as_bytes()).into_owned();
+	let part_start = |range: &RangeInclusive<u64>| format!(
+		"\r\n--{boundary}\r\nContent-Type: {part_type}\r\nContent-Range: bytes {}-{}/{length}\r\n\r\n",
+		range.start(),
+		range.end(),
+	);
+	let end        = format!("\r\n--{boundary}--\r\n");
+	let body       = match source {
+		AssetSource::Local(file)        => {
+			drop(file);
+			let mut reader: Pin<Box<dyn AsyncRead + Send>> = Box::pin(empty());
+			for range in ranges {
+				let handle = File::open(path).await
+					.map_err(|err| AssetsError::FailedToOpenLocalFile(path.to_owned(), err))?
+				;
+				let part   = local_range(handle, path, range).await?;
+				reader     = Box::pin(reader.chain(Cursor::new(part_start(range))).chain(part));
+			}
+			reader = Box::pin(BufReader::with_capacity(read_buffer, reader.chain(Cursor::new(end))));
+			Body::from_stream(ReaderStream::with_capacity(reader, stream_buffer))
+		},
+		AssetSource::Packaged(contents) => {
+			let mut parts = vec![];
+			for range in ranges {
+				parts.extend_from_slice(part_start(range).as_bytes());
+				parts.extend_from_slice(packaged_range(contents, range));
+			}
+			parts.extend_from_slice(end.as_bytes());
+			Body::from(parts)
+		},
+	};
+	Response::builder()
+		.status(StatusCode::PARTIAL_CONTENT)
+		.header(header::CONTENT_TYPE, format!("multipart/byteranges; boundary={boundary}"))
+		.body(body)
+		.map_err(AssetsError::FailedToBuildResponseBody)
+}
+
+//		precompressed_encoding													
+/// Chooses a precompressed file to serve in place of the requested file.
+/// 
+/// The first of the accepted encodings for which a precompressed file exists
+/// is chosen. Whether any precompressed files exist is also returned, as in
+/// that case the response depends upon the `Accept-Encoding` header, even if
+/// the client does not accept any of the available encodings.
+/// 
+/// # Parameters
+/// 
+/// * `accepted` - The encodings accepted by the client, in order of
+///                preference.
+/// * `exists`   - A function that checks whether a precompressed file exists
+///                for an encoding.
+/// 
+fn precompressed_encoding<F>(accepted: &[Encoding], exists: F) -> (Option<Encoding>, bool)
+where
+	F: Fn(Encoding) -> bool,
+{
+	let available = Encoding::ALL.into_iter().filter(|&encoding| exists(encoding)).collect::<Vec<_>>();
+	(accepted.iter().copied().find(|encoding| available.contains(encoding)), !available.is_empty())
+}
+
+//		range_length															
+/// Gets the number of bytes in a range.
+/// 
+/// # Parameters
+/// 
+/// * `range` - The range.
+/// 
+const fn range_length(range: &RangeInclusive<u64>) -> u64 {
+	range.end().saturating_sub(*range.start()).saturating_add(1)
+}
+
+//		range_request															
+/// Determines which parts of a file have been requested.
+/// 
+/// If the request has no `Range` header, or has an `If-Range` header that does
+/// not [match](if_range_matches()) the current file, the whole file is
+/// requested. Otherwise, the `Range` header is [parsed](parse_ranges()).
+/// 
+/// # Parameters
+/// 
+/// * `headers`       - The request headers.
+/// * `length`        - The length of the file.
+/// * `etag`          - The entity tag of the file, if known.
+/// * `last_modified` - The time at which the file was last modified, if known.
+/// 
+fn range_request(
+	headers:       &HeaderMap,
+	length:        u64,
+	etag:          Option<&str>,
+	last_modified: Option<SystemTime>,
+) -> RangeRequest {
+	let Some(range) = headers.get(header::RANGE).and_then(|value| value.to_str().ok()) else {
+		return RangeRequest::Whole;
+	};
+	if let Some(if_range) = headers.get(header::IF_RANGE) {
+		if !if_range.to_str().is_ok_and(|value| if_range_matches(value, etag, last_modified)) {
+			return RangeRequest::Whole;
+		}
+	}
+	parse_ranges(range, length)
+}
+
+//		resolve_asset															
+/// Works out where to find a static asset, and how it should be cached.
+/// 
+/// Returns the directory of packaged files, the path of the asset in the local
+/// filesystem, whether to serve the local file rather than the packaged one,
+/// and the `Cache-Control` header to send, if any. Assets requested using
+/// their [fingerprinted](super::manifest) paths are given the
+/// [`fingerprint_cache_control`](super::config::PublicAssets#structfield.fingerprint_cache_control)
+/// header, regardless of any cache rules, and are always served from the
+/// packaged files, as the fingerprint is a hash of the packaged contents.
+/// 
+/// # Parameters
+/// 
+/// * `state`         - The application state.
+/// * `context`       - The protection context of the asset.
+/// * `path`          - The path of the asset, without a leading slash.
+/// * `fingerprinted` - Whether the asset was requested using its
+///                     fingerprinted path.
+/// 
+fn resolve_asset<SP: StateProvider>(
+	state:         &SP,
+	context:       AssetContext,
+	path:          &str,
+	fingerprinted: bool,
+) -> (Arc<Dir<'static>>, PathBuf, bool, Option<HeaderValue>) {
+	let config = state.config();
+	let (basedir, local_path, behavior, cache) = match context {
+		AssetContext::Public    => (
+			state.assets_dir(),
+			config.public_assets.local_path.join(path),
+			&config.public_assets.behavior,
+			if fingerprinted {
+				cache_control(&[], config.public_assets.fingerprint_cache_control.as_ref(), path)
+			} else {
+				cache_control(&config.public_assets.cache_rules, config.public_assets.cache_control.as_ref(), path)
+			},
+		),
+		AssetContext::Protected => (
+			state.content_dir(),
+			config.protected_assets.local_path.join(path),
+			&config.protected_assets.behavior,
+			cache_control(&config.protected_assets.cache_rules, config.protected_assets.cache_control.as_ref(), path),
+		),
+	};
+	let is_local = !fingerprinted && match *behavior {
+		LoadingBehavior::Deny       => false,
+		LoadingBehavior::Supplement => basedir.get_file(path).is_none(),
+		LoadingBehavior::Override   => local_path.exists(),
+	};
+	(basedir, local_path, is_local, cache)
+}
+
+//		sibling_path															
+/// Gets the path of the precompressed version of a local file.
+/// 
+/// # Parameters
+/// 
+/// * `path`     - The path of the file.
+/// * `encoding` - The encoding of the precompressed file.
+/// 
+fn sibling_path(path: &Path, encoding: Encoding) -> PathBuf {
+	let mut name = path.as_os_str().to_owned();
+	name.push(encoding.extension());
+	PathBuf::from(name)
+}
+
+//		unix_seconds															
+/// Converts a time to whole seconds since the Unix epoch.
+/// 
+/// HTTP dates have a resolution of one second, so times must be truncated
+/// before they are compared with them.
+/// 
+/// # Parameters
+/// 
+/// * `time` - The time to convert.
+/// 
+fn unix_seconds(time: SystemTime) -> Option<u64> {
+	time.duration_since(UNIX_EPOCH).ok().map(|duration| duration.as_secs())
+}
+
+//		whole_content															
+/// Creates a `200 OK` response containing the whole of a file.
+/// 
+/// Packaged files are served directly from memory. Local files are read into
+/// memory and served all at once, unless they are larger than the configured
+/// [`stream_threshold`](StaticFiles#structfield.stream_threshold), in which
+/// case they are streamed.
+/// 
+/// # Parameters
+/// 
+/// * `source`       - The source of the file.
+/// * `path`         - The path of the file, if local.
+/// * `length`       - The length of the file.
+/// * `content_type` - The content type of the file.
+/// * `config`       - The configuration for serving static files.
+/// 
+/// # Errors
+/// 
+/// If the file cannot be read, or the response cannot be built, an error will
+/// be returned.
+/// 
+async fn whole_content(
+	source:       AssetSource,
+	path:         PathBuf,
+	length:       u64,
+	content_type: HeaderValue,
+	config:       &StaticFiles,
+) -> Result<Response, AssetsError> {
+	let body = match source {
+		AssetSource::Local(mut file)    => {
+			if length > config.stream_threshold.saturating_mul(1_024) as u64 {
+				let reader = BufReader::with_capacity(config.read_buffer.saturating_mul(1_024), file);
+				let stream = ReaderStream::with_capacity(reader, config.stream_buffer.saturating_mul(1_024));
+				Body::from_stream(stream)
+			} else {
+				let mut contents = vec![];
+				let _count       = file.read_to_end(&mut contents).await
+					.map_err(|err| AssetsError::FailedToReadLocalFile(path, err))?
+				;
+				Body::from(contents)
+			}
+		},
+		AssetSource::Packaged(contents) => Body::from(contents),
 	};
 	Response::builder()
 		.status(StatusCode::OK)
-		.header(
-			header::CONTENT_TYPE,
-			HeaderValue::from_str(mime_type.as_ref())
-				.map_err(|_err| AssetsError::InvalidMimeTypeHeader(mime_type))?
-		)
+		.header(header::CONTENT_TYPE, content_type)
 		.body(body)
 		.map_err(AssetsError::FailedToBuildResponseBody)
 }
@@ -0,0 +1,212 @@
+//! Fingerprinted URLs for static assets.
+//! 
+//! To allow static assets to be cached indefinitely, they can be referred to
+//! using URLs that contain a hash of their contents, such as
+//! `/css/styles.3f2a9c0d81b4e657.css` for `css/styles.css`. When a file
+//! changes, so does its URL, and so browsers fetch the new version instead of
+//! using the one that they have cached.
+//! 
+//! The [`AssetManifest`] holds the hashes of the packaged assets, and is
+//! provided by the application state. It is used by
+//! [`get_public_static_asset()`](super::handlers::get_public_static_asset())
+//! to resolve fingerprinted names back to the real files, which are served
+//! with the configured
+//! [`fingerprint_cache_control`](super::config::PublicAssets#structfield.fingerprint_cache_control)
+//! header. The unfingerprinted URLs continue to work as before.
+//! 
+//! When the `tera` feature is enabled, the manifest can also be registered
+//! with Tera as a function, so that templates can generate fingerprinted URLs:
+//! 
+//! ```tera
+//! <link rel="stylesheet" href="{{ asset_url(path="css/styles.css") }}">
+//! ```
+//! 
+//! Only packaged files are fingerprinted, as local files can change while the
+//! application is running. Fingerprinted URLs are therefore always served from
+//! the packaged files, even when a local file would otherwise override them.
+//! Precompressed `.br` and `.gz` files are not
+//! fingerprinted themselves, but are still served in place of the files they
+//! belong to.
+
+
+
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/manifest.rs"]
+mod tests;
+
+
+
+//		Packages																										
+
+use include_dir::{Dir, DirEntry};
+use sha2::{Digest as _, Sha256};
+use std::collections::HashMap;
+
+#[cfg(feature = "tera")]
+use ::tera::{Error as TeraError, Function, Result as TeraResult, Value};
+
+
+
+//		Constants																										
+
+/// The name under which the [`AssetManifest`] is conventionally registered as
+/// a Tera function.
+pub const ASSET_URL_FUNCTION: &str = "asset_url";
+
+/// The file extensions of precompressed files, which are not fingerprinted.
+const PRECOMPRESSED_EXTENSIONS: [&str; 2] = ["br", "gz"];
+
+
+
+//		Structs																											
+
+//		AssetManifest															
+/// The content hashes of the packaged static assets.
+/// 
+/// This maps the path of each packaged asset to its fingerprinted path, and
+/// back again. Paths are relative to the root of the assets, without a leading
+/// slash. An empty manifest, as created by [`Default`], does not fingerprint
+/// anything.
+/// 
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AssetManifest {
+	//		Private properties													
+	/// The fingerprinted paths, keyed by the paths of the assets.
+	fingerprinted: HashMap<String, String>,
+	
+	/// The paths of the assets, keyed by their fingerprinted paths.
+	originals:     HashMap<String, String>,
+}
+
+//󰭅		AssetManifest															
+impl AssetManifest {
+	//		new																	
+	/// Creates a manifest of the assets in a directory.
+	/// 
+	/// Each file is read and hashed, and so this should be done once, when the
+	/// application starts.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `dir` - The directory containing the packaged static assets.
+	/// 
+	#[must_use]
+	pub fn new(dir: &Dir<'_>) -> Self {
+		let mut manifest = Self::default();
+		manifest.add_dir(dir);
+		manifest
+	}
+	
+	//		add_dir																
+	/// Adds the assets in a directory, and its subdirectories, to the
+	/// manifest.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `dir` - The directory to add.
+	/// 
+	fn add_dir(&mut self, dir: &Dir<'_>) {
+		for entry in dir.entries() {
+			match *entry {
+				DirEntry::Dir(ref subdir) => self.add_dir(subdir),
+				DirEntry::File(ref file)  => {
+					let Some(path) = file.path().to_str() else {
+						continue;
+					};
+					if PRECOMPRESSED_EXTENSIONS.iter().any(|&extension| {
+						path.rsplit_once('.').is_some_and(|(_, suffix)| suffix == extension)
+					}) {
+						continue;
+					}
+					let fingerprinted = fingerprint(path, file.contents());
+					drop(self.originals.insert(fingerprinted.clone(), path.to_owned()));
+					drop(self.fingerprinted.insert(path.to_owned(), fingerprinted));
+				},
+			}
+		}
+	}
+	
+	//		fingerprinted														
+	/// Gets the fingerprinted path of an asset.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `path` - The path of the asset, without a leading slash.
+	/// 
+	#[must_use]
+	pub fn fingerprinted(&self, path: &str) -> Option<&str> {
+		self.fingerprinted.get(path).map(String::as_str)
+	}
+	
+	//		original															
+	/// Gets the path of the asset that a fingerprinted path refers to.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `fingerprinted` - The fingerprinted path, without a leading slash.
+	/// 
+	#[must_use]
+	pub fn original(&self, fingerprinted: &str) -> Option<&str> {
+		self.originals.get(fingerprinted).map(String::as_str)
+	}
+	
+	//		url																	
+	/// Gets the URL to use for an asset.
+	/// 
+	/// If the asset is in the manifest, this is its fingerprinted path.
+	/// Otherwise, the path is used as it is, so that local assets and assets
+	/// that do not exist still produce a usable URL.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `path` - The path of the asset, with or without a leading slash.
+	/// 
+	#[must_use]
+	pub fn url(&self, path: &str) -> String {
+		let relative = path.trim_start_matches('/');
+		format!("/{}", self.fingerprinted(relative).unwrap_or(relative))
+	}
+}
+
+//󰭅		Function																
+#[cfg(feature = "tera")]
+impl Function for AssetManifest {
+	//		call																
+	fn call(&self, args: &HashMap<String, Value>) -> TeraResult<Value> {
+		let path = args.get("path").and_then(Value::as_str).ok_or_else(|| {
+			TeraError::msg(format!("The {ASSET_URL_FUNCTION} function requires a `path` argument"))
+		})?;
+		Ok(Value::String(self.url(path)))
+	}
+}
+
+
+
+//		Functions																										
+
+//		fingerprint																
+/// Creates the fingerprinted path of a file.
+/// 
+/// The first 64 bits of a SHA-256 hash of the file's contents are inserted, in
+/// hexadecimal, before the file's extension, or appended if it has none. For
+/// instance, `css/styles.css` becomes `css/styles.3f2a9c0d81b4e657.css`.
+/// 
+/// # Parameters
+/// 
+/// * `path`     - The path of the file.
+/// * `contents` - The contents of the file.
+/// 
+fn fingerprint(path: &str, contents: &[u8]) -> String {
+	let digest = Sha256::digest(contents);
+	let hash   = digest.first_chunk::<8>().map_or(0, |&bytes| u64::from_be_bytes(bytes));
+	match path.rsplit_once('.') {
+		Some((stem, extension)) if !extension.contains('/') && !stem.is_empty() && !stem.ends_with('/') => {
+			format!("{stem}.{hash:016x}.{extension}")
+		},
+		_ => format!("{path}.{hash:016x}"),
+	}
+}
+
+
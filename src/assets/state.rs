@@ -4,7 +4,10 @@
 
 //		Packages																										
 
-use super::config::Config;
+use super::{
+	config::Config,
+	manifest::AssetManifest,
+};
 use include_dir::Dir;
 use std::sync::Arc;
 
@@ -26,6 +29,11 @@ pub trait StateProvider: Send + Sync + 'static {
 	//		content_dir															
 	/// The directory containing the Markdown content.
 	fn content_dir(&self) -> Arc<Dir<'static>>;
+	
+	//		asset_manifest														
+	/// The manifest of content hashes for the static assets, used to serve
+	/// [fingerprinted](super::manifest) URLs.
+	fn asset_manifest(&self) -> &AssetManifest;
 }
 
 
@@ -0,0 +1,47 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use serde_json::{from_value, json, to_value};
+
+
+
+//		Tests																											
+
+//		cache_control__deserialize												
+#[test]
+fn cache_control__deserialize() {
+	let value: CacheControl = from_value(json!("no-cache")).unwrap();
+	assert_eq!(value,                                              CacheControl::from_static("no-cache"));
+	assert_eq!(to_value(&value).unwrap(),                          json!("no-cache"));
+	assert!(from_value::<CacheControl>(json!("no-cache\nfoo")).is_err());
+}
+
+//		cache_control__to_header												
+#[test]
+fn cache_control__to_header() {
+	assert_eq!(CacheControl::from_static("private").to_header().unwrap(), "private");
+	assert_eq!(CacheControl::from_static("").to_header(),                 None);
+}
+
+//		cache_pattern__deserialize												
+#[test]
+fn cache_pattern__deserialize() {
+	let pattern: CachePattern = from_value(json!("css/*.css")).unwrap();
+	assert!(pattern.matches("css/styles.css"));
+	assert!(!pattern.matches("js/app.js"));
+	assert_eq!(to_value(&pattern).unwrap(), json!("css/*.css"));
+	assert!(from_value::<CachePattern>(json!("[invalid")).is_err());
+}
+
+//		config__deserialize														
+#[test]
+fn config__deserialize() {
+	let mut config = to_value(Config::default()).unwrap();
+	config["public"]["cache_rules"] = json!([{ "pattern": "[invalid", "value": "no-cache" }]);
+	assert!(from_value::<Config>(config).is_err());
+	assert_eq!(from_value::<Config>(to_value(Config::default()).unwrap()).unwrap(), Config::default());
+}
+
+
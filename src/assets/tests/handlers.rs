@@ -0,0 +1,361 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use async_compression::tokio::bufread::GzipDecoder;
+use axum::body::to_bytes;
+use core::time::Duration;
+use include_dir::{DirEntry, File as PackagedFile};
+use std::env::temp_dir;
+use super::super::{config::Config, manifest::AssetManifest};
+use tokio::fs;
+
+
+
+//		Constants																										
+
+/// A directory of assets, laid out as they are when packaged.
+static ASSETS: Dir<'static> = Dir::new("", &[
+	DirEntry::Dir(Dir::new("css", &[
+		DirEntry::File(PackagedFile::new("css/styles.css", b"body { margin: 0; }")),
+	])),
+]);
+
+
+
+//		Structs																											
+
+//		TestState																
+#[derive(Debug, Default)]
+struct TestState {
+	config:   Config,
+	manifest: AssetManifest,
+}
+
+//󰭅		StateProvider															
+impl StateProvider for TestState {
+	//		config																
+	fn config(&self) -> &Config {
+		&self.config
+	}
+	
+	//		assets_dir															
+	fn assets_dir(&self) -> Arc<Dir<'static>> {
+		Arc::new(ASSETS.clone())
+	}
+	
+	//		content_dir															
+	fn content_dir(&self) -> Arc<Dir<'static>> {
+		Arc::new(Dir::new("", &[]))
+	}
+	
+	//		asset_manifest														
+	fn asset_manifest(&self) -> &AssetManifest {
+		&self.manifest
+	}
+}
+
+
+
+//		Functions																										
+
+//		headers																	
+fn headers(pairs: &[(header::HeaderName, &'static str)]) -> HeaderMap {
+	let mut headers = HeaderMap::new();
+	for &(ref name, value) in pairs {
+		drop(headers.insert(name, HeaderValue::from_static(value)));
+	}
+	headers
+}
+
+//		time																	
+fn time(seconds: u64) -> SystemTime {
+	UNIX_EPOCH + Duration::from_secs(seconds)
+}
+
+
+
+//		Tests																											
+
+//		accepted_encodings														
+#[test]
+fn accepted_encodings() {
+	assert_eq!(super::accepted_encodings(&HeaderMap::new()),                                              vec![]);
+	assert_eq!(super::accepted_encodings(&headers(&[(header::ACCEPT_ENCODING, "gzip, deflate, br")])),    vec![Encoding::Brotli, Encoding::Gzip]);
+	assert_eq!(super::accepted_encodings(&headers(&[(header::ACCEPT_ENCODING, "br;q=0.5, gzip")])),       vec![Encoding::Gzip, Encoding::Brotli]);
+	assert_eq!(super::accepted_encodings(&headers(&[(header::ACCEPT_ENCODING, "br;q=0, x-gzip")])),       vec![Encoding::Gzip]);
+	assert_eq!(super::accepted_encodings(&headers(&[(header::ACCEPT_ENCODING, "*;q=0.1, gzip;q=0.2")])),  vec![Encoding::Gzip, Encoding::Brotli]);
+	assert_eq!(super::accepted_encodings(&headers(&[(header::ACCEPT_ENCODING, "identity, gzip;q=bad")])), vec![]);
+}
+
+//		cache_control															
+#[test]
+fn cache_control() {
+	let rules   = vec![
+		CacheRule { pattern: "*.html".parse().unwrap(),      value: "no-cache".parse().unwrap() },
+		CacheRule { pattern: "css/*.*.css".parse().unwrap(), value: "public, max-age=31536000, immutable".parse().unwrap() },
+		CacheRule { pattern: "img/*".parse().unwrap(),       value: "".parse().unwrap() },
+	];
+	let private = CacheControl::from_static("private");
+	let empty   = CacheControl::from_static("");
+	assert_eq!(super::cache_control(&rules, None,           "index.html").unwrap(),            "no-cache");
+	assert_eq!(super::cache_control(&rules, None,           "docs/index.html").unwrap(),       "no-cache");
+	assert_eq!(super::cache_control(&rules, None,           "css/styles.1a2b3c.css").unwrap(), "public, max-age=31536000, immutable");
+	assert_eq!(super::cache_control(&rules, Some(&private), "css/styles.css").unwrap(),        "private");
+	assert_eq!(super::cache_control(&rules, None,           "css/styles.css"),                 None);
+	//	Empty values mean that no header is sent
+	assert_eq!(super::cache_control(&rules, Some(&private), "img/logo.png"),                   None);
+	assert_eq!(super::cache_control(&[],    Some(&empty),   "css/styles.css"),                 None);
+}
+
+//		compressed_content														
+#[tokio::test]
+async fn compressed_content() {
+	let contents = "body { margin: 0; }\n".repeat(100);
+	let response = super::compressed_content(
+		AssetSource::Packaged(contents.clone().leak().as_bytes()),
+		HeaderValue::from_static("text/css"),
+		Encoding::Gzip,
+		&StaticFiles::default(),
+	).unwrap();
+	assert_eq!(response.status(),                        StatusCode::OK);
+	assert_eq!(response.headers()[header::CONTENT_TYPE], "text/css");
+	let body        = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+	assert!(body.len() < contents.len());
+	let mut decoded = String::new();
+	let _count      = GzipDecoder::new(&*body).read_to_string(&mut decoded).await.unwrap();
+	assert_eq!(decoded, contents);
+}
+
+//		encoded_etag															
+#[test]
+fn encoded_etag() {
+	assert_eq!(super::encoded_etag(r#""abc""#,   Encoding::Brotli), r#"W/"abc-br""#);
+	assert_eq!(super::encoded_etag(r#"W/"abc""#, Encoding::Gzip),   r#"W/"abc-gzip""#);
+}
+
+//		etag_matches															
+#[test]
+fn etag_matches() {
+	assert!( super::etag_matches(r#""abc""#,              Some(r#""abc""#)));
+	assert!( super::etag_matches(r#""xyz", "abc""#,       Some(r#""abc""#)));
+	assert!( super::etag_matches(r#"W/"abc""#,            Some(r#""abc""#)));
+	assert!( super::etag_matches("*",                     Some(r#""abc""#)));
+	assert!( super::etag_matches("*",                     None));
+	assert!(!super::etag_matches(r#""xyz""#,              Some(r#""abc""#)));
+	assert!(!super::etag_matches(r#""abc""#,              None));
+}
+
+//		if_range_matches														
+#[test]
+fn if_range_matches() {
+	assert!( super::if_range_matches(r#""abc""#,                        Some(r#""abc""#), None));
+	assert!(!super::if_range_matches(r#""xyz""#,                        Some(r#""abc""#), None));
+	//	Weak tags never match, as strong comparison is required
+	assert!(!super::if_range_matches(r#"W/"abc""#,                      Some(r#"W/"abc""#), None));
+	assert!(!super::if_range_matches(r#""abc""#,                        None,             Some(time(1_700_000_000))));
+	assert!( super::if_range_matches("Tue, 14 Nov 2023 22:13:20 GMT",   None,             Some(time(1_700_000_000))));
+	assert!(!super::if_range_matches("Tue, 14 Nov 2023 22:13:20 GMT",   None,             Some(time(1_700_000_001))));
+	assert!(!super::if_range_matches("Tue, 14 Nov 2023 22:13:20 GMT",   None,             None));
+}
+
+//		is_compressible															
+#[test]
+fn is_compressible() {
+	for compressible in ["text/css", "text/html", "application/javascript", "application/json", "image/svg+xml", "application/manifest+json"] {
+		assert!(super::is_compressible(&compressible.parse().unwrap()), "{compressible}");
+	}
+	for incompressible in ["image/png", "font/woff2", "application/zip", "application/octet-stream"] {
+		assert!(!super::is_compressible(&incompressible.parse().unwrap()), "{incompressible}");
+	}
+}
+
+//		is_not_modified__if_modified_since										
+#[test]
+fn is_not_modified__if_modified_since() {
+	//	1_700_000_000 is Tue, 14 Nov 2023 22:13:20 GMT
+	let request = headers(&[(header::IF_MODIFIED_SINCE, "Tue, 14 Nov 2023 22:13:20 GMT")]);
+	assert!( is_not_modified(&request, None, Some(time(1_700_000_000))));
+	assert!( is_not_modified(&request, None, Some(time(1_699_999_999))));
+	//	Sub-second precision is ignored
+	assert!( is_not_modified(&request, None, Some(time(1_700_000_000) + Duration::from_millis(500))));
+	assert!(!is_not_modified(&request, None, Some(time(1_700_000_001))));
+	assert!(!is_not_modified(&request, None, None));
+	assert!(!is_not_modified(&headers(&[(header::IF_MODIFIED_SINCE, "yesterday")]), None, Some(time(0))));
+}
+
+//		is_not_modified__if_none_match											
+#[test]
+fn is_not_modified__if_none_match() {
+	assert!( is_not_modified(&headers(&[(header::IF_NONE_MATCH, r#""abc""#)]), Some(r#""abc""#), None));
+	assert!(!is_not_modified(&headers(&[(header::IF_NONE_MATCH, r#""xyz""#)]), Some(r#""abc""#), None));
+	//	If-Modified-Since is ignored when If-None-Match is present
+	let request = headers(&[
+		(header::IF_NONE_MATCH,     r#""xyz""#),
+		(header::IF_MODIFIED_SINCE, "Tue, 14 Nov 2023 22:13:20 GMT"),
+	]);
+	assert!(!is_not_modified(&request, Some(r#""abc""#), Some(time(0))));
+	assert!(!is_not_modified(&HeaderMap::new(), Some(r#""abc""#), Some(time(0))));
+}
+
+//		packaged_etag															
+#[test]
+fn packaged_etag() {
+	static FIRST:  Dir<'static> = Dir::new("", &[DirEntry::File(PackagedFile::new("test/packaged_etag.txt", b"contents"))]);
+	static SECOND: Dir<'static> = Dir::new("", &[DirEntry::File(PackagedFile::new("test/packaged_etag.txt", b"other"))]);
+	let etag = super::packaged_etag(FIRST.get_file("test/packaged_etag.txt").unwrap().contents());
+	assert!(etag.starts_with('"') && etag.ends_with('"'));
+	assert_eq!(etag.len(), 45);
+	assert_eq!(super::packaged_etag(FIRST.get_file("test/packaged_etag.txt").unwrap().contents()),  etag);
+	//	Files at the same path in different directories have their own tags
+	assert_ne!(super::packaged_etag(SECOND.get_file("test/packaged_etag.txt").unwrap().contents()), etag);
+}
+
+//		packaged_range															
+#[test]
+fn packaged_range() {
+	assert_eq!(super::packaged_range(b"0123456789", &(2..=4)),  b"234");
+	assert_eq!(super::packaged_range(b"0123456789", &(9..=9)),  b"9");
+	assert_eq!(super::packaged_range(b"0123456789", &(9..=10)), b"");
+}
+
+//		parse_ranges															
+#[test]
+fn parse_ranges() {
+	assert_eq!(super::parse_ranges("bytes=0-4",          10), RangeRequest::Partial(vec![0..=4]));
+	assert_eq!(super::parse_ranges("bytes=5-",           10), RangeRequest::Partial(vec![5..=9]));
+	assert_eq!(super::parse_ranges("bytes=-3",           10), RangeRequest::Partial(vec![7..=9]));
+	assert_eq!(super::parse_ranges("bytes=-30",          10), RangeRequest::Partial(vec![0..=9]));
+	assert_eq!(super::parse_ranges("bytes=8-20",         10), RangeRequest::Partial(vec![8..=9]));
+	assert_eq!(super::parse_ranges("Bytes=0-1, 4-5",     10), RangeRequest::Partial(vec![0..=1, 4..=5]));
+	//	Ranges beyond the end of the file are discarded
+	assert_eq!(super::parse_ranges("bytes=0-1,20-30",    10), RangeRequest::Partial(vec![0..=1]));
+	assert_eq!(super::parse_ranges("bytes=10-",          10), RangeRequest::Unsatisfiable);
+	assert_eq!(super::parse_ranges("bytes=-0",           10), RangeRequest::Unsatisfiable);
+	assert_eq!(super::parse_ranges("bytes=0-",           0),  RangeRequest::Unsatisfiable);
+}
+
+//		parse_ranges__ignored													
+#[test]
+fn parse_ranges__ignored() {
+	assert_eq!(super::parse_ranges("items=0-4",          10), RangeRequest::Whole);
+	assert_eq!(super::parse_ranges("bytes",              10), RangeRequest::Whole);
+	assert_eq!(super::parse_ranges("bytes=",             10), RangeRequest::Whole);
+	assert_eq!(super::parse_ranges("bytes=4-2",          10), RangeRequest::Whole);
+	assert_eq!(super::parse_ranges("bytes=a-b",          10), RangeRequest::Whole);
+	assert_eq!(super::parse_ranges("bytes=0-1,5",        10), RangeRequest::Whole);
+	let many = vec!["0-0"; MAX_RANGES + 1].join(",");
+	assert_eq!(super::parse_ranges(&format!("bytes={many}"), 10), RangeRequest::Whole);
+}
+
+//		partial_content__multiple												
+#[tokio::test]
+async fn partial_content__multiple() {
+	let response = partial_content(
+		AssetSource::Packaged(b"0123456789"),
+		Path::new(""),
+		&[0..=1, 7..=9],
+		10,
+		HeaderValue::from_static("text/plain"),
+		&StaticFiles::default(),
+	).await.unwrap();
+	assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+	let content_type = response.headers()[header::CONTENT_TYPE].to_str().unwrap().to_owned();
+	let boundary     = content_type.strip_prefix("multipart/byteranges; boundary=").unwrap();
+	let body         = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+	assert_eq!(String::from_utf8(body.to_vec()).unwrap(), format!(
+		"\r\n--{boundary}\r\nContent-Type: text/plain\r\nContent-Range: bytes 0-1/10\r\n\r\n01\
+		 \r\n--{boundary}\r\nContent-Type: text/plain\r\nContent-Range: bytes 7-9/10\r\n\r\n789\
+		 \r\n--{boundary}--\r\n"
+	));
+}
+
+//		partial_content__multiple_local											
+#[tokio::test]
+async fn partial_content__multiple_local() {
+	let path = temp_dir().join(format!("terracotta-range-{}.txt", UNIX_EPOCH.elapsed().unwrap().as_nanos()));
+	fs::write(&path, b"0123456789").await.unwrap();
+	let response = partial_content(
+		AssetSource::Local(File::open(&path).await.unwrap()),
+		&path,
+		&[2..=3, 8..=8],
+		10,
+		HeaderValue::from_static("text/plain"),
+		&StaticFiles::default(),
+	).await.unwrap();
+	let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+	fs::remove_file(&path).await.unwrap();
+	let text = String::from_utf8(body.to_vec()).unwrap();
+	assert!(text.contains("Content-Range: bytes 2-3/10\r\n\r\n23\r\n"));
+	assert!(text.contains("Content-Range: bytes 8-8/10\r\n\r\n8\r\n"));
+	assert!(text.ends_with("--\r\n"));
+}
+
+//		partial_content__single													
+#[tokio::test]
+async fn partial_content__single() {
+	let response = partial_content(
+		AssetSource::Packaged(b"0123456789"),
+		Path::new(""),
+		&[3..=5],
+		10,
+		HeaderValue::from_static("text/plain"),
+		&StaticFiles::default(),
+	).await.unwrap();
+	assert_eq!(response.status(),                              StatusCode::PARTIAL_CONTENT);
+	assert_eq!(response.headers()[header::CONTENT_TYPE],       "text/plain");
+	assert_eq!(response.headers()[header::CONTENT_RANGE],      "bytes 3-5/10");
+	assert_eq!(to_bytes(response.into_body(), usize::MAX).await.unwrap(), "345");
+}
+
+//		precompressed_encoding													
+#[test]
+fn precompressed_encoding() {
+	let both = [Encoding::Brotli, Encoding::Gzip];
+	assert_eq!(super::precompressed_encoding(&both,                |_| true),                          (Some(Encoding::Brotli), true));
+	assert_eq!(super::precompressed_encoding(&both,                |encoding| encoding == Encoding::Gzip), (Some(Encoding::Gzip),   true));
+	assert_eq!(super::precompressed_encoding(&[Encoding::Brotli], |encoding| encoding == Encoding::Gzip), (None,                   true));
+	assert_eq!(super::precompressed_encoding(&both,                |_| false),                         (None,                   false));
+}
+
+//		range_request															
+#[test]
+fn range_request() {
+	let etag = Some(r#""abc""#);
+	assert_eq!(super::range_request(&HeaderMap::new(), 10, etag, None), RangeRequest::Whole);
+	assert_eq!(
+		super::range_request(&headers(&[(header::RANGE, "bytes=0-1")]), 10, etag, None),
+		RangeRequest::Partial(vec![0..=1]),
+	);
+	assert_eq!(
+		super::range_request(&headers(&[(header::RANGE, "bytes=0-1"), (header::IF_RANGE, r#""abc""#)]), 10, etag, None),
+		RangeRequest::Partial(vec![0..=1]),
+	);
+	//	The file has changed, so the whole of it is needed
+	assert_eq!(
+		super::range_request(&headers(&[(header::RANGE, "bytes=0-1"), (header::IF_RANGE, r#""xyz""#)]), 10, etag, None),
+		RangeRequest::Whole,
+	);
+}
+
+//		resolve_asset__fingerprinted											
+#[tokio::test]
+async fn resolve_asset__fingerprinted() {
+	let local_path = temp_dir().join(format!("terracotta-assets-{}", UNIX_EPOCH.elapsed().unwrap().as_nanos()));
+	fs::create_dir_all(local_path.join("css")).await.unwrap();
+	fs::write(local_path.join("css/styles.css"), b"body { margin: 1em; }").await.unwrap();
+	let mut state = TestState { manifest: AssetManifest::new(&ASSETS), ..Default::default() };
+	state.config.public_assets.behavior   = LoadingBehavior::Override;
+	state.config.public_assets.local_path = local_path.clone();
+	let (_, _, unfingerprinted_local, unfingerprinted_cache) = resolve_asset(&state, AssetContext::Public, "css/styles.css", false);
+	//	The fingerprint is of the packaged file, so the local one must not be used
+	let (_, _, fingerprinted_local,   fingerprinted_cache)   = resolve_asset(&state, AssetContext::Public, "css/styles.css", true);
+	fs::remove_dir_all(&local_path).await.unwrap();
+	assert!( unfingerprinted_local);
+	assert_eq!(unfingerprinted_cache,        None);
+	assert!(!fingerprinted_local);
+	assert_eq!(fingerprinted_cache.unwrap(), "public, max-age=31536000, immutable");
+}
+
+
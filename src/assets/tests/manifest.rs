@@ -0,0 +1,80 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use include_dir::File;
+
+#[cfg(feature = "tera")]
+use ::tera::{Context, Tera};
+
+
+
+//		Constants																										
+
+/// A directory of assets, laid out as they are when packaged.
+static ASSETS: Dir<'static> = Dir::new("", &[
+	DirEntry::File(File::new("robots.txt", b"User-agent: *")),
+	DirEntry::Dir(Dir::new("css", &[
+		DirEntry::File(File::new("css/styles.css",    b"body { margin: 0; }")),
+		DirEntry::File(File::new("css/styles.css.br", b"compressed")),
+		DirEntry::File(File::new("css/styles.css.gz", b"compressed")),
+	])),
+]);
+
+
+
+//		Tests																											
+
+//		asset_manifest__new														
+#[test]
+fn asset_manifest__new() {
+	let manifest = AssetManifest::new(&ASSETS);
+	let styles   = manifest.fingerprinted("css/styles.css").unwrap();
+	assert_eq!(styles,                                      super::fingerprint("css/styles.css", b"body { margin: 0; }"));
+	assert_eq!(manifest.original(styles),                   Some("css/styles.css"));
+	assert_eq!(manifest.original("css/styles.css"),         None);
+	assert!(manifest.fingerprinted("robots.txt").is_some());
+	assert_eq!(manifest.fingerprinted("css/styles.css.br"), None);
+	assert_eq!(manifest.fingerprinted("css/styles.css.gz"), None);
+}
+
+//		asset_manifest__url														
+#[test]
+fn asset_manifest__url() {
+	let manifest = AssetManifest::new(&ASSETS);
+	let expected = format!("/{}", manifest.fingerprinted("css/styles.css").unwrap());
+	assert_eq!(manifest.url("css/styles.css"),  expected);
+	assert_eq!(manifest.url("/css/styles.css"), expected);
+	assert_eq!(manifest.url("css/missing.css"), "/css/missing.css");
+	assert_eq!(AssetManifest::default().url("css/styles.css"), "/css/styles.css");
+}
+
+//		asset_manifest__tera_function											
+#[cfg(feature = "tera")]
+#[test]
+fn asset_manifest__tera_function() {
+	let manifest = AssetManifest::new(&ASSETS);
+	let mut tera = Tera::default();
+	tera.register_function(ASSET_URL_FUNCTION, manifest.clone());
+	tera.add_raw_templates(vec![
+		("valid",   r#"{{ asset_url(path="css/styles.css") }}"#),
+		("invalid", "{{ asset_url() }}"),
+	]).unwrap();
+	assert_eq!(tera.render("valid", &Context::new()).unwrap(), manifest.url("css/styles.css"));
+	assert!(tera.render("invalid", &Context::new()).is_err());
+}
+
+//		fingerprint																
+#[test]
+fn fingerprint() {
+	let hash = format!("{:016x}", u64::from_be_bytes(*Sha256::digest(b"contents").first_chunk::<8>().unwrap()));
+	assert_eq!(super::fingerprint("css/styles.css",    b"contents"), format!("css/styles.{hash}.css"));
+	assert_eq!(super::fingerprint("css/bulma.min.css", b"contents"), format!("css/bulma.min.{hash}.css"));
+	assert_eq!(super::fingerprint("LICENSE",           b"contents"), format!("LICENSE.{hash}"));
+	assert_eq!(super::fingerprint(".well-known/file",  b"contents"), format!(".well-known/file.{hash}"));
+	assert_eq!(super::fingerprint("img/.hidden",       b"contents"), format!("img/.hidden.{hash}"));
+	assert_ne!(super::fingerprint("css/styles.css",    b"changed"),  format!("css/styles.{hash}.css"));
+}
+
+
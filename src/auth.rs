@@ -4,12 +4,33 @@
 
 //		Modules																											
 
+pub mod api_keys;
+pub mod audit;
+pub mod basic;
+pub mod config;
+pub mod csrf;
 pub mod errors;
 pub mod handlers;
+pub mod impersonation;
+#[cfg(feature = "jwt")]
+pub mod jwt;
+pub mod lockout;
 pub mod middleware;
+#[cfg(feature = "oidc")]
+pub mod oidc;
+pub mod password;
+pub mod permissions;
+pub mod remember;
 pub mod requests;
+pub mod responses;
+pub mod revocation;
 pub mod routing;
+#[cfg(any(feature = "file-store", feature = "sqlite-store"))]
+pub mod sessions;
 pub mod state;
+#[cfg(feature = "totp")]
+pub mod totp;
+pub mod users;
 pub mod utility;
 
 
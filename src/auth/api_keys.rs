@@ -0,0 +1,408 @@
+#![allow(clippy::unused_async, reason = "Middleware functions need to be async")]
+
+//! API key authentication.
+//! 
+//! This provides an alternative to session-based authentication for
+//! service-to-service calls. Keys are supplied in a header, which is
+//! `X-API-Key` by default, or optionally in a query parameter, as configured
+//! in [`ApiKeys`](super::config::ApiKeys).
+//! 
+//! Keys are not stored by the application. Instead, only a hash of each key is
+//! held, as produced by [`hash_api_key()`], and an [`ApiKeyProvider`] looks up
+//! the [`ApiKey`] with a matching hash. Each key belongs to a [`User`], and
+//! holds a set of scopes that limit what it can be used for. New keys can be
+//! created using [`generate_api_key()`].
+//! 
+//! API key authentication is opted into per router, using
+//! [`add_api_key_authentication()`](super::routing::RouterExt::add_api_key_authentication()),
+//! along with the scopes that the router's routes require. Unknown and expired
+//! keys are rejected with a 401 response, and count towards the
+//! [lockout policy](super::lockout), and keys that lack a required scope are
+//! rejected with a 403 response, both with a JSON [`Problem`] body. The time
+//! at which each key was last used is recorded through the provider. Users
+//! authenticated in other ways, such as by a session, need to hold the
+//! router's scopes as permissions.
+
+
+
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/api_keys.rs"]
+mod tests;
+
+
+
+//		Packages																										
+
+use super::{
+	audit::{AuthEvent, ClientInfo, LoginMethod, record_event},
+	config::ApiKeys,
+	errors::AuthError,
+	middleware::{Context, User, UserProvider, forbidden},
+	responses::Problem,
+	state::StateProvider,
+	utility::generate_token,
+};
+use axum::{
+	body::Body,
+	extract::State,
+	http::{HeaderMap, HeaderValue, Request, StatusCode, Uri, header::WWW_AUTHENTICATE},
+	middleware::Next,
+	response::{IntoResponse as _, Response},
+};
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD as BASE64};
+use core::future::Future;
+use sha2::{Digest as _, Sha256};
+use std::sync::Arc;
+use tower_sessions::cookie::time::OffsetDateTime;
+use tracing::{info, warn};
+use url::form_urlencoded;
+
+
+
+//		Constants																										
+
+/// The `WWW-Authenticate` challenge given when an API key is needed.
+const CHALLENGE: &str = "ApiKey";
+
+/// The number of random bytes in a generated API key.
+const KEY_LENGTH: usize = 32;
+
+
+
+//		Structs																											
+
+//		ApiKey																	
+/// An API key, as found by an [`ApiKeyProvider`].
+/// 
+/// The key itself is not included, as only its hash should be stored.
+/// 
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[expect(clippy::exhaustive_structs, reason = "Needs to be constructable by provider implementations")]
+pub struct ApiKey<U: User> {
+	//		Public properties													
+	/// An identifier for the key, which is not secret, and is used in logs and
+	/// when recording use of the key.
+	pub id:           String,
+	
+	/// The user that the key belongs to.
+	pub user:         U,
+	
+	/// The scopes granted to the key.
+	pub scopes:       Vec<String>,
+	
+	/// The time at which the key expires, as a Unix timestamp. If this is not
+	/// set, the key does not expire.
+	pub expires_at:   Option<i64>,
+	
+	/// The time at which the key was last used, as a Unix timestamp, if it
+	/// has been used.
+	pub last_used_at: Option<i64>,
+}
+
+//󰭅		ApiKey																	
+impl<U: User> ApiKey<U> {
+	//		has_scope															
+	/// Checks whether the key has been granted a scope.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `scope` - The scope to check.
+	/// 
+	#[must_use]
+	pub fn has_scope(&self, scope: &str) -> bool {
+		self.scopes.iter().any(|granted| granted == scope)
+	}
+	
+	//		is_expired_at														
+	/// Checks whether the key has expired at a given time.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `now` - The time to check against, as a Unix timestamp.
+	/// 
+	#[must_use]
+	pub fn is_expired_at(&self, now: i64) -> bool {
+		self.expires_at.is_some_and(|expires_at| now >= expires_at)
+	}
+}
+
+
+
+//		Traits																											
+
+//§		ApiKeyProvider															
+/// A trait for providing API keys.
+/// 
+/// This is used by [`api_key_layer()`] to find the key that has been supplied
+/// with a request, and the user that it belongs to, and to record when it was
+/// used. Keys would typically be held in a database, indexed by their hash.
+/// 
+pub trait ApiKeyProvider: UserProvider {
+	//		find_by_api_key_hash												
+	/// Finds an API key by the hash of the key.
+	/// 
+	/// Returns [`Some(ApiKey)`](Some) if a key with the hash exists, whether or
+	/// not it has expired, otherwise returns [`None`].
+	/// 
+	/// # Parameters
+	/// 
+	/// * `state` - The application state.
+	/// * `hash`  - The hash of the supplied key, as produced by
+	///             [`hash_api_key()`].
+	/// 
+	/// # Errors
+	/// 
+	/// If the lookup fails, an error will be returned.
+	/// 
+	fn find_by_api_key_hash<SP: StateProvider>(
+		state: &SP,
+		hash:  &str,
+	) -> impl Future<Output = Result<Option<ApiKey<Self::User>>, Self::Error>> + Send;
+	
+	//		record_api_key_use													
+	/// Records that an API key has been used.
+	/// 
+	/// This is called each time a key is accepted, and should update the
+	/// key's [`last_used_at`](ApiKey::last_used_at) time.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `state`   - The application state.
+	/// * `key`     - The key that was used.
+	/// * `used_at` - The time at which the key was used, as a Unix timestamp.
+	/// 
+	/// # Errors
+	/// 
+	/// If the update fails, an error will be returned.
+	/// 
+	fn record_api_key_use<SP: StateProvider>(
+		state:   &SP,
+		key:     &ApiKey<Self::User>,
+		used_at: i64,
+	) -> impl Future<Output = Result<(), Self::Error>> + Send;
+}
+
+
+
+//		Functions																										
+
+//		api_key_layer															
+/// Prepare the authentication context from an API key.
+/// 
+/// This layer is a middleware that is used to set up the authentication
+/// context for API key authentication. It finds the key supplied with the
+/// request using the [`ApiKeyProvider`], checks that it has not expired and
+/// that it has all of the required scopes, records its use, and stores a
+/// context without a session for its user in the request's extensions, so
+/// that it can be used by the route handlers and by
+/// [`protect()`](super::middleware::protect()).
+/// 
+/// Unknown and expired keys are rejected with a 401 response, and keys that
+/// lack a required scope with a 403 response, both with a JSON [`Problem`]
+/// body. Accepted keys, and unknown and expired keys, are recorded as
+/// [audit events](super::audit), with unknown keys identified by their hash.
+/// Unknown and expired keys also count towards the
+/// [lockout policy](super::lockout), against the key's hash and the client's
+/// IP address, and once either is locked out, keys are rejected without being
+/// looked up.
+/// 
+/// If there is no key and an authentication context has already been set up,
+/// for instance by session-based authentication, it is left as-is, but the
+/// current user, if any, must hold each of the scopes as a
+/// [permission](super::middleware::User::has_permission()), otherwise a 403
+/// response is given. Anonymous requests are passed through, so that
+/// [`protect()`](super::middleware::protect()) can ask them to authenticate.
+/// 
+/// # Parameters
+/// 
+/// * `state`   - The application state.
+/// * `scopes`  - The scopes that the key must have.
+/// * `request` - The request.
+/// * `next`    - The next middleware.
+/// 
+/// # Errors
+/// 
+/// If the key lookup, or the recording of its use, fails, an error will be
+/// returned.
+/// 
+pub async fn api_key_layer<SP, U, UP>(
+	State((state, scopes)): State<(Arc<SP>, &'static [&'static str])>,
+	mut request:            Request<Body>,
+	next:                   Next,
+) -> Result<Response, AuthError>
+where
+	SP: StateProvider,
+	U:  User,
+	UP: ApiKeyProvider<User = U>,
+{
+	let Some(supplied) = supplied_key(&state.config().api_keys, request.headers(), request.uri()) else {
+		let Some(auth_cx) = request.extensions().get::<Context<U>>() else {
+			drop(request.extensions_mut().insert(Context::<U>::without_session(None).with_challenge(CHALLENGE)));
+			return Ok(next.run(request).await);
+		};
+		let lacking = auth_cx.current_user.as_ref()
+			.and_then(|user| missing_permission(user, scopes).map(|scope| (user, scope)))
+		;
+		if let Some((user, scope)) = lacking {
+			warn!("Rejected user {} lacking permission: {scope}", user.to_loggable_string());
+			return Ok(forbidden(auth_cx.response_format(request.extensions(), request.headers())));
+		}
+		return Ok(next.run(request).await);
+	};
+	let client = ClientInfo::from_request(request.headers(), request.extensions());
+	let hash   = hash_api_key(&supplied);
+	let policy = &state.config().lockout;
+	if let Some(remaining) = state.lockout().check(policy, &hash, client.ip) {
+		warn!("Rejected API key from locked out client ({}s remaining)", remaining.as_secs());
+		record_event(&*state, AuthEvent::LockedOut { user: hash, seconds: remaining.as_secs() }, &client).await;
+		return Ok(unauthorized("Too many failed attempts have been made, so the API key cannot be checked"));
+	}
+	let Some(key) = UP::find_by_api_key_hash(&*state, &hash).await
+		.map_err(|err| AuthError::UserProviderError(Box::new(err)))?
+	else {
+		warn!("Rejected unknown API key");
+		record_key_failure(&*state, &hash, hash.clone(), &client).await;
+		return Ok(unauthorized("The API key is not recognised"));
+	};
+	let now = OffsetDateTime::now_utc().unix_timestamp();
+	if key.is_expired_at(now) {
+		warn!("Rejected expired API key: {}", key.id);
+		record_key_failure(&*state, &hash, key.user.to_loggable_string(), &client).await;
+		return Ok(unauthorized("The API key has expired"));
+	}
+	if let Some(scope) = missing_scope(&key, scopes) {
+		warn!("Rejected API key {} lacking scope: {scope}", key.id);
+		return Ok(Problem::new(
+			StatusCode::FORBIDDEN,
+			format!("The API key does not have the required scope: {scope}"),
+		).into_response());
+	}
+	UP::record_api_key_use(&*state, &key, now).await
+		.map_err(|err| AuthError::UserProviderError(Box::new(err)))?
+	;
+	info!("Current API key user: {} (key {})", key.user.to_loggable_string(), key.id);
+	record_event(&*state, AuthEvent::Login { user: key.user.to_loggable_string(), method: LoginMethod::ApiKey }, &client).await;
+	drop(request.extensions_mut().insert(Context::<U>::without_session(Some(key.user)).with_challenge(CHALLENGE).with_client(client)));
+	Ok(next.run(request).await)
+}
+
+//		generate_api_key														
+/// Generates a new random API key.
+/// 
+/// Returns the key, which should be given to the client and then discarded,
+/// and its hash, which should be stored so that the key can be found by an
+/// [`ApiKeyProvider`].
+/// 
+#[must_use]
+pub fn generate_api_key() -> (String, String) {
+	let key  = generate_token(KEY_LENGTH);
+	let hash = hash_api_key(&key);
+	(key, hash)
+}
+
+//		hash_api_key															
+/// Hashes an API key for storage and lookup.
+/// 
+/// Keys are long and random, and so a fast hash is sufficient, and allows keys
+/// to be looked up by their hash.
+/// 
+/// # Parameters
+/// 
+/// * `key` - The API key.
+/// 
+#[must_use]
+pub fn hash_api_key(key: &str) -> String {
+	BASE64.encode(Sha256::digest(key.as_bytes()))
+}
+
+//		missing_permission														
+/// Finds the first required scope that a user does not hold as a permission,
+/// if any.
+/// 
+/// # Parameters
+/// 
+/// * `user`   - The user.
+/// * `scopes` - The required scopes.
+/// 
+fn missing_permission<U: User>(user: &U, scopes: &[&'static str]) -> Option<&'static str> {
+	scopes.iter().copied().find(|scope| !user.has_permission(scope))
+}
+
+//		missing_scope															
+/// Finds the first required scope that a key has not been granted, if any.
+/// 
+/// # Parameters
+/// 
+/// * `key`    - The API key.
+/// * `scopes` - The required scopes.
+/// 
+fn missing_scope<U: User>(key: &ApiKey<U>, scopes: &[&'static str]) -> Option<&'static str> {
+	scopes.iter().copied().find(|scope| !key.has_scope(scope))
+}
+
+//		record_key_failure														
+/// Records a failed attempt to use an API key.
+/// 
+/// The failure is recorded as an [audit event](super::audit), and is counted
+/// against the key's hash and the client's IP address, if known, so that
+/// clients guessing keys are locked out in the same way as those guessing
+/// passwords.
+/// 
+/// # Parameters
+/// 
+/// * `state`  - The application state.
+/// * `hash`   - The hash of the supplied key.
+/// * `user`   - The user that the key belongs to, or the hash if the key is
+///              not known.
+/// * `client` - The client that supplied the key.
+/// 
+async fn record_key_failure<SP: StateProvider>(state: &SP, hash: &str, user: String, client: &ClientInfo) {
+	let policy = &state.config().lockout;
+	record_event(state, AuthEvent::LoginFailed { user, method: LoginMethod::ApiKey }, client).await;
+	if let Some(duration) = state.lockout().record_failure(policy, hash, client.ip) {
+		record_event(state, AuthEvent::LockedOut { user: hash.to_owned(), seconds: duration.as_secs() }, client).await;
+	}
+}
+
+//		supplied_key															
+/// Gets the API key supplied with a request, if there is one.
+/// 
+/// The configured header is checked first, and then, if enabled, the
+/// configured query parameter.
+/// 
+/// # Parameters
+/// 
+/// * `config`  - The API key configuration.
+/// * `headers` - The request headers.
+/// * `uri`     - The request URI.
+/// 
+#[must_use]
+pub fn supplied_key(config: &ApiKeys, headers: &HeaderMap, uri: &Uri) -> Option<String> {
+	if let Some(key) = headers.get(config.header.as_str()).and_then(|value| value.to_str().ok()) {
+		let trimmed = key.trim();
+		return (!trimmed.is_empty()).then(|| trimmed.to_owned());
+	}
+	let param = config.query_param.as_deref()?;
+	form_urlencoded::parse(uri.query()?.as_bytes())
+		.find(|field| field.0 == param)
+		.map(|(_, value)| value.into_owned())
+		.filter(|value| !value.is_empty())
+}
+
+//		unauthorized															
+/// Creates a response for a request with an unusable API key.
+/// 
+/// # Parameters
+/// 
+/// * `detail` - An explanation of the problem.
+/// 
+fn unauthorized(detail: &str) -> Response {
+	(
+		[(WWW_AUTHENTICATE, HeaderValue::from_static(CHALLENGE))],
+		Problem::new(StatusCode::UNAUTHORIZED, detail),
+	).into_response()
+}
+
+
@@ -0,0 +1,418 @@
+//! Audit events for authentication.
+//! 
+//! Significant authentication events, such as logins, failed logins, logouts,
+//! lockouts, expired logins, and [impersonation](super::impersonation), are
+//! reported as [`AuthEvent`]s to the [`AuditSink`] provided by the application
+//! state, so that they can be kept as an audit trail separately from the
+//! general application logs. Each event is wrapped in an [`AuditEntry`], which
+//! records when it happened and the [client](ClientInfo) that caused it.
+//! 
+//! Two sinks are provided: [`TracingAuditSink`], which emits events as
+//! structured log lines, and [`JsonLinesAuditSink`], which appends them to a
+//! file as one JSON object per line. Other destinations, such as a database or
+//! an external service, can be supported by implementing [`AuditSink`].
+//! 
+//! Events are reported for interactive logins, i.e. those using the login
+//! form, the second login step, OpenID Connect, and remember-me tokens. They
+//! are also reported for each request that uses [HTTP Basic](super::basic)
+//! authentication or an [API key](super::api_keys), as those credentials are
+//! checked every time. Users are identified using
+//! [`User::to_loggable_string()`](super::middleware::User::to_loggable_string()),
+//! or for failed logins, [`Credentials::to_loggable_string()`](super::middleware::Credentials::to_loggable_string()).
+//! A sink that fails does not prevent the login from proceeding, but the
+//! failure is logged.
+
+
+
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/audit.rs"]
+mod tests;
+
+
+
+//		Packages																										
+
+use super::state::StateProvider;
+use axum::{
+	extract::ConnectInfo,
+	http::{Extensions, HeaderMap, header::USER_AGENT},
+};
+use core::{
+	convert::Infallible,
+	error::Error,
+	fmt::{Debug, Display, Formatter, self},
+	future::Future,
+	net::{IpAddr, SocketAddr},
+};
+use serde::{Deserialize, Serialize};
+use std::{io, path::Path};
+use tokio::{
+	fs::{File, OpenOptions},
+	io::AsyncWriteExt as _,
+	sync::Mutex,
+};
+use tower_sessions::cookie::time::OffsetDateTime;
+use tracing::{info, warn};
+
+
+
+//		Constants																										
+
+/// The tracing target used by [`TracingAuditSink`].
+pub const AUDIT_TARGET: &str = "audit";
+
+
+
+//		Enums																											
+
+//		AuthEvent																
+/// An authentication event.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AuthEvent {
+	/// A user logged in.
+	Login {
+		/// The user.
+		user:   String,
+		
+		/// How the user logged in.
+		method: LoginMethod,
+	},
+	
+	/// A login attempt failed.
+	LoginFailed {
+		/// The user, or the credentials supplied if the user is not known.
+		user:   String,
+		
+		/// How the user tried to log in.
+		method: LoginMethod,
+	},
+	
+	/// A login attempt was rejected because of too many failed attempts, or a
+	/// failed attempt caused further attempts to be rejected.
+	LockedOut {
+		/// The user, or the credentials supplied if the user is not known.
+		user:    String,
+		
+		/// How long further attempts will be rejected for, in seconds.
+		seconds: u64,
+	},
+	
+	/// A user logged out.
+	Logout {
+		/// The user.
+		user: String,
+	},
+	
+	/// A user's login exceeded the
+	/// [absolute expiry](super::config::Expiry#structfield.absolute), and so
+	/// they were logged out.
+	SessionExpired {
+		/// The user.
+		user: String,
+	},
+	
+	/// A user started impersonating another user.
+	ImpersonationStarted {
+		/// The user doing the impersonating.
+		user:   String,
+		
+		/// The user being impersonated.
+		target: String,
+	},
+	
+	/// A request was made by a user while impersonating another user.
+	ImpersonatedRequest {
+		/// The user doing the impersonating.
+		user:   String,
+		
+		/// The user being impersonated.
+		target: String,
+		
+		/// The HTTP method of the request.
+		method: String,
+		
+		/// The path of the request.
+		path:   String,
+	},
+	
+	/// A user stopped impersonating another user.
+	ImpersonationStopped {
+		/// The user doing the impersonating.
+		user:   String,
+		
+		/// The user who was being impersonated.
+		target: String,
+	},
+}
+
+//󰭅		Display																	
+impl Display for AuthEvent {
+	//		fmt																	
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match *self {
+			Self::Login { ref user, method }                                         => write!(f, "Login by user {user} using {method}"),
+			Self::LoginFailed { ref user, method }                                   => write!(f, "Failed login by user {user} using {method}"),
+			Self::LockedOut { ref user, seconds }                                    => write!(f, "Locked out user {user} for {seconds}s"),
+			Self::Logout { ref user }                                                => write!(f, "Logout by user {user}"),
+			Self::SessionExpired { ref user }                                        => write!(f, "Login expired for user {user}"),
+			Self::ImpersonationStarted { ref user, ref target }                      => write!(f, "User {user} started impersonating user {target}"),
+			Self::ImpersonatedRequest { ref user, ref target, ref method, ref path } => write!(f, "User {user} impersonating user {target} requested {method} {path}"),
+			Self::ImpersonationStopped { ref user, ref target }                      => write!(f, "User {user} stopped impersonating user {target}"),
+		}
+	}
+}
+
+//		LoginMethod																
+/// The ways in which a user can log in.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+#[serde(rename_all = "snake_case")]
+pub enum LoginMethod {
+	/// The login form, using a username and password.
+	Password,
+	
+	/// The [second login step](super::totp), using a one-time password or
+	/// recovery code.
+	TwoFactor,
+	
+	/// A [remember-me token](super::remember).
+	RememberMe,
+	
+	/// An [OpenID Connect](super::oidc) provider.
+	Oidc,
+	
+	/// [HTTP Basic authentication](super::basic).
+	Basic,
+	
+	/// An [API key](super::api_keys). As the key must not be revealed, failed
+	/// attempts with an unknown key are recorded against the key's hash.
+	ApiKey,
+}
+
+//󰭅		Display																	
+impl Display for LoginMethod {
+	//		fmt																	
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str(match *self {
+			Self::Password   => "password",
+			Self::TwoFactor  => "two-factor",
+			Self::RememberMe => "remember-me",
+			Self::Oidc       => "OIDC",
+			Self::Basic      => "Basic",
+			Self::ApiKey     => "API key",
+		})
+	}
+}
+
+
+
+//		Structs																											
+
+//		AuditEntry																
+/// An authentication event, along with when it happened and who caused it.
+/// 
+/// This is what is passed to an [`AuditSink`]. When serialised, the fields of
+/// the event and the client are flattened into the entry, with the type of
+/// event given in an `event` field.
+/// 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct AuditEntry {
+	//		Public properties													
+	/// The time at which the event happened, as a Unix timestamp.
+	pub at:     i64,
+	
+	/// The event.
+	#[serde(flatten)]
+	pub event:  AuthEvent,
+	
+	/// The client that caused the event.
+	#[serde(flatten)]
+	pub client: ClientInfo,
+}
+
+//󰭅		AuditEntry																
+impl AuditEntry {
+	//		new																	
+	/// Creates a new audit entry for an event that has just happened.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `event`  - The event.
+	/// * `client` - The client that caused the event.
+	/// 
+	#[must_use]
+	pub fn new(event: AuthEvent, client: ClientInfo) -> Self {
+		Self {
+			at: OffsetDateTime::now_utc().unix_timestamp(),
+			event,
+			client,
+		}
+	}
+}
+
+//		ClientInfo																
+/// Details of the client that made a request.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[expect(clippy::exhaustive_structs, reason = "Simple data structure")]
+pub struct ClientInfo {
+	//		Public properties													
+	/// The client's IP address. This is only known if the server has been
+	/// started with connection info, as is the case with
+	/// [`server()`](crate::app::create::server()).
+	pub ip:         Option<IpAddr>,
+	
+	/// The client's `User-Agent` header, if supplied.
+	pub user_agent: Option<String>,
+}
+
+//󰭅		ClientInfo																
+impl ClientInfo {
+	//		from_request														
+	/// Gets the details of the client from a request.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `headers`    - The request headers.
+	/// * `extensions` - The request extensions.
+	/// 
+	#[must_use]
+	pub fn from_request(headers: &HeaderMap, extensions: &Extensions) -> Self {
+		Self {
+			ip:         extensions.get::<ConnectInfo<SocketAddr>>().map(|&ConnectInfo(address)| address.ip()),
+			user_agent: headers.get(USER_AGENT).and_then(|value| value.to_str().ok()).map(ToOwned::to_owned),
+		}
+	}
+}
+
+//		JsonLinesAuditSink														
+/// An audit sink that appends events to a file, as one JSON object per line.
+/// 
+/// Each line is written and flushed as the event happens, so that the file can
+/// be followed by log shippers. The file is created if it does not exist.
+/// 
+#[derive(Debug)]
+pub struct JsonLinesAuditSink {
+	//		Private properties													
+	/// The file being written to.
+	file: Mutex<File>,
+}
+
+//󰭅		JsonLinesAuditSink														
+impl JsonLinesAuditSink {
+	//		open																
+	/// Opens a file to append events to.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `path` - The path of the file.
+	/// 
+	/// # Errors
+	/// 
+	/// If the file cannot be opened or created, an error will be returned.
+	/// 
+	pub async fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+		let file = OpenOptions::new().create(true).append(true).open(path).await?;
+		Ok(Self { file: Mutex::new(file) })
+	}
+}
+
+//󰭅		AuditSink																
+impl AuditSink for JsonLinesAuditSink {
+	type Error = io::Error;
+	
+	//		record																
+	async fn record(&self, entry: &AuditEntry) -> Result<(), Self::Error> {
+		let mut line = serde_json::to_vec(entry)?;
+		line.push(b'\n');
+		let mut file = self.file.lock().await;
+		file.write_all(&line).await?;
+		file.flush().await
+	}
+}
+
+//		TracingAuditSink														
+/// An audit sink that emits events as log lines.
+/// 
+/// Events are logged with the [`AUDIT_TARGET`] target, so that they can be
+/// filtered or routed separately from other log lines. Failed logins and
+/// lockouts are logged as warnings, and other events as information.
+/// 
+#[derive(Clone, Copy, Debug, Default)]
+#[expect(clippy::exhaustive_structs, reason = "Unit struct")]
+pub struct TracingAuditSink;
+
+//󰭅		AuditSink																
+impl AuditSink for TracingAuditSink {
+	type Error = Infallible;
+	
+	//		record																
+	async fn record(&self, entry: &AuditEntry) -> Result<(), Self::Error> {
+		let ip         = entry.client.ip.map(|ip| ip.to_string());
+		let user_agent = entry.client.user_agent.as_deref();
+		if matches!(entry.event, AuthEvent::LoginFailed { .. } | AuthEvent::LockedOut { .. }) {
+			warn!(target: AUDIT_TARGET, at = entry.at, ip, user_agent, "{}", entry.event);
+		} else {
+			info!(target: AUDIT_TARGET, at = entry.at, ip, user_agent, "{}", entry.event);
+		}
+		Ok(())
+	}
+}
+
+
+
+//		Traits																											
+
+//§		AuditSink																
+/// A destination for authentication audit events.
+/// 
+/// The sink is provided by the application state, and is given each event as
+/// it happens. [`TracingAuditSink`] and [`JsonLinesAuditSink`] are provided,
+/// and other destinations can be supported by implementing this trait.
+/// 
+pub trait AuditSink: Debug + Send + Sync + 'static {
+	/// The error type returned when an event cannot be recorded.
+	type Error: Error + Send + Sync + 'static;
+	
+	//		record																
+	/// Records an event.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `entry` - The event, along with when it happened and who caused it.
+	/// 
+	/// # Errors
+	/// 
+	/// If the event cannot be recorded, an error will be returned.
+	/// 
+	fn record(&self, entry: &AuditEntry) -> impl Future<Output = Result<(), Self::Error>> + Send;
+}
+
+
+
+//		Functions																										
+
+//		record_event															
+/// Records an authentication event using the application's audit sink.
+/// 
+/// If the sink fails, the failure is logged, but is not otherwise reported, so
+/// that problems with the audit trail do not prevent users from logging in.
+/// 
+/// # Parameters
+/// 
+/// * `state`  - The application state.
+/// * `event`  - The event.
+/// * `client` - The client that caused the event.
+/// 
+pub async fn record_event<SP: StateProvider>(state: &SP, event: AuthEvent, client: &ClientInfo) {
+	let entry = AuditEntry::new(event, client.clone());
+	if let Err(err) = state.audit_sink().record(&entry).await {
+		warn!("Failed to record audit event: {}: {err}", entry.event);
+	}
+}
+
+
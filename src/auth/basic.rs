@@ -0,0 +1,206 @@
+#![allow(clippy::unused_async, reason = "Middleware functions need to be async")]
+
+//! HTTP Basic authentication.
+//! 
+//! This provides an alternative to session-based authentication for machine
+//! clients that can only send credentials in the `Authorization: Basic`
+//! header. The username and password are decoded into the application's
+//! [`Credentials`] type, using the field names given in the [`Basic`]
+//! configuration, and are verified using
+//! [`UserProvider::find_by_credentials()`] on every request. Failed attempts
+//! count towards the same [lockout policy](super::lockout) as the login form.
+//! Users who need a second factor are never authenticated in this way, as a
+//! password alone is not enough for them.
+//! 
+//! Basic authentication is opted into per router, using
+//! [`add_basic_authentication()`](super::routing::RouterExt::add_basic_authentication()),
+//! and unauthenticated requests to its protected routes receive a 401 response
+//! with a `WWW-Authenticate: Basic` challenge for the configured realm.
+
+
+
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/basic.rs"]
+mod tests;
+
+
+
+//		Packages																										
+
+use super::{
+	audit::{AuthEvent, ClientInfo, LoginMethod, record_event},
+	config::Basic,
+	errors::AuthError,
+	middleware::{Context, Credentials, User, UserProvider},
+	state::StateProvider,
+};
+use axum::{
+	body::Body,
+	extract::State,
+	http::{HeaderMap, Request, header::AUTHORIZATION},
+	middleware::Next,
+	response::Response,
+};
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use serde::de::value::{Error as DeError, MapDeserializer};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+
+
+//		Functions																										
+
+//		basic_auth_layer														
+/// Prepare the authentication context from HTTP Basic credentials.
+/// 
+/// This layer is a middleware that is used to set up the authentication
+/// context for Basic authentication. It decodes the credentials in the
+/// `Authorization` header, verifies them using
+/// [`UserProvider::find_by_credentials()`], and stores a context without a
+/// session in the request's extensions, so that it can be used by the route
+/// handlers and by [`protect()`](super::middleware::protect()). The context
+/// asks for Basic credentials when authentication is required.
+/// 
+/// Failed attempts are counted against the username and the client's IP
+/// address, if known, in the same way as for the
+/// [login form](super::handlers::post_login()). Once either is locked out,
+/// attempts are rejected without the credentials being checked. Successful
+/// and failed attempts, and lockouts, are recorded as
+/// [audit events](super::audit).
+/// 
+/// Users who need a second factor cannot supply one using Basic
+/// authentication, and so are not authenticated, even if their password is
+/// correct.
+/// 
+/// If there are no Basic credentials and an authentication context has
+/// already been set up, for instance by session-based authentication, it is
+/// left as-is.
+/// 
+/// # Parameters
+/// 
+/// * `state`   - The application state.
+/// * `request` - The request.
+/// * `next`    - The next middleware.
+/// 
+/// # Errors
+/// 
+/// If the user lookup fails, an error will be returned.
+/// 
+pub async fn basic_auth_layer<SP, U, UP>(
+	State(state): State<Arc<SP>>,
+	mut request:  Request<Body>,
+	next:         Next,
+) -> Result<Response, AuthError>
+where
+	SP: StateProvider,
+	U:  User,
+	UP: UserProvider<User = U>,
+{
+	let config    = &state.config().basic;
+	let challenge = challenge(config);
+	let Some((username, password)) = basic_credentials(request.headers()) else {
+		if request.extensions().get::<Context<U>>().is_none() {
+			drop(request.extensions_mut().insert(Context::<U>::without_session(None).with_challenge(challenge)));
+		}
+		return Ok(next.run(request).await);
+	};
+	let Some(credentials) = to_credentials::<UP::Credentials>(config, &username, &password) else {
+		drop(request.extensions_mut().insert(Context::<U>::without_session(None).with_challenge(challenge)));
+		return Ok(next.run(request).await);
+	};
+	let credentials_string = credentials.to_loggable_string();
+	let client             = ClientInfo::from_request(request.headers(), request.extensions());
+	let policy             = &state.config().lockout;
+	let user               = if let Some(remaining) = state.lockout().check(policy, &credentials_string, client.ip) {
+		warn!("Rejected locked out Basic authentication for user: {} ({}s remaining)", &credentials_string, remaining.as_secs());
+		record_event(&*state, AuthEvent::LockedOut { user: credentials_string.clone(), seconds: remaining.as_secs() }, &client).await;
+		None
+	} else if let Some(found) = UP::find_by_credentials(&*state, &credentials).await
+		.map_err(|err| AuthError::UserProviderError(Box::new(err)))?
+	{
+		#[cfg(feature = "totp")]
+		if found.requires_two_factor() {
+			warn!("Rejected Basic authentication for user needing a second factor: {}", found.to_loggable_string());
+			record_event(&*state, AuthEvent::LoginFailed { user: found.to_loggable_string(), method: LoginMethod::Basic }, &client).await;
+			drop(request.extensions_mut().insert(Context::<U>::without_session(None).with_challenge(challenge).with_client(client)));
+			return Ok(next.run(request).await);
+		}
+		info!("Current Basic user: {}", found.to_loggable_string());
+		state.lockout().record_success(&credentials_string);
+		record_event(&*state, AuthEvent::Login { user: found.to_loggable_string(), method: LoginMethod::Basic }, &client).await;
+		Some(found)
+	} else {
+		warn!("Failed Basic authentication for user: {}", &credentials_string);
+		record_event(&*state, AuthEvent::LoginFailed { user: credentials_string.clone(), method: LoginMethod::Basic }, &client).await;
+		if let Some(duration) = state.lockout().record_failure(policy, &credentials_string, client.ip) {
+			record_event(&*state, AuthEvent::LockedOut { user: credentials_string.clone(), seconds: duration.as_secs() }, &client).await;
+		}
+		None
+	};
+	drop(request.extensions_mut().insert(Context::<U>::without_session(user).with_challenge(challenge).with_client(client)));
+	Ok(next.run(request).await)
+}
+
+//		basic_credentials														
+/// Gets the username and password from the request headers, if supplied.
+/// 
+/// The credentials must be base64-encoded UTF-8, in the form
+/// `username:password`, as described by [RFC 7617](https://www.rfc-editor.org/rfc/rfc7617).
+/// The password may contain colons, but the username may not.
+/// 
+/// # Parameters
+/// 
+/// * `headers` - The request headers.
+/// 
+fn basic_credentials(headers: &HeaderMap) -> Option<(String, String)> {
+	let value          = headers.get(AUTHORIZATION)?.to_str().ok()?;
+	let (scheme, rest) = value.split_once(' ')?;
+	if !scheme.eq_ignore_ascii_case("Basic") {
+		return None;
+	}
+	let decoded        = String::from_utf8(BASE64.decode(rest.trim()).ok()?).ok()?;
+	let (user, pass)   = decoded.split_once(':')?;
+	Some((user.to_owned(), pass.to_owned()))
+}
+
+//		challenge																
+/// Creates the `WWW-Authenticate` challenge for the configured realm.
+/// 
+/// Any quotes or backslashes in the realm are escaped.
+/// 
+/// # Parameters
+/// 
+/// * `config` - The Basic authentication configuration.
+/// 
+fn challenge(config: &Basic) -> String {
+	let realm = config.realm.replace('\\', r"\\").replace('"', r#"\""#);
+	format!(r#"Basic realm="{realm}", charset="UTF-8""#)
+}
+
+//		to_credentials															
+/// Converts a username and password into the application's credentials type.
+/// 
+/// They are supplied to the credentials type's [`Deserialize`](serde::Deserialize)
+/// implementation as a map containing the configured username and password
+/// fields. If that fails, for instance because the credentials type requires
+/// other fields, the reason is logged and [`None`] is returned.
+/// 
+/// # Parameters
+/// 
+/// * `config`   - The Basic authentication configuration.
+/// * `username` - The supplied username.
+/// * `password` - The supplied password.
+/// 
+fn to_credentials<C: Credentials>(config: &Basic, username: &str, password: &str) -> Option<C> {
+	let fields = [
+		(config.username_field.as_str(), username),
+		(config.password_field.as_str(), password),
+	];
+	C::deserialize(MapDeserializer::<_, DeError>::new(fields.into_iter()))
+		.inspect_err(|err| warn!("Failed to convert Basic authentication credentials: {err}"))
+		.ok()
+}
+
+
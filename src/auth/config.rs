@@ -0,0 +1,604 @@
+#![allow(clippy::exhaustive_structs, reason = "Configuration structs")]
+
+//! Configuration for the authentication functionality.
+
+
+
+//		Packages																										
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use core::fmt::{Debug, Formatter, self};
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as DeError};
+use smart_default::SmartDefault;
+use tower_sessions::cookie::{Key, SameSite as CookieSameSite};
+
+#[cfg(feature = "jwt")]
+use jsonwebtoken::{DecodingKey, errors::{Error as JwtError, ErrorKind as JwtErrorKind}};
+
+
+
+//		Enums																											
+
+//		JwtAlgorithm															
+/// The supported algorithms for verifying JSON Web Tokens.
+#[cfg(feature = "jwt")]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[expect(clippy::exhaustive_enums, reason = "Exhaustive")]
+pub enum JwtAlgorithm {
+	/// HMAC using SHA-256, with a shared secret.
+	#[serde(rename = "HS256")]
+	Hs256,
+	
+	/// RSASSA-PKCS1-v1_5 using SHA-256, with an RSA public key.
+	#[serde(rename = "RS256")]
+	Rs256,
+	
+	/// Edwards-curve digital signatures, with an Ed25519 public key.
+	#[serde(rename = "EdDSA")]
+	EdDsa,
+}
+
+//		SameSite																
+/// The possible options for the `SameSite` attribute of the session cookie.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[expect(clippy::exhaustive_enums, reason = "Exhaustive")]
+pub enum SameSite {
+	/// Only send the cookie with same-site requests.
+	Strict,
+	
+	/// Send the cookie with same-site requests, and with top-level cross-site
+	/// navigations that use a safe method such as `GET`.
+	Lax,
+	
+	/// Send the cookie with all requests. This requires the cookie to be
+	/// [secure](Cookie#structfield.secure).
+	None,
+}
+
+//󰭅		From SameSite															
+impl From<SameSite> for CookieSameSite {
+	//		from																
+	fn from(same_site: SameSite) -> Self {
+		match same_site {
+			SameSite::Strict => Self::Strict,
+			SameSite::Lax    => Self::Lax,
+			SameSite::None   => Self::None,
+		}
+	}
+}
+
+
+
+//		Structs																											
+
+//		Config																	
+/// The configuration options for authentication.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, SmartDefault)]
+pub struct Config {
+	//		Public properties													
+	/// The key used to sign session cookies, as a base64-encoded string of at
+	/// least 64 bytes. If this is not set, a random key will be generated each
+	/// time the application starts, which means that all sessions will be
+	/// invalidated on restart, and that sessions cannot be shared between
+	/// multiple instances of the application.
+	pub session_key:   Option<SessionKey>,
+	
+	/// The attributes of the session cookie.
+	pub cookie:        Cookie,
+	
+	/// The expiry policy for sessions.
+	pub expiry:        Expiry,
+	
+	/// The brute-force protection policy for logins.
+	pub lockout:       Lockout,
+	
+	/// The policy for persistent "remember me" logins.
+	pub remember:      Remember,
+	
+	/// The policy for redirects after logging in.
+	pub redirects:     Redirects,
+	
+	/// The configuration for HTTP Basic authentication.
+	pub basic:         Basic,
+	
+	/// The configuration for API key authentication.
+	pub api_keys:      ApiKeys,
+	
+	/// The policy for impersonating other users.
+	pub impersonation: Impersonation,
+	
+	/// The configuration for bearer token authentication.
+	#[cfg(feature = "jwt")]
+	pub jwt:           Jwt,
+	
+	/// The configuration for logging in using an OpenID Connect provider.
+	#[cfg(feature = "oidc")]
+	pub oidc:          Oidc,
+	
+	/// The configuration for two-factor authentication using time-based
+	/// one-time passwords.
+	#[cfg(feature = "totp")]
+	pub totp:          Totp,
+}
+
+//		ApiKeys																	
+/// The configuration for API key authentication.
+/// 
+/// This is only used by routers that opt in using
+/// [`add_api_key_authentication()`](super::routing::RouterExt::add_api_key_authentication()).
+/// See the [`api_keys`](super::api_keys) module for details.
+/// 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, SmartDefault)]
+pub struct ApiKeys {
+	//		Public properties													
+	/// The name of the header that API keys are supplied in.
+	#[default = "X-API-Key"]
+	pub header:      String,
+	
+	/// The name of the query parameter that API keys can be supplied in. If
+	/// this is not set, keys are only accepted in the header. Keys supplied in
+	/// URLs are more likely to be exposed, for instance in logs, and so this
+	/// should only be enabled for clients that cannot set headers.
+	pub query_param: Option<String>,
+}
+
+//		Basic																	
+/// The configuration for HTTP Basic authentication.
+/// 
+/// This is only used by routers that opt in using
+/// [`add_basic_authentication()`](super::routing::RouterExt::add_basic_authentication()).
+/// The username and password supplied are deserialised into the application's
+/// [`Credentials`](super::middleware::Credentials) type using the configured
+/// field names.
+/// 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, SmartDefault)]
+pub struct Basic {
+	//		Public properties													
+	/// The realm given in the `WWW-Authenticate` challenge, which is shown to
+	/// users by some clients.
+	#[default = "Restricted"]
+	pub realm:          String,
+	
+	/// The name of the credentials field that the username is supplied as.
+	#[default = "username"]
+	pub username_field: String,
+	
+	/// The name of the credentials field that the password is supplied as.
+	#[default = "password"]
+	pub password_field: String,
+}
+
+//		Cookie																	
+/// The attributes of the session cookie.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, SmartDefault)]
+pub struct Cookie {
+	//		Public properties													
+	/// The name of the session cookie.
+	#[default = "id"]
+	pub name:      String,
+	
+	/// The domain to which the cookie will be sent. If this is not set, the
+	/// cookie will only be sent to the host that set it, excluding subdomains.
+	pub domain:    Option<String>,
+	
+	/// The path to which the cookie will be sent.
+	#[default = "/"]
+	pub path:      String,
+	
+	/// Whether the cookie should only be sent over HTTPS. This should be
+	/// enabled in production, and is disabled by default only so that the
+	/// application works over plain HTTP during local development.
+	#[default = false]
+	pub secure:    bool,
+	
+	/// Whether the cookie should be inaccessible to JavaScript.
+	#[default = true]
+	pub http_only: bool,
+	
+	/// The `SameSite` attribute of the cookie, which controls whether it is
+	/// sent with cross-site requests.
+	#[default(SameSite::Strict)]
+	pub same_site: SameSite,
+}
+
+//		Expiry																	
+/// The expiry policy for sessions.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, SmartDefault)]
+pub struct Expiry {
+	//		Public properties													
+	/// The period of inactivity after which a session expires, in seconds.
+	/// Each request made with the session extends it by this amount. If this
+	/// is not set, the session cookie will expire when the browser session
+	/// ends.
+	pub inactivity: Option<u64>,
+	
+	/// The maximum lifetime of a login, in seconds, regardless of activity.
+	/// Once this has elapsed since the user logged in, they will be logged out
+	/// and will need to log in again. If this is not set, there is no limit.
+	pub absolute:   Option<u64>,
+}
+
+//		Impersonation															
+/// The policy for impersonating other users.
+/// 
+/// Users with the configured permission can assume the identity of another
+/// user, for instance so that support staff can see the application as that
+/// user does. See the [`impersonation`](super::impersonation) module for
+/// details.
+/// 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, SmartDefault)]
+pub struct Impersonation {
+	//		Public properties													
+	/// The permission that a user needs in order to impersonate other users.
+	/// Users who hold this permission cannot themselves be impersonated.
+	#[default = "impersonate"]
+	pub permission: String,
+}
+
+//		Jwt																		
+/// The configuration for bearer token authentication using JSON Web Tokens.
+/// 
+/// The key is parsed when the configuration is loaded, so that an unusable key
+/// is reported at startup rather than when the first token arrives.
+/// 
+#[cfg(feature = "jwt")]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, SmartDefault)]
+#[serde(try_from = "UncheckedJwt")]
+pub struct Jwt {
+	//		Public properties													
+	/// The algorithm that tokens must be signed with. Tokens signed with any
+	/// other algorithm will be rejected.
+	#[default(JwtAlgorithm::Hs256)]
+	pub algorithm: JwtAlgorithm,
+	
+	/// The key used to verify token signatures. For `HS256` this is the shared
+	/// secret, and for `RS256` and `EdDSA` it is the PEM-encoded public key.
+	/// Token authentication will fail if this is not set, and the configuration
+	/// will fail to load if it is set but is not a valid key for the algorithm.
+	pub key:       String,
+	
+	/// The expected issuer of tokens. If this is set, tokens must have a
+	/// matching `iss` claim.
+	pub issuer:    Option<String>,
+	
+	/// The expected audiences of tokens. If any are set, tokens must have an
+	/// `aud` claim that matches at least one of them.
+	pub audience:  Vec<String>,
+	
+	/// The amount of clock skew to allow when checking the `exp` and `nbf`
+	/// claims, in seconds.
+	#[default = 60]
+	pub leeway:    u64,
+}
+
+//󰭅		Jwt																		
+#[cfg(feature = "jwt")]
+impl Jwt {
+	//		decoding_key														
+	/// Parses the configured key for the configured algorithm.
+	/// 
+	/// # Errors
+	/// 
+	/// If the key is missing, or is not a valid key for the algorithm, an error
+	/// will be returned.
+	/// 
+	pub fn decoding_key(&self) -> Result<DecodingKey, JwtError> {
+		if self.key.is_empty() {
+			return Err(JwtErrorKind::InvalidKeyFormat.into());
+		}
+		match self.algorithm {
+			JwtAlgorithm::Hs256 => Ok(DecodingKey::from_secret(self.key.as_bytes())),
+			JwtAlgorithm::Rs256 => DecodingKey::from_rsa_pem(self.key.as_bytes()),
+			JwtAlgorithm::EdDsa => DecodingKey::from_ed_pem(self.key.as_bytes()),
+		}
+	}
+}
+
+//󰭅		TryFrom<UncheckedJwt>													
+#[cfg(feature = "jwt")]
+impl TryFrom<UncheckedJwt> for Jwt {
+	type Error = String;
+	
+	//		try_from															
+	fn try_from(unchecked: UncheckedJwt) -> Result<Self, Self::Error> {
+		let jwt = Self {
+			algorithm: unchecked.algorithm,
+			key:       unchecked.key,
+			issuer:    unchecked.issuer,
+			audience:  unchecked.audience,
+			leeway:    unchecked.leeway,
+		};
+		if !jwt.key.is_empty() {
+			drop(jwt.decoding_key().map_err(|err| format!("Invalid JWT key: {err}"))?);
+		}
+		Ok(jwt)
+	}
+}
+
+//		Lockout																	
+/// The brute-force protection policy for logins.
+/// 
+/// Failed login attempts are counted per username and per IP address. Once
+/// the number of failures reaches the relevant threshold, further attempts are
+/// locked out for a period that doubles with each subsequent failure, up to a
+/// maximum. Failures are forgotten once no more have occurred for the length
+/// of the window, and a successful login resets the counts.
+/// 
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize, SmartDefault)]
+pub struct Lockout {
+	//		Public properties													
+	/// Whether brute-force protection is enabled.
+	#[default = true]
+	pub enabled:        bool,
+	
+	/// The number of failures for a single username after which it will be
+	/// locked out.
+	#[default = 5]
+	pub user_threshold: u32,
+	
+	/// The number of failures from a single IP address after which it will be
+	/// locked out. This is higher than the username threshold by default, as
+	/// many users may share an address.
+	#[default = 20]
+	pub ip_threshold:   u32,
+	
+	/// The period, in seconds, after the last failure at which the failure
+	/// count is reset.
+	#[default = 900]
+	pub window:         u64,
+	
+	/// The duration of the first lockout, in seconds. Each further failure
+	/// while over the threshold doubles this.
+	#[default = 30]
+	pub duration:       u64,
+	
+	/// The maximum duration of a lockout, in seconds.
+	#[default = 3600]
+	pub max_duration:   u64,
+}
+
+//		Oidc																	
+/// The configuration for logging in using an OpenID Connect provider.
+/// 
+/// The application needs to be registered with the provider as a client, with
+/// the [`redirect_uri`](Oidc#structfield.redirect_uri) pointing at the
+/// [callback handler](super::oidc::get_oidc_callback()). The session cookie
+/// must use a [`SameSite`] setting of `Lax` or `None`, as otherwise it will not
+/// be sent when the provider redirects back to the application.
+/// 
+#[cfg(feature = "oidc")]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, SmartDefault)]
+pub struct Oidc {
+	//		Public properties													
+	/// The issuer identifier of the provider, which is the URL that its
+	/// discovery document is found under, at
+	/// `/.well-known/openid-configuration`. Logins will fail if this is not
+	/// set.
+	pub issuer:         String,
+	
+	/// The client ID that the application is registered with.
+	pub client_id:      String,
+	
+	/// The client secret that the application is registered with, if any.
+	/// Public clients rely upon PKCE alone, and do not have a secret.
+	pub client_secret:  Option<String>,
+	
+	/// The full URL of the callback handler, as registered with the provider.
+	pub redirect_uri:   String,
+	
+	/// The scopes to request. The `openid` scope is always requested, whether
+	/// or not it is listed.
+	#[default(vec!["openid".to_owned(), "email".to_owned(), "profile".to_owned()])]
+	pub scopes:         Vec<String>,
+	
+	/// The algorithms that ID tokens may be signed with. Tokens signed with
+	/// any other algorithm will be rejected. `HS256` tokens are verified using
+	/// the client secret, and others using the provider's published keys.
+	#[default(vec![JwtAlgorithm::Rs256])]
+	pub algorithms:     Vec<JwtAlgorithm>,
+	
+	/// The amount of clock skew to allow when checking the times in ID tokens,
+	/// in seconds.
+	#[default = 60]
+	pub leeway:         u64,
+	
+	/// The time to wait for the provider to respond, in seconds.
+	#[default = 10]
+	pub timeout:        u64,
+	
+	/// How long the provider's discovery document and signing keys are reused
+	/// for before being fetched again, in seconds. The keys are always fetched
+	/// again if a token is signed with a key that they do not include.
+	#[default = 3_600]
+	pub cache_lifetime: u64,
+}
+
+//		Redirects																
+/// The policy for redirects after logging in.
+/// 
+/// The page to return to after logging in is supplied by the client, and so it
+/// is checked before being redirected to, to prevent open redirects. Relative
+/// paths on the same site are always allowed, and other sites can be allowed
+/// by listing their origins. See
+/// [`safe_redirect_uri()`](super::utility::safe_redirect_uri()) for details.
+/// 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, SmartDefault)]
+pub struct Redirects {
+	//		Public properties													
+	/// The origins of other sites that can be redirected to after logging in,
+	/// in the form `https://example.com`, including the port if it is not the
+	/// default. By default, only relative paths are allowed.
+	pub allowed_origins: Vec<String>,
+}
+
+//		Remember																
+/// The policy for persistent "remember me" logins.
+/// 
+/// When a user ticks the "remember me" box on the login form, they are issued
+/// a long-lived cookie that is used to log them back in once their session has
+/// ended. The cookie uses the same domain, path, `Secure`, and `SameSite`
+/// attributes as the [session cookie](Cookie), and is always inaccessible to
+/// JavaScript. See the [`remember`](super::remember) module for details.
+/// 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, SmartDefault)]
+pub struct Remember {
+	//		Public properties													
+	/// Whether persistent logins are enabled. If they are not, the "remember
+	/// me" box is ignored, and existing tokens are not accepted.
+	#[default = true]
+	pub enabled:      bool,
+	
+	/// The name of the cookie used to hold the token.
+	#[default = "remember"]
+	pub cookie_name:  String,
+	
+	/// How long a token remains valid after it is issued, in seconds. This is
+	/// not extended when the token is used, so the user will need to log in
+	/// again once it has elapsed.
+	#[default = 2_592_000]
+	pub lifetime:     u64,
+	
+	/// How long the previous value of a token continues to be accepted after
+	/// it has been replaced, in seconds. This allows for browsers sending
+	/// several requests at once with the same cookie. Use of an old value
+	/// outside of this period is treated as theft of the token.
+	#[default = 60]
+	pub grace_period: u64,
+}
+
+//		SessionKey																
+/// A key used to sign session cookies.
+/// 
+/// This is configured as a base64-encoded string, and is validated when the
+/// configuration is loaded, so that an unusable key is reported at startup
+/// rather than when the first request arrives. The key material is not shown
+/// when debug-formatted, to avoid it leaking into logs.
+/// 
+#[derive(Clone, Eq, PartialEq)]
+pub struct SessionKey(Vec<u8>);
+
+//󰭅		SessionKey																
+impl SessionKey {
+	/// The minimum length of the key, in bytes.
+	pub const MIN_LENGTH: usize = 64;
+	
+	//		to_key																
+	/// Converts the configured key into a cookie-signing key.
+	#[must_use]
+	pub fn to_key(&self) -> Key {
+		Key::from(&self.0)
+	}
+}
+
+//󰭅		Debug																	
+impl Debug for SessionKey {
+	//		fmt																	
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str("SessionKey(..)")
+	}
+}
+
+//󰭅		Deserialize																
+impl<'de> Deserialize<'de> for SessionKey {
+	//		deserialize															
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		let string = String::deserialize(deserializer)?;
+		let bytes  = BASE64.decode(string.trim()).map_err(|err| DeError::custom(format!("Invalid session key: {err}")))?;
+		if bytes.len() < Self::MIN_LENGTH {
+			return Err(DeError::custom(format!(
+				"Invalid session key: must be at least {} bytes, but is {}",
+				Self::MIN_LENGTH,
+				bytes.len(),
+			)));
+		}
+		Ok(Self(bytes))
+	}
+}
+
+//󰭅		Serialize																
+impl Serialize for SessionKey {
+	//		serialize															
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		serializer.serialize_str(&BASE64.encode(&self.0))
+	}
+}
+
+//		Totp																	
+/// The configuration for two-factor authentication using time-based one-time
+/// passwords (TOTP), as described by [RFC 6238](https://www.rfc-editor.org/rfc/rfc6238).
+/// 
+/// Users for whom [`User::requires_two_factor()`](super::middleware::User::requires_two_factor())
+/// returns `true` are asked for a code from their authenticator app, or a
+/// recovery code, once their password has been accepted. See the
+/// [`totp`](super::totp) module for details.
+/// 
+#[cfg(feature = "totp")]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, SmartDefault)]
+pub struct Totp {
+	//		Public properties													
+	/// The issuer shown alongside the account name in authenticator apps. This
+	/// must not contain a colon.
+	#[default = "Terracotta"]
+	pub issuer:           String,
+	
+	/// The number of digits in each code, which must be between 6 and 8.
+	/// Changing this will invalidate existing enrolments in most apps.
+	#[default = 6]
+	pub digits:           usize,
+	
+	/// How long each code is valid for, in seconds. Changing this will
+	/// invalidate existing enrolments in most apps.
+	#[default = 30]
+	pub step:             u64,
+	
+	/// The number of steps either side of the current one for which codes are
+	/// accepted, to allow for clock drift and the time taken to enter a code.
+	#[default = 1]
+	pub skew:             u8,
+	
+	/// The number of recovery codes issued on enrolment.
+	#[default = 10]
+	pub recovery_codes:   usize,
+	
+	/// How long the user has to enter a code once their password has been
+	/// accepted, in seconds, after which they need to log in again.
+	#[default = 300]
+	pub pending_lifetime: u64,
+	
+	/// The path of the second login step, which needs to be routed to
+	/// [`get_totp_login()`](super::totp::get_totp_login()) and
+	/// [`post_totp_login()`](super::totp::post_totp_login()).
+	#[default = "/login/totp"]
+	pub path:             String,
+}
+
+
+//		UncheckedJwt															
+/// The token configuration as loaded, before its key has been checked.
+#[cfg(feature = "jwt")]
+#[derive(Deserialize)]
+struct UncheckedJwt {
+	//		Private properties													
+	/// See [`Jwt::algorithm`].
+	algorithm: JwtAlgorithm,
+	
+	/// See [`Jwt::key`].
+	key:       String,
+	
+	/// See [`Jwt::issuer`].
+	issuer:    Option<String>,
+	
+	/// See [`Jwt::audience`].
+	audience:  Vec<String>,
+	
+	/// See [`Jwt::leeway`].
+	leeway:    u64,
+}
+
+
@@ -0,0 +1,280 @@
+#![allow(clippy::unused_async, reason = "Middleware functions need to be async")]
+
+//! Cross-site request forgery (CSRF) protection.
+//! 
+//! Each session is given a random token, which must be supplied with every
+//! request that uses an unsafe method, i.e. anything other than `GET`, `HEAD`,
+//! `OPTIONS`, or `TRACE`. The token can be supplied either in a form field
+//! called `csrf_token`, or in an `X-CSRF-Token` header, which is useful for
+//! JavaScript clients.
+//! 
+//! The token for the current session can be obtained using
+//! [`Context::csrf_token()`], and should be made available to templates so
+//! that it can be included in forms. The login page receives it as
+//! `CsrfToken`, and the same name is recommended for other templates:
+//! 
+//! ```html
+//! <input type="hidden" name="csrf_token" value="{{ CsrfToken }}">
+//! ```
+//! 
+//! Requests that carry a bearer token or an [API key](super::api_keys) are not
+//! checked, as browsers do not send those credentials automatically, and so
+//! such requests cannot be forged by another site. So that they cannot make
+//! use of the session cookie either, they are treated as anonymous until
+//! authenticated by the token or API key layers. HTTP Basic credentials, on
+//! the other hand, are remembered and sent automatically by browsers, and so
+//! requests using them are checked in the same way as those using a session.
+//! Machine clients that use unsafe methods should therefore use bearer tokens
+//! or API keys rather than Basic authentication.
+
+
+
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/csrf.rs"]
+mod tests;
+
+
+
+//		Packages																										
+
+use super::{
+	api_keys::supplied_key,
+	config::ApiKeys,
+	errors::AuthError,
+	middleware::{Context, User, forbidden},
+	state::StateProvider,
+	utility::{generate_token, tokens_match},
+};
+use axum::{
+	Extension,
+	body::{Body, to_bytes},
+	extract::State,
+	http::{HeaderMap, HeaderName, Method, Request, Uri, header::{AUTHORIZATION, CONTENT_TYPE}},
+	middleware::Next,
+	response::Response,
+};
+use std::sync::Arc;
+use tower_sessions::Session;
+use tracing::warn;
+use url::form_urlencoded;
+
+
+
+//		Constants																										
+
+/// The name of the form field used to supply the CSRF token.
+pub const CSRF_FIELD: &str = "csrf_token";
+
+/// The name of the header used to supply the CSRF token.
+pub const CSRF_HEADER: HeaderName = HeaderName::from_static("x-csrf-token");
+
+/// The name of the template variable that the CSRF token is made available
+/// as.
+pub const CSRF_TEMPLATE_VAR: &str = "CsrfToken";
+
+/// The maximum size of a form body that will be read to find the CSRF token.
+/// This matches the default body limit applied by Axum.
+const FORM_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// The key used to store the session's CSRF token.
+const SESSION_CSRF_TOKEN_KEY: &str = "_csrf_token";
+
+/// The number of random bytes in a CSRF token.
+const TOKEN_LENGTH: usize = 32;
+
+
+
+//		Functions																										
+
+//		authorization_scheme													
+/// Gets the scheme of the `Authorization` header, if there is one.
+/// 
+/// # Parameters
+/// 
+/// * `headers` - The request headers.
+/// 
+fn authorization_scheme(headers: &HeaderMap) -> Option<&str> {
+	headers.get(AUTHORIZATION)?.to_str().ok()?.split_whitespace().next()
+}
+
+//		clear_csrf_token														
+/// Clears the CSRF token for a session.
+/// 
+/// A new token will be generated the next time one is requested. This is done
+/// when a user logs in, so that a token obtained before authentication cannot
+/// be used afterwards.
+/// 
+/// # Parameters
+/// 
+/// * `session` - The session.
+/// 
+/// # Errors
+/// 
+/// If the session cannot be updated, an error will be returned.
+/// 
+pub async fn clear_csrf_token(session: &Session) -> Result<(), AuthError> {
+	drop(session.remove::<String>(SESSION_CSRF_TOKEN_KEY).await?);
+	Ok(())
+}
+
+//		csrf_layer																
+/// Validates CSRF tokens.
+/// 
+/// This layer is a middleware that checks that requests using unsafe methods
+/// supply the CSRF token for their session, in either the `csrf_token` form
+/// field or the `X-CSRF-Token` header. Requests that do not are rejected with
+/// a 403 response, and the rejection is logged.
+/// 
+/// Requests that carry a bearer token or an API key are not checked. Instead,
+/// their authentication context is replaced with an anonymous one without a
+/// session, so that they can only be authenticated by those credentials, by
+/// the token or API key layers, and not by the session cookie. Requests with
+/// HTTP Basic credentials but no session are rejected, as they cannot supply a
+/// token. Other requests without a session are not checked.
+/// 
+/// # Parameters
+/// 
+/// * `state`   - The application state.
+/// * `auth_cx` - The authentication context.
+/// * `request` - The request.
+/// * `next`    - The next middleware.
+/// 
+/// # Errors
+/// 
+/// If the session cannot be read, an error will be returned.
+/// 
+pub async fn csrf_layer<SP, U>(
+	State(state):       State<Arc<SP>>,
+	Extension(auth_cx): Extension<Context<U>>,
+	mut request:        Request<Body>,
+	next:               Next,
+) -> Result<Response, AuthError>
+where
+	SP: StateProvider,
+	U:  User,
+{
+	if is_safe_method(request.method()) {
+		return Ok(next.run(request).await);
+	}
+	if has_machine_credentials(&state.config().api_keys, request.headers(), request.uri()) {
+		drop(request.extensions_mut().insert(Context::<U>::without_session(None).with_client(auth_cx.client().clone())));
+		return Ok(next.run(request).await);
+	}
+	let format = auth_cx.response_format(request.extensions(), request.headers());
+	let Some(session) = auth_cx.session() else {
+		if authorization_scheme(request.headers()).is_some_and(|scheme| scheme.eq_ignore_ascii_case("Basic")) {
+			warn!("Rejected Basic request without a session to supply a CSRF token: {} {}", request.method(), request.uri());
+			return Ok(forbidden(format));
+		}
+		return Ok(next.run(request).await);
+	};
+	let expected            = session.get::<String>(SESSION_CSRF_TOKEN_KEY).await?;
+	let (supplied, rebuilt) = supplied_token(request).await;
+	if let (Some(expected_token), Some(supplied_token)) = (expected, supplied) {
+		if tokens_match(&expected_token, &supplied_token) {
+			return Ok(next.run(rebuilt).await);
+		}
+	}
+	warn!("Rejected request with missing or invalid CSRF token: {} {}", rebuilt.method(), rebuilt.uri());
+	Ok(forbidden(format))
+}
+
+//		csrf_token																
+/// Gets the CSRF token for a session.
+/// 
+/// If the session does not yet have a token, a new one is generated and
+/// stored in the session.
+/// 
+/// # Parameters
+/// 
+/// * `session` - The session.
+/// 
+/// # Errors
+/// 
+/// If the session cannot be read or written, an error will be returned.
+/// 
+pub async fn csrf_token(session: &Session) -> Result<String, AuthError> {
+	if let Some(token) = session.get::<String>(SESSION_CSRF_TOKEN_KEY).await? {
+		return Ok(token);
+	}
+	let token = generate_token(TOKEN_LENGTH);
+	session.insert(SESSION_CSRF_TOKEN_KEY, &token).await?;
+	Ok(token)
+}
+
+//		form_token																
+/// Finds the CSRF token in a URL-encoded form body.
+/// 
+/// # Parameters
+/// 
+/// * `body` - The form body.
+/// 
+fn form_token(body: &[u8]) -> Option<String> {
+	form_urlencoded::parse(body)
+		.find(|field| field.0 == CSRF_FIELD)
+		.map(|(_, value)| value.into_owned())
+}
+
+//		has_machine_credentials													
+/// Checks whether a request carries a bearer token or an API key.
+/// 
+/// These are not sent automatically by browsers, and so a request carrying
+/// them cannot have been forged by another site.
+/// 
+/// # Parameters
+/// 
+/// * `config`  - The API key configuration.
+/// * `headers` - The request headers.
+/// * `uri`     - The request URI.
+/// 
+fn has_machine_credentials(config: &ApiKeys, headers: &HeaderMap, uri: &Uri) -> bool {
+	authorization_scheme(headers).is_some_and(|scheme| scheme.eq_ignore_ascii_case("Bearer"))
+		|| supplied_key(config, headers, uri).is_some()
+}
+
+//		is_safe_method															
+/// Checks whether a request method is safe, i.e. does not change state.
+/// 
+/// # Parameters
+/// 
+/// * `method` - The request method.
+/// 
+const fn is_safe_method(method: &Method) -> bool {
+	matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE)
+}
+
+//		supplied_token															
+/// Gets the CSRF token supplied with a request.
+/// 
+/// The header is checked first, and then, if the request has a URL-encoded
+/// form body, the form field. Reading the form field requires the body to be
+/// buffered, and so the request is returned, rebuilt with the buffered body.
+/// 
+/// # Parameters
+/// 
+/// * `request` - The request.
+/// 
+async fn supplied_token(request: Request<Body>) -> (Option<String>, Request<Body>) {
+	if let Some(token) = request.headers().get(CSRF_HEADER).and_then(|value| value.to_str().ok()) {
+		return (Some(token.to_owned()), request);
+	}
+	let is_form = request.headers().get(CONTENT_TYPE)
+		.and_then(|value| value.to_str().ok())
+		.is_some_and(|value| value.starts_with("application/x-www-form-urlencoded"))
+	;
+	if !is_form {
+		return (None, request);
+	}
+	let (parts, body) = request.into_parts();
+	match to_bytes(body, FORM_BODY_LIMIT).await {
+		Ok(bytes) => (form_token(&bytes), Request::from_parts(parts, Body::from(bytes))),
+		Err(err)  => {
+			warn!("Failed to read form body to check CSRF token: {err}");
+			(None, Request::from_parts(parts, Body::empty()))
+		},
+	}
+}
+
+
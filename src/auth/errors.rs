@@ -9,6 +9,9 @@ use axum::{
 	http::{StatusCode, uri::InvalidUri},
 	response::{IntoResponse, Response},
 };
+use core::error::Error;
+#[cfg(feature = "jwt")]
+use jsonwebtoken::errors::Error as JwtError;
 use thiserror::Error as ThisError;
 use tower_sessions::session::Error as SessionError;
 
@@ -22,10 +25,41 @@ use tower_sessions::session::Error as SessionError;
 #[derive(Debug, ThisError)]
 #[non_exhaustive]
 pub enum AuthError {
+	/// An operation that requires a session was attempted without one, such
+	/// as logging in when authenticating using a bearer token.
+	#[error("No session available")]
+	NoSession,
+	
+	/// Error when logging in using an OpenID Connect provider.
+	#[cfg(feature = "oidc")]
+	#[error("OIDC error: {0}")]
+	OidcError(#[from] OidcError),
+	
+	/// Error when accessing the store of remember-me tokens, or when
+	/// converting the user ID held in a token.
+	#[error("Remember-me token store error: {0}")]
+	RememberStoreError(Box<dyn Error + Send + Sync>),
+	
+	/// Error when accessing the store of users' revocation generations, or
+	/// when serialising a user ID for it.
+	#[error("Revocation store error: {0}")]
+	RevocationStoreError(Box<dyn Error + Send + Sync>),
+	
 	/// Error when handling sessions.
 	#[error("Session error: {0}")]
 	SessionError(#[from] SessionError),
 	
+	/// The key configured for verifying bearer tokens is missing or invalid.
+	#[cfg(feature = "jwt")]
+	#[error("Token key error: {0}")]
+	TokenKeyError(JwtError),
+	
+	/// Error when setting up or checking time-based one-time passwords, for
+	/// instance because the stored secret is invalid.
+	#[cfg(feature = "totp")]
+	#[error("TOTP error: {0}")]
+	TotpError(String),
+	
 	/// There was a problem parsing the URL.
 	#[error("URL error: {0}")]
 	UrlError(#[from] InvalidUri),
@@ -33,6 +67,10 @@ pub enum AuthError {
 	/// There was a problem constructing a URL from parts.
 	#[error("URL error: {0}")]
 	UrlPartsError(#[from] InvalidUriParts),
+	
+	/// The user provider failed to look up a user.
+	#[error("User provider error: {0}")]
+	UserProviderError(Box<dyn Error + Send + Sync>),
 }
 
 //󰭅		IntoResponse															
@@ -46,4 +84,52 @@ impl IntoResponse for AuthError {
 	}
 }
 
+//		OidcError																
+/// Represents all possible errors that can occur when logging in using an
+/// OpenID Connect provider.
+#[cfg(feature = "oidc")]
+#[derive(Clone, Debug, Eq, PartialEq, ThisError)]
+#[non_exhaustive]
+pub enum OidcError {
+	/// The provider could not be contacted.
+	#[error("Failed to contact the OIDC provider: {0}")]
+	Request(String),
+	
+	/// The provider sent a response that could not be used.
+	#[error("Invalid response from the OIDC provider: {0}")]
+	InvalidResponse(String),
+	
+	/// The ID token was invalid.
+	#[error("Invalid ID token: {0}")]
+	InvalidIdToken(String),
+	
+	/// The callback did not match a login started in the same session.
+	#[error("Invalid or missing OIDC login state")]
+	InvalidState,
+	
+	/// The provider did not authenticate the user, for instance because they
+	/// declined to grant access.
+	#[error("OIDC login rejected by the provider: {0}")]
+	Rejected(String),
+}
+
+//		PasswordError															
+/// Represents all possible errors that can occur when hashing or verifying
+/// passwords.
+#[derive(Clone, Debug, Eq, PartialEq, ThisError)]
+#[non_exhaustive]
+pub enum PasswordError {
+	/// The password could not be hashed.
+	#[error("Failed to hash password: {0}")]
+	FailedToHash(String),
+	
+	/// The password hash could not be parsed.
+	#[error("Invalid password hash: {0}")]
+	InvalidHash(String),
+	
+	/// The password hash uses an algorithm that is not supported.
+	#[error("Unsupported password hash algorithm: {0}")]
+	UnsupportedAlgorithm(String),
+}
+
 
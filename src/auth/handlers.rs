@@ -8,27 +8,36 @@
 //		Packages																										
 
 use super::{
+	audit::{AuthEvent, LoginMethod, record_event},
+	csrf::{CSRF_TEMPLATE_VAR, csrf_token},
 	errors::AuthError,
 	middleware::{Context, Credentials, User, UserProvider},
+	remember::{forget_login, remember_login},
 	requests::PostLogin,
 	state::StateProvider,
-	utility::{build_uri, extract_uri_query_parts},
+	utility::{build_uri, extract_uri_query_parts, redirect_target, safe_redirect_uri},
 };
 use crate::app::{
 	errors::AppError,
 	state::StateProvider as AppStateProvider,
 };
 use axum::{
+	Extension,
 	Form,
-	extract::State,
-	http::Uri,
-	response::{Html, Redirect},
+	extract::{ConnectInfo, State},
+	http::{HeaderMap, Uri, header::SET_COOKIE},
+	response::{AppendHeaders, Html, IntoResponse as _, Redirect, Response},
 };
+use core::net::SocketAddr;
 use rubedo::sugar::s;
 use std::sync::Arc;
 use tera::Context as Template;
+use tower_sessions::Session;
 use tracing::{info, warn};
 
+#[cfg(feature = "totp")]
+use super::totp::start_totp_login;
+
 
 
 //		Functions																										
@@ -38,10 +47,14 @@ use tracing::{info, warn};
 /// 
 /// Renders the login template.
 /// 
+/// If there is a session, its [CSRF token](super::csrf) is made available to
+/// the template as `CsrfToken`, for inclusion in the login form.
+/// 
 /// # Parameters
 /// 
-/// * `state` - The application state.
-/// * `uri`   - The request URI.
+/// * `state`   - The application state.
+/// * `session` - The session, if any.
+/// * `uri`     - The request URI.
 /// 
 /// # Errors
 /// 
@@ -49,6 +62,7 @@ use tracing::{info, warn};
 /// 
 pub async fn get_login<SP: AppStateProvider>(
 	State(state): State<Arc<SP>>,
+	session:      Option<Extension<Session>>,
 	mut uri:      Uri,
 ) -> Result<Html<String>, AppError> {
 	let mut params = extract_uri_query_parts(&uri);
@@ -57,11 +71,20 @@ pub async fn get_login<SP: AppStateProvider>(
 		failed     = true;
 		drop(params.remove("failed"));
 	}
+	let mut locked = false;
+	if params.contains_key("locked") {
+		locked     = true;
+		drop(params.remove("locked"));
+	}
 	uri              = build_uri(uri.path(), &params).map_err(AuthError::from)?;
 	let mut template = Template::new();
 	template.insert("Title",   &state.title());
 	template.insert("PageURL", &uri.path_and_query().map_or_else(|| s!("/"), ToString::to_string));
 	template.insert("Failed",  &failed);
+	template.insert("Locked",  &locked);
+	if let Some(Extension(ref current)) = session {
+		template.insert(CSRF_TEMPLATE_VAR, &csrf_token(current).await?);
+	}
 	Ok(Html(state.render("login", &template).await?))
 }
 
@@ -72,11 +95,31 @@ pub async fn get_login<SP: AppStateProvider>(
 /// requested page. Otherwise, it redirects back to the login page with a
 /// `failed` parameter.
 /// 
+/// Failed attempts are counted against the username, as given by
+/// [`Credentials::to_loggable_string()`], and against the client's IP address,
+/// if known. Once either is locked out, according to the configured
+/// [lockout policy](super::config::Lockout), attempts are rejected without the
+/// credentials being checked, and the redirect has a `locked` parameter
+/// instead. The client's IP address is only known if the server has been
+/// started with connection info, as is the case with
+/// [`server()`](crate::app::create::server()).
+/// 
+/// If the "remember me" box was ticked, and persistent logins are enabled, a
+/// [remember-me token](super::remember) is issued in a cookie, so that the user
+/// will be logged back in once their session ends.
+/// 
+/// If the `totp` feature is enabled and the user
+/// [requires a second factor](User::requires_two_factor()), they are not
+/// logged in yet. Instead, they are redirected to the
+/// [second login step](super::totp), and the failure count is not reset until
+/// that has been completed.
+/// 
 /// # Parameters
 /// 
-/// * `state` - The application state.
-/// * `auth`  - The authentication context.
-/// * `login` - The login form.
+/// * `state`        - The application state.
+/// * `connect_info` - The connection info, if available.
+/// * `auth`         - The authentication context.
+/// * `login`        - The login form.
 /// 
 /// # Errors
 /// 
@@ -84,49 +127,94 @@ pub async fn get_login<SP: AppStateProvider>(
 /// 
 pub async fn post_login<SP, C, U, UP>(
 	State(state): State<Arc<SP>>,
+	connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
 	mut auth:     Context<U>,
 	Form(login):  Form<PostLogin<C>>,
-) -> Result<Redirect, AuthError>
+) -> Result<Response, AuthError>
 where
 	SP: StateProvider,
 	C:  Credentials,
 	U:  User,
 	UP: UserProvider<Credentials = C, User = U>,
 {
-	let uri        = login.uri.parse::<Uri>()?;
-	let mut params = extract_uri_query_parts(&uri);
-	if let Some(ref user) = UP::find_by_credentials(&*state, &login.credentials) {
+	let uri                = safe_redirect_uri(&login.uri, &state.config().redirects.allowed_origins);
+	let mut params         = extract_uri_query_parts(&uri);
+	let credentials_string = login.credentials.to_loggable_string();
+	let ip                 = connect_info.map(|Extension(ConnectInfo(address))| address.ip());
+	let policy             = &state.config().lockout;
+	let mut cookie         = None;
+	if let Some(remaining) = state.lockout().check(policy, &credentials_string, ip) {
+		drop(params.insert(s!("locked"), s!("")));
+		warn!("Rejected locked out login attempt for user: {} ({}s remaining)", &credentials_string, remaining.as_secs());
+		record_event(&*state, AuthEvent::LockedOut { user: credentials_string.clone(), seconds: remaining.as_secs() }, auth.client()).await;
+	} else if let Some(ref user) = UP::find_by_credentials(&*state, &login.credentials).await
+		.map_err(|err| AuthError::UserProviderError(Box::new(err)))?
+	{
+		#[cfg(feature = "totp")]
+		if user.requires_two_factor() {
+			info!("Awaiting second factor for user: {}", user.to_loggable_string());
+			start_totp_login(&auth, user, &uri.to_string(), login.remember).await?;
+			return Ok(Redirect::to(&state.config().totp.path).into_response());
+		}
 		info!("Logging in user: {}", user.to_loggable_string());
-		auth.login(user).await?;
+		state.lockout().record_success(&credentials_string);
+		auth.login(&*state, user).await?;
+		record_event(&*state, AuthEvent::Login { user: user.to_loggable_string(), method: LoginMethod::Password }, auth.client()).await;
+		if login.remember && state.config().remember.enabled {
+			cookie = Some(remember_login(&*state, user).await?);
+		}
 	} else {
-		drop(params.insert(s!("failed"), s!("")));
-		let credentials_string = login.credentials.to_loggable_string();
+		let lockout = state.lockout().record_failure(policy, &credentials_string, ip);
+		drop(params.insert(if lockout.is_some() { s!("locked") } else { s!("failed") }, s!("")));
 		warn!("Failed login attempt for user: {}", &credentials_string);
+		record_event(&*state, AuthEvent::LoginFailed { user: credentials_string.clone(), method: LoginMethod::Password }, auth.client()).await;
+		if let Some(duration) = lockout {
+			record_event(&*state, AuthEvent::LockedOut { user: credentials_string.clone(), seconds: duration.as_secs() }, auth.client()).await;
+		}
 	}
-	Ok(Redirect::to(
-		&build_uri(uri.path(), &params)?
-			.path_and_query()
-			.map_or_else(|| s!("/"), ToString::to_string)
-	))
+	Ok((
+		AppendHeaders(cookie.map(|set| (SET_COOKIE, set.to_string()))),
+		Redirect::to(&redirect_target(&uri, &params)?),
+	).into_response())
 }
 
 //		get_logout																
 /// Logs the user out.
 /// 
-/// Logs the user out, and redirects to the home page.
+/// Logs the user out, and redirects to the home page. Any
+/// [remember-me token](super::remember) supplied with the request is forgotten,
+/// and removed from the browser.
 /// 
 /// # Parameters
 /// 
-/// * `auth` - The authentication context.
+/// * `state`   - The application state.
+/// * `headers` - The request headers.
+/// * `auth`    - The authentication context.
+/// 
+/// # Errors
+/// 
+/// If the session or the remember-me token cannot be deleted, an error will be
+/// returned.
 /// 
-pub async fn get_logout<U: User>(
-	auth: Context<U>,
-) -> Redirect {
+pub async fn get_logout<SP, U>(
+	State(state): State<Arc<SP>>,
+	headers:      HeaderMap,
+	auth:         Context<U>,
+) -> Result<Response, AuthError>
+where
+	SP: StateProvider,
+	U:  User,
+{
 	if let Some(ref user) = auth.current_user {
 		info!("Logging out user: {}", user.to_loggable_string());
+		record_event(&*state, AuthEvent::Logout { user: user.to_loggable_string() }, auth.client()).await;
 	}
-	auth.logout().await;
-	Redirect::to("/")
+	let cookie = forget_login(&*state, &headers).await?;
+	auth.logout().await?;
+	Ok((
+		AppendHeaders(cookie.map(|set| (SET_COOKIE, set.to_string()))),
+		Redirect::to("/"),
+	).into_response())
 }
 
 
@@ -0,0 +1,196 @@
+#![allow(clippy::unused_async, reason = "Handler functions need to be async")]
+
+//! Impersonation of other users.
+//! 
+//! Users with the [configured permission](super::config::Impersonation) can
+//! assume the identity of another user, for instance so that support staff
+//! can see the application as that user does. While impersonating, the
+//! [current user](super::middleware::Context::current_user) is the user being
+//! impersonated, so that routes and permissions behave exactly as they would
+//! for them, and the original user is retained in the session, and is
+//! available from [`Context::impersonator()`]. Both are made available to
+//! templates by [`Context::add_template_vars()`], so that pages can show that
+//! impersonation is in progress.
+//! 
+//! Impersonation is started using [`post_impersonate()`], and ended using
+//! [`post_stop_impersonating()`], or by logging out. Users who hold the
+//! permission themselves cannot be impersonated, and if the original user
+//! loses the permission, any impersonation in progress is ended. The start and
+//! end of each impersonation, and every request made while impersonating, are
+//! recorded as [audit events](super::audit).
+
+
+
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/impersonation.rs"]
+mod tests;
+
+
+
+//		Packages																										
+
+use super::{
+	audit::{AuthEvent, record_event},
+	config::Impersonation,
+	errors::AuthError,
+	middleware::{Context, User, UserProvider, forbidden},
+	state::StateProvider,
+	utility::safe_redirect_uri,
+};
+use axum::{
+	Form,
+	extract::State,
+	http::{Extensions, HeaderMap},
+	response::{IntoResponse as _, Redirect, Response},
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+
+
+//		Structs																											
+
+//		PostImpersonate															
+/// The data sent by the form used to start impersonating a user.
+/// 
+/// This is consumed by the [`post_impersonate()`] handler.
+/// 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct PostImpersonate<I> {
+	//		Public properties													
+	/// The ID of the user to impersonate.
+	pub user_id: I,
+	
+	/// The URL to redirect to once impersonation has started. If this is not
+	/// given, the user is redirected to the home page.
+	#[serde(default)]
+	pub uri:     Option<String>,
+}
+
+
+
+//		Functions																										
+
+//		can_impersonate															
+/// Checks whether one user is allowed to impersonate another.
+/// 
+/// The user must hold the configured permission, and the target must not. The
+/// target must also not hold any permissions or roles that the user does not
+/// hold themselves, so that impersonation cannot be used to gain privileges.
+/// Users cannot impersonate themselves.
+/// 
+/// # Parameters
+/// 
+/// * `config` - The impersonation policy.
+/// * `user`   - The user who wants to impersonate the target.
+/// * `target` - The user to be impersonated.
+/// 
+#[must_use]
+pub fn can_impersonate<U: User>(config: &Impersonation, user: &U, target: &U) -> bool {
+	user.has_permission(&config.permission)
+		&& !target.has_permission(&config.permission)
+		&& target.permissions().iter().all(|permission| user.has_permission(permission))
+		&& target.roles().iter().all(|role| user.has_role(role))
+		&& user.id().to_string() != target.id().to_string()
+}
+
+//		post_impersonate														
+/// Starts impersonating another user.
+/// 
+/// Checks that the current user is [allowed](can_impersonate()) to
+/// impersonate the requested user, and if so, starts impersonating them and
+/// redirects to the requested page, or to the home page. Otherwise, a 403
+/// response is returned, in the form given by the request's
+/// [`ResponseFormat`](super::responses::ResponseFormat).
+/// 
+/// # Parameters
+/// 
+/// * `state`      - The application state.
+/// * `extensions` - The request extensions.
+/// * `headers`    - The request headers.
+/// * `auth`       - The authentication context.
+/// * `form`       - The impersonation form.
+/// 
+/// # Errors
+/// 
+/// If the user lookup fails, or the session cannot be written, an error will
+/// be returned.
+/// 
+pub async fn post_impersonate<SP, U, UP>(
+	State(state): State<Arc<SP>>,
+	extensions:   Extensions,
+	headers:      HeaderMap,
+	mut auth:     Context<U>,
+	Form(form):   Form<PostImpersonate<U::Id>>,
+) -> Result<Response, AuthError>
+where
+	SP: StateProvider,
+	U:  User,
+	UP: UserProvider<User = U>,
+{
+	let format = auth.response_format(&extensions, &headers);
+	let Some(user) = auth.current_user.clone() else {
+		return Ok(forbidden(format));
+	};
+	let Some(target) = UP::find_by_id(&*state, &form.user_id).await
+		.map_err(|err| AuthError::UserProviderError(Box::new(err)))?
+		.filter(|found| can_impersonate(&state.config().impersonation, &user, found))
+	else {
+		warn!("Impersonation of user {} denied to user: {}", form.user_id, user.to_loggable_string());
+		return Ok(forbidden(format));
+	};
+	let target_string = target.to_loggable_string();
+	if !auth.impersonate(target).await? {
+		return Ok(forbidden(format));
+	}
+	info!("User {} started impersonating user: {}", user.to_loggable_string(), target_string);
+	record_event(&*state, AuthEvent::ImpersonationStarted {
+		user:   user.to_loggable_string(),
+		target: target_string,
+	}, auth.client()).await;
+	let uri = safe_redirect_uri(form.uri.as_deref().unwrap_or("/"), &state.config().redirects.allowed_origins);
+	Ok(Redirect::to(&uri.to_string()).into_response())
+}
+
+//		post_stop_impersonating													
+/// Stops impersonating another user.
+/// 
+/// The original user becomes the current user again, and is redirected to the
+/// home page. If no impersonation is in progress, this just redirects. As this
+/// changes the session, it only accepts `POST` requests, so that it is covered
+/// by [CSRF protection](super::csrf) in the same way as [`post_impersonate()`].
+/// 
+/// # Parameters
+/// 
+/// * `state` - The application state.
+/// * `auth`  - The authentication context.
+/// 
+/// # Errors
+/// 
+/// If the session cannot be written, an error will be returned.
+/// 
+pub async fn post_stop_impersonating<SP, U>(
+	State(state): State<Arc<SP>>,
+	mut auth:     Context<U>,
+) -> Result<Response, AuthError>
+where
+	SP: StateProvider,
+	U:  User,
+{
+	if let Some(target) = auth.stop_impersonating().await? {
+		if let Some(ref user) = auth.current_user {
+			info!("User {} stopped impersonating user: {}", user.to_loggable_string(), target.to_loggable_string());
+			record_event(&*state, AuthEvent::ImpersonationStopped {
+				user:   user.to_loggable_string(),
+				target: target.to_loggable_string(),
+			}, auth.client()).await;
+		}
+	}
+	Ok(Redirect::to("/").into_response())
+}
+
+
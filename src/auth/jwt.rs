@@ -0,0 +1,280 @@
+#![allow(clippy::unused_async, reason = "Middleware functions need to be async")]
+
+//! Bearer token authentication using JSON Web Tokens.
+//! 
+//! This provides an alternative to session-based authentication, suitable for
+//! APIs. Tokens are supplied in the `Authorization: Bearer` header, and are
+//! verified against the key configured in [`Jwt`]. The signature, algorithm,
+//! expiry (`exp`), not-before time (`nbf`), audience (`aud`), and issuer (`iss`)
+//! are all checked, and the claims are then mapped to a [`User`] by a
+//! [`TokenUserProvider`].
+
+
+
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/jwt.rs"]
+mod tests;
+
+
+
+//		Packages																										
+
+use super::{
+	config::{Jwt, JwtAlgorithm},
+	errors::AuthError,
+	middleware::{Context, User, UserProvider},
+	responses::Problem,
+	state::StateProvider,
+};
+use axum::{
+	body::Body,
+	extract::{FromRequestParts, State},
+	http::{HeaderMap, Request, header::AUTHORIZATION, request::Parts},
+	middleware::Next,
+	response::Response,
+};
+use core::{
+	fmt::{Debug, Formatter, self},
+	future::Future,
+};
+use jsonwebtoken::{
+	Algorithm,
+	DecodingKey,
+	Validation,
+	decode,
+	errors::{Error as JwtError, ErrorKind as JwtErrorKind},
+};
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+use tracing::info;
+
+
+
+//		Structs																											
+
+//		BearerToken																
+/// A bearer token extracted from the `Authorization` header.
+/// 
+/// This extractor obtains the raw token from a request, without verifying it.
+/// If the header is missing or is not a bearer token, the request is rejected
+/// with a 401 response asking for a bearer token, with a JSON [`Problem`] body.
+/// 
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[expect(clippy::exhaustive_structs, reason = "Simple wrapper for destructuring as an extractor")]
+pub struct BearerToken(pub String);
+
+//󰭅		FromRequestParts														
+impl<S> FromRequestParts<S> for BearerToken
+where
+	S: Send + Sync,
+{
+	type Rejection = Response;
+	
+	//		from_request_parts													
+	/// Creates a bearer token from the request parts.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `parts` - The request parts.
+	/// * `state` - The application state.
+	/// 
+	async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+		bearer_token(&parts.headers)
+			.map(|token| Self(token.to_owned()))
+			.ok_or_else(|| Problem::unauthorized("Bearer"))
+	}
+}
+
+//		TokenVerifier															
+/// Verifies bearer tokens against the configured key.
+/// 
+/// The key is parsed, and the validation rules are prepared, once when the
+/// verifier is created, rather than for each token. The key material is not
+/// shown when debug-formatted, to avoid it leaking into logs.
+/// 
+#[derive(Clone)]
+pub struct TokenVerifier {
+	//		Private properties													
+	/// The key that token signatures are verified with.
+	key:        DecodingKey,
+	
+	/// The rules that tokens are validated against.
+	validation: Validation,
+}
+
+//󰭅		TokenVerifier															
+impl TokenVerifier {
+	//		new																	
+	/// Creates a new token verifier from the token configuration.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `config` - The token configuration.
+	/// 
+	/// # Errors
+	/// 
+	/// If the configured key is missing or invalid, an error will be returned.
+	/// 
+	pub fn new(config: &Jwt) -> Result<Self, JwtError> {
+		let key                 = config.decoding_key()?;
+		let mut validation      = Validation::new(match config.algorithm {
+			JwtAlgorithm::Hs256 => Algorithm::HS256,
+			JwtAlgorithm::Rs256 => Algorithm::RS256,
+			JwtAlgorithm::EdDsa => Algorithm::EdDSA,
+		});
+		validation.leeway       = config.leeway;
+		validation.validate_nbf = true;
+		validation.validate_aud = !config.audience.is_empty();
+		if !config.audience.is_empty() {
+			validation.set_audience(&config.audience);
+		}
+		if let Some(ref issuer) = config.issuer {
+			validation.set_issuer(&[issuer]);
+		}
+		Ok(Self { key, validation })
+	}
+	
+	//		decode																
+	/// Verifies a token and decodes its claims.
+	/// 
+	/// Returns [`Some(Claims)`](Some) if the token is valid, otherwise returns
+	/// [`None`]. The reason for an invalid token is logged, but is not returned,
+	/// as it should not be revealed to the client.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `token` - The token to verify.
+	/// 
+	#[must_use]
+	pub fn decode<C: DeserializeOwned>(&self, token: &str) -> Option<C> {
+		match decode::<C>(token, &self.key, &self.validation) {
+			Ok(data) => Some(data.claims),
+			Err(err) => {
+				info!("Invalid bearer token: {err}");
+				None
+			},
+		}
+	}
+}
+
+//󰭅		Debug																	
+impl Debug for TokenVerifier {
+	//		fmt																	
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.debug_struct("TokenVerifier")
+			.field("validation", &self.validation)
+			.finish_non_exhaustive()
+	}
+}
+
+
+
+//		Traits																											
+
+//§		TokenUserProvider														
+/// A trait for providing user data from token claims.
+/// 
+/// This is used by [`token_auth_layer()`] to map the claims of a verified token
+/// to a user. The claims type can contain any claims required, and the user
+/// could be constructed directly from them, or looked up using them.
+/// 
+pub trait TokenUserProvider: UserProvider {
+	/// The claims data type. This is the type that the token payload will be
+	/// deserialised into, once the token has been verified.
+	type Claims: DeserializeOwned + Send + Sync + 'static;
+	
+	//		find_by_claims														
+	/// Finds a user by the claims of a verified token.
+	/// 
+	/// Returns [`Some(User)`](Some) if the claims identify a valid user,
+	/// otherwise returns [`None`].
+	/// 
+	/// # Parameters
+	/// 
+	/// * `state`  - The application state.
+	/// * `claims` - The claims of the verified token.
+	/// 
+	/// # Errors
+	/// 
+	/// If the lookup fails, an error will be returned.
+	/// 
+	fn find_by_claims<SP: StateProvider>(
+		state:  &SP,
+		claims: &Self::Claims,
+	) -> impl Future<Output = Result<Option<Self::User>, Self::Error>> + Send;
+}
+
+
+
+//		Functions																										
+
+//		bearer_token															
+/// Gets the bearer token from the request headers, if there is one.
+/// 
+/// # Parameters
+/// 
+/// * `headers` - The request headers.
+/// 
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+	let value          = headers.get(AUTHORIZATION)?.to_str().ok()?;
+	let (scheme, rest) = value.split_once(' ')?;
+	let token          = rest.trim();
+	(scheme.eq_ignore_ascii_case("Bearer") && !token.is_empty()).then_some(token)
+}
+
+//		token_auth_layer														
+/// Prepare the authentication context from a bearer token.
+/// 
+/// This layer is a middleware that is used to set up the authentication
+/// context for token-based authentication. It verifies the bearer token in the
+/// `Authorization` header, maps its claims to a user, and stores a context
+/// without a session in the request's extensions, so that it can be used by
+/// the route handlers and by [`protect()`](super::middleware::protect()).
+/// 
+/// If there is no bearer token and an authentication context has already been
+/// set up, for instance by session-based authentication, it is left as-is.
+/// 
+/// # Parameters
+/// 
+/// * `state`    - The application state.
+/// * `verifier` - The token verifier, if the configured key could be parsed.
+/// * `request`  - The request.
+/// * `next`     - The next middleware.
+/// 
+/// # Errors
+/// 
+/// If the configured key is invalid, or the user lookup fails, an error will
+/// be returned.
+/// 
+pub async fn token_auth_layer<SP, U, UP>(
+	State((state, verifier)): State<(Arc<SP>, Option<Arc<TokenVerifier>>)>,
+	mut request:              Request<Body>,
+	next:                     Next,
+) -> Result<Response, AuthError>
+where
+	SP: StateProvider,
+	U:  User,
+	UP: TokenUserProvider<User = U>,
+{
+	let Some(token) = bearer_token(request.headers()) else {
+		if request.extensions().get::<Context<U>>().is_none() {
+			drop(request.extensions_mut().insert(Context::<U>::without_session(None)));
+		}
+		return Ok(next.run(request).await);
+	};
+	let user = match verifier.ok_or_else(|| AuthError::TokenKeyError(JwtErrorKind::InvalidKeyFormat.into()))?.decode::<UP::Claims>(token) {
+		Some(claims) => UP::find_by_claims(&*state, &claims).await
+			.map_err(|err| AuthError::UserProviderError(Box::new(err)))?
+		,
+		None         => None,
+	};
+	if let Some(ref current) = user {
+		info!("Current token user: {}", current.id());
+	}
+	drop(request.extensions_mut().insert(Context::<U>::without_session(user)));
+	Ok(next.run(request).await)
+}
+
+
@@ -0,0 +1,323 @@
+//! Brute-force protection for logins.
+//! 
+//! Failed login attempts are tracked per username and per IP address, and once
+//! the configured threshold is reached, further attempts are locked out for a
+//! period that increases exponentially with each subsequent failure. The policy
+//! is configured using [`Lockout`](super::config::Lockout).
+//! 
+//! Tracking is held in memory, and so is specific to each instance of the
+//! application, and is reset when the application restarts.
+
+
+
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/lockout.rs"]
+mod tests;
+
+
+
+//		Packages																										
+
+use super::config::Lockout as LockoutConfig;
+use core::{
+	fmt::{Debug, Display, Formatter, self},
+	net::IpAddr,
+	time::Duration,
+};
+use parking_lot::Mutex;
+use std::{
+	collections::HashMap,
+	sync::Arc,
+	time::Instant,
+};
+use tracing::warn;
+
+
+
+//		Enums																											
+
+//		LockoutKey																
+/// The identity that failed login attempts are counted against.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[expect(clippy::exhaustive_enums, reason = "Exhaustive")]
+pub enum LockoutKey {
+	/// The IP address that the attempts came from.
+	Ip(IpAddr),
+	
+	/// The username that the attempts were made for.
+	Username(String),
+}
+
+//󰭅		Display																	
+impl Display for LockoutKey {
+	//		fmt																	
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		match *self {
+			Self::Ip(ref ip)             => write!(f, "IP address {ip}"),
+			Self::Username(ref username) => write!(f, "username {username}"),
+		}
+	}
+}
+
+
+
+//		Structs																											
+
+//		Attempts																
+/// The failed login attempts recorded against a [`LockoutKey`].
+#[derive(Clone, Copy, Debug)]
+struct Attempts {
+	//		Private properties													
+	/// The number of failures within the current window.
+	failures:     u32,
+	
+	/// The time of the most recent failure.
+	last_failure: Instant,
+	
+	/// The time at which the current lockout ends, if there is one.
+	locked_until: Option<Instant>,
+}
+
+//		LockoutEvent															
+/// Details of a lockout, as passed to the lockout hook.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct LockoutEvent {
+	//		Public properties													
+	/// The identity that has been locked out.
+	pub key:      LockoutKey,
+	
+	/// The number of failures that led to the lockout.
+	pub failures: u32,
+	
+	/// How long the lockout will last.
+	pub duration: Duration,
+}
+
+//		LockoutTracker															
+/// Tracks failed login attempts, and determines when to lock logins out.
+/// 
+/// A hook can be registered using [`with_hook()`](LockoutTracker::with_hook()),
+/// which will be called whenever a lockout begins, for instance to notify an
+/// administrator or an external monitoring system.
+/// 
+#[derive(Default)]
+pub struct LockoutTracker {
+	//		Private properties													
+	/// The failed attempts recorded against each identity.
+	attempts: Mutex<HashMap<LockoutKey, Attempts>>,
+	
+	/// The hook to call when a lockout begins.
+	hook:     Option<Arc<dyn LockoutHook>>,
+}
+
+//󰭅		LockoutTracker															
+impl LockoutTracker {
+	//		new																	
+	/// Creates a new lockout tracker.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+	
+	//		with_hook															
+	/// Sets the hook to call when a lockout begins.
+	/// 
+	/// The hook can be any [`LockoutHook`] implementation, including a closure
+	/// that accepts a [`LockoutEvent`].
+	/// 
+	/// # Parameters
+	/// 
+	/// * `hook` - The hook to call.
+	/// 
+	#[must_use]
+	pub fn with_hook<H: LockoutHook + 'static>(mut self, hook: H) -> Self {
+		self.hook = Some(Arc::new(hook));
+		self
+	}
+	
+	//		check																
+	/// Checks whether a login attempt is currently locked out.
+	/// 
+	/// Returns the time remaining until the attempt will be allowed, if either
+	/// the username or the IP address is locked out, otherwise [`None`].
+	/// 
+	/// # Parameters
+	/// 
+	/// * `config`   - The lockout policy.
+	/// * `username` - The username that the attempt is for.
+	/// * `ip`       - The IP address that the attempt is from, if known.
+	/// 
+	#[must_use]
+	pub fn check(&self, config: &LockoutConfig, username: &str, ip: Option<IpAddr>) -> Option<Duration> {
+		if !config.enabled {
+			return None;
+		}
+		let now      = Instant::now();
+		let attempts = self.attempts.lock();
+		keys(username, ip)
+			.filter_map(|(key, _)| attempts.get(&key)?.locked_until?.checked_duration_since(now))
+			.filter(|remaining| !remaining.is_zero())
+			.max()
+	}
+	
+	//		record_failure														
+	/// Records a failed login attempt.
+	/// 
+	/// The failure is counted against both the username and the IP address.
+	/// If either reaches its threshold, it is locked out, the lockout is logged,
+	/// and the hook is called.
+	/// 
+	/// Returns the duration of the lockout, if one has begun, otherwise
+	/// [`None`].
+	/// 
+	/// # Parameters
+	/// 
+	/// * `config`   - The lockout policy.
+	/// * `username` - The username that the attempt was for.
+	/// * `ip`       - The IP address that the attempt was from, if known.
+	/// 
+	pub fn record_failure(&self, config: &LockoutConfig, username: &str, ip: Option<IpAddr>) -> Option<Duration> {
+		if !config.enabled {
+			return None;
+		}
+		let now          = Instant::now();
+		let window       = Duration::from_secs(config.window);
+		let mut events   = vec![];
+		let mut attempts = self.attempts.lock();
+		//	Forget about identities that have not failed recently
+		attempts.retain(|_, recorded| {
+			recorded.locked_until.is_some_and(|until| until > now) || now.duration_since(recorded.last_failure) < window
+		});
+		for (key, is_ip) in keys(username, ip) {
+			let threshold         = if is_ip { config.ip_threshold } else { config.user_threshold };
+			let recorded          = attempts.entry(key.clone()).or_insert(Attempts {
+				failures:     0,
+				last_failure: now,
+				locked_until: None,
+			});
+			recorded.failures     = recorded.failures.saturating_add(1);
+			recorded.last_failure = now;
+			if recorded.failures >= threshold {
+				let duration          = lockout_duration(config, recorded.failures.saturating_sub(threshold));
+				recorded.locked_until = now.checked_add(duration);
+				events.push(LockoutEvent {
+					key,
+					failures: recorded.failures,
+					duration,
+				});
+			}
+		}
+		drop(attempts);
+		for event in &events {
+			warn!("Locking out {} for {}s after {} failed login attempts", event.key, event.duration.as_secs(), event.failures);
+			if let Some(ref hook) = self.hook {
+				hook.on_lockout(event);
+			}
+		}
+		events.into_iter().map(|event| event.duration).max()
+	}
+	
+	//		record_success														
+	/// Records a successful login.
+	/// 
+	/// This clears any failures recorded against the username. Failures
+	/// recorded against the IP address are left to expire by themselves, as
+	/// otherwise an attacker could reset them by periodically logging in to an
+	/// account of their own while guessing the passwords of others.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `username` - The username that logged in.
+	/// 
+	pub fn record_success(&self, username: &str) {
+		_ = self.attempts.lock().remove(&LockoutKey::Username(username.to_owned()));
+	}
+}
+
+//󰭅		Debug																	
+impl Debug for LockoutTracker {
+	//		fmt																	
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.debug_struct("LockoutTracker")
+			.field("attempts", &self.attempts.lock().len())
+			.field("hook",     &self.hook.is_some())
+			.finish()
+	}
+}
+
+
+
+//		Traits																											
+
+//§		LockoutHook																
+/// A hook that is called when a lockout begins.
+/// 
+/// This can be used to notify an administrator or an external monitoring
+/// system. It is called as part of processing the login request, and so
+/// should not block for any significant time. It is implemented for any
+/// closure that accepts a [`LockoutEvent`].
+/// 
+pub trait LockoutHook: Send + Sync {
+	//		on_lockout															
+	/// Called when a lockout begins.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `event` - Details of the lockout.
+	/// 
+	fn on_lockout(&self, event: &LockoutEvent);
+}
+
+//󰭅		LockoutHook																
+impl<F> LockoutHook for F
+where
+	F: Fn(&LockoutEvent) + Send + Sync,
+{
+	//		on_lockout															
+	fn on_lockout(&self, event: &LockoutEvent) {
+		self(event);
+	}
+}
+
+
+
+//		Functions																										
+
+//		keys																	
+/// Gets the identities that a login attempt is counted against.
+/// 
+/// Each identity is returned along with a flag indicating whether it is an IP
+/// address.
+/// 
+/// # Parameters
+/// 
+/// * `username` - The username that the attempt is for.
+/// * `ip`       - The IP address that the attempt is from, if known.
+/// 
+fn keys(username: &str, ip: Option<IpAddr>) -> impl Iterator<Item = (LockoutKey, bool)> {
+	[
+		Some((LockoutKey::Username(username.to_owned()), false)),
+		ip.map(|address| (LockoutKey::Ip(address), true)),
+	].into_iter().flatten()
+}
+
+//		lockout_duration														
+/// Calculates the duration of a lockout.
+/// 
+/// The duration doubles for each failure beyond the threshold, up to the
+/// configured maximum.
+/// 
+/// # Parameters
+/// 
+/// * `config` - The lockout policy.
+/// * `excess` - The number of failures beyond the threshold.
+/// 
+fn lockout_duration(config: &LockoutConfig, excess: u32) -> Duration {
+	let factor = 2_u64.checked_pow(excess).unwrap_or(u64::MAX);
+	Duration::from_secs(config.duration.saturating_mul(factor).min(config.max_duration))
+}
+
+
@@ -4,11 +4,25 @@
 
 
 
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/middleware.rs"]
+mod tests;
+
+
+
 //		Packages																										
 
 use super::{
+	audit::{AuthEvent, ClientInfo, record_event},
+	csrf::{CSRF_TEMPLATE_VAR, clear_csrf_token, csrf_token},
 	errors::AuthError,
 	handlers::get_login,
+	impersonation::can_impersonate,
+	remember::restore_login,
+	responses::{Problem, ResponseFormat},
+	revocation::generation,
 	state::StateProvider,
 };
 use crate::app::{
@@ -19,23 +33,58 @@ use axum::{
 	Extension,
 	body::Body,
 	extract::{FromRequestParts, State, rejection::ExtensionRejection},
-	http::{Request, StatusCode, Uri, request::Parts},
+	http::{Extensions, HeaderMap, Request, StatusCode, Uri, header::SET_COOKIE, request::Parts},
 	middleware::Next,
-	response::{IntoResponse as _, Response},
+	response::{AppendHeaders, IntoResponse as _, Response},
+};
+use core::{
+	error::Error,
+	fmt::{Debug, Display},
 };
-use core::fmt::{Debug, Display};
 use rubedo::sugar::s;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use std::sync::Arc;
-use tower_sessions::Session;
-use tracing::info;
+use tera::Context as Template;
+use tower_sessions::{Session, cookie::time::OffsetDateTime};
+use tracing::{info, warn};
 
 
 
 //		Constants																										
 
+/// The name of the template variable that the current user is made available
+/// as, by [`Context::add_template_vars()`].
+pub const CURRENT_USER_TEMPLATE_VAR: &str = "CurrentUser";
+
+/// The name of the template variable that the [flashed](Context::flash())
+/// messages are made available as, by [`Context::add_template_vars()`].
+pub const FLASH_TEMPLATE_VAR:        &str = "Flash";
+
+/// The name of the template variable that the user who is impersonating the
+/// current user, if any, is made available as, by
+/// [`Context::add_template_vars()`].
+pub const IMPERSONATOR_TEMPLATE_VAR: &str = "Impersonator";
+
+/// The name of the template variable that indicates whether there is a current
+/// user, as set by [`Context::add_template_vars()`].
+pub const LOGGED_IN_TEMPLATE_VAR:    &str = "LoggedIn";
+
+/// The key used to store messages that have been flashed, and not yet shown.
+const SESSION_FLASH_KEY:             &str = "_flash";
+
+/// The key used to store the user's [revocation generation](super::revocation)
+/// at the time that they logged in.
+const SESSION_GENERATION_KEY:        &str = "_generation";
+
+/// The key used to store the ID of the user being impersonated.
+const SESSION_IMPERSONATED_ID_KEY:   &str = "_impersonated_id";
+
+/// The key used to store the time at which the session's user logged in, as a
+/// Unix timestamp.
+const SESSION_LOGIN_AT_KEY:          &str = "_login_at";
+
 /// The key used to store the session's user ID.
-const SESSION_USER_ID_KEY: &str = "_user_id";
+const SESSION_USER_ID_KEY:           &str = "_user_id";
 
 
 
@@ -45,7 +94,13 @@ const SESSION_USER_ID_KEY: &str = "_user_id";
 /// The authentication context.
 /// 
 /// This struct contains the current user and session data, to persist the
-/// context of an authentication session.
+/// context of an authentication session. When authenticating using bearer
+/// tokens there is no session, and the context only holds the current user.
+/// 
+/// When a user is [impersonating](super::impersonation) another user, the
+/// current user is the one being impersonated, so that the application behaves
+/// as it would for them, and the user doing the impersonating is available
+/// from [`impersonator()`](Self::impersonator()).
 /// 
 #[derive(Clone, Debug)]
 pub struct Context<U: User> {
@@ -54,8 +109,17 @@ pub struct Context<U: User> {
 	pub current_user: Option<U>,
 	
 	//		Private properties													
-	/// The active session.
-	session:          Session,
+	/// The active session, if any.
+	session:          Option<Session>,
+	
+	/// The `WWW-Authenticate` challenge to use, if not the default.
+	challenge:        Option<String>,
+	
+	/// The client that made the request.
+	client:           ClientInfo,
+	
+	/// The user who is impersonating the current user, if any.
+	impersonator:     Option<U>,
 }
 
 //󰭅		Context																	
@@ -66,38 +130,356 @@ impl<U: User> Context<U> {
 	/// # Parameters
 	/// 
 	/// * `session` - The active session.
-	/// * `key`     - The HMAC key.
 	/// 
 	#[must_use]
 	pub const fn new(session: Session) -> Self {
 		Self {
 			current_user: None,
-			session,
+			session:      Some(session),
+			challenge:    None,
+			client:       ClientInfo { ip: None, user_agent: None },
+			impersonator: None,
+		}
+	}
+	
+	//		without_session														
+	/// Creates a new authentication context that has no session.
+	/// 
+	/// This is used when the user has been identified by other means, such as
+	/// a bearer token, and so there is no session to persist. Logging in and
+	/// out are not possible with such a context.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `current_user` - The current user, if any.
+	/// 
+	#[must_use]
+	pub const fn without_session(current_user: Option<U>) -> Self {
+		Self {
+			current_user,
+			session:      None,
+			challenge:    None,
+			client:       ClientInfo { ip: None, user_agent: None },
+			impersonator: None,
+		}
+	}
+	
+	//		with_challenge														
+	/// Sets the `WWW-Authenticate` challenge for this context.
+	/// 
+	/// This is used when the user has been identified by a scheme other than
+	/// sessions or bearer tokens, such as HTTP Basic authentication, so that
+	/// unauthenticated requests are told how to authenticate.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `challenge` - The value for the `WWW-Authenticate` header.
+	/// 
+	#[must_use]
+	pub fn with_challenge<S: Into<String>>(mut self, challenge: S) -> Self {
+		self.challenge = Some(challenge.into());
+		self
+	}
+	
+	//		with_client															
+	/// Sets the details of the client that made the request.
+	/// 
+	/// These are included in [audit events](super::audit) recorded using this
+	/// context.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `client` - The client that made the request.
+	/// 
+	#[must_use]
+	pub fn with_client(mut self, client: ClientInfo) -> Self {
+		self.client = client;
+		self
+	}
+	
+	//		add_template_vars													
+	/// Adds the authentication details to a template context.
+	/// 
+	/// The following are made available:
+	/// 
+	///   - [`CURRENT_USER_TEMPLATE_VAR`] - The current user, if any, in the
+	///                                     form of a [`TemplateUser`].
+	///   - [`IMPERSONATOR_TEMPLATE_VAR`] - The user impersonating the current
+	///                                     user, if any, in the same form.
+	///   - [`LOGGED_IN_TEMPLATE_VAR`]    - Whether there is a current user.
+	///   - [`FLASH_TEMPLATE_VAR`]        - The messages [flashed](Self::flash())
+	///                                     since the last page was rendered.
+	///                                     These are removed from the session,
+	///                                     so that they are only shown once.
+	///   - [`CSRF_TEMPLATE_VAR`]         - The session's [CSRF token](super::csrf),
+	///                                     if there is a session.
+	/// 
+	/// This is done automatically by [`render()`](Self::render()).
+	/// 
+	/// # Parameters
+	/// 
+	/// * `template` - The template context to add to.
+	/// 
+	/// # Errors
+	/// 
+	/// If the session cannot be read or written, an error will be returned.
+	/// 
+	pub async fn add_template_vars(&self, template: &mut Template) -> Result<(), AuthError> {
+		let mut messages = Vec::<String>::new();
+		if let Some(ref session) = self.session {
+			messages = session.remove(SESSION_FLASH_KEY).await?.unwrap_or_default();
+			template.insert(CSRF_TEMPLATE_VAR, &csrf_token(session).await?);
+		}
+		template.insert(CURRENT_USER_TEMPLATE_VAR, &self.current_user.as_ref().map(TemplateUser::new));
+		template.insert(IMPERSONATOR_TEMPLATE_VAR, &self.impersonator.as_ref().map(TemplateUser::new));
+		template.insert(LOGGED_IN_TEMPLATE_VAR,    &self.current_user.is_some());
+		template.insert(FLASH_TEMPLATE_VAR,        &messages);
+		Ok(())
+	}
+	
+	//		challenge															
+	/// Gets the `WWW-Authenticate` challenge for this context.
+	/// 
+	/// This is `Bearer` for token-based authentication, and `Cookie` for
+	/// session-based authentication, unless another challenge has been set
+	/// using [`with_challenge()`](Self::with_challenge()).
+	/// 
+	#[must_use]
+	pub fn challenge(&self) -> &str {
+		self.challenge.as_deref().unwrap_or_else(|| if self.session.is_some() { "Cookie" } else { "Bearer" })
+	}
+	
+	//		client																
+	/// Gets the details of the client that made the request.
+	#[must_use]
+	pub const fn client(&self) -> &ClientInfo {
+		&self.client
+	}
+	
+	//		csrf_token															
+	/// Gets the CSRF token for the session.
+	/// 
+	/// This is made available to templates as `CsrfToken` by
+	/// [`add_template_vars()`](Self::add_template_vars()), so that it can be
+	/// included in forms. See the [`csrf`](super::csrf) module for details. If
+	/// there is no session, there is no token.
+	/// 
+	/// # Errors
+	/// 
+	/// If the session cannot be read or written, an error will be returned.
+	/// 
+	pub async fn csrf_token(&self) -> Result<Option<String>, AuthError> {
+		match self.session {
+			Some(ref session) => Ok(Some(csrf_token(session).await?)),
+			None              => Ok(None),
 		}
 	}
 	
+	//		flash																
+	/// Adds a message to be shown on the next page rendered.
+	/// 
+	/// Messages are kept in the session until they are made available to a
+	/// template as [`FLASH_TEMPLATE_VAR`], by
+	/// [`add_template_vars()`](Self::add_template_vars()). This allows a
+	/// message to survive a redirect, for instance to confirm that a form has
+	/// been submitted. If there is no session, the message is discarded.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `message` - The message to show.
+	/// 
+	/// # Errors
+	/// 
+	/// If the session cannot be read or written, an error will be returned.
+	/// 
+	pub async fn flash<M: Into<String> + Send>(&self, message: M) -> Result<(), AuthError> {
+		if let Some(ref session) = self.session {
+			let mut messages = session.get::<Vec<String>>(SESSION_FLASH_KEY).await?.unwrap_or_default();
+			messages.push(message.into());
+			session.insert(SESSION_FLASH_KEY, &messages).await?;
+		}
+		Ok(())
+	}
+	
 	//		get_user															
 	/// Gets the current user.
 	/// 
 	/// Retrieves the current user id from the session, obtains the user's data
-	/// from the data store, and verifies the session's authentication ID.
+	/// from the data store, and verifies the session's authentication ID. If
+	/// the login has exceeded the configured
+	/// [absolute expiry](super::config::Expiry#structfield.absolute), or the
+	/// user's sessions have been [revoked](super::revocation) since they logged
+	/// in, the user is logged out.
 	/// 
 	/// # Parameters
 	/// 
 	/// * `state` - The application state.
 	/// 
-	pub async fn get_user<SP, UP>(&self, state: &SP) -> Option<U>
+	/// # Errors
+	/// 
+	/// If the session cannot be read, or the user lookup or revocation check
+	/// fails, an error will be returned.
+	/// 
+	pub async fn get_user<SP, UP>(&self, state: &SP) -> Result<Option<U>, AuthError>
 	where
 		SP: StateProvider,
 		UP: UserProvider<User = U>,
 	{
-		if let Ok(Some(user_id)) = self.session.get(SESSION_USER_ID_KEY).await {
-			if let Some(user)    = UP::find_by_id(state, &user_id) {
-				return Some(user);
+		let Some(ref session) = self.session else {
+			return Ok(self.current_user.clone());
+		};
+		let Some(user_id) = session.get(SESSION_USER_ID_KEY).await? else {
+			return Ok(None);
+		};
+		let user = UP::find_by_id(state, &user_id).await
+			.map_err(|err| AuthError::UserProviderError(Box::new(err)))?
+		;
+		if self.has_expired(state).await {
+			info!("Login expired for user: {user_id}");
+			if let Some(ref expired) = user {
+				record_event(state, AuthEvent::SessionExpired { user: expired.to_loggable_string() }, &self.client).await;
 			}
-			self.logout().await;
+			self.logout().await?;
+			return Ok(None);
+		}
+		if self.is_revoked(state, &user_id).await? {
+			info!("Revoked session for user: {user_id}");
+			self.logout().await?;
+			return Ok(None);
+		}
+		if user.is_none() {
+			self.logout().await?;
+		}
+		Ok(user)
+	}
+	
+	//		get_impersonated_user												
+	/// Gets the user being impersonated, if any.
+	/// 
+	/// If the session records that the given user, who has logged in, is
+	/// impersonating someone, that user is looked up and returned. If the
+	/// impersonation is no longer allowed, for instance because the user has
+	/// lost the required permission, or the impersonated user no longer
+	/// exists, it is ended.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `state` - The application state.
+	/// * `user`  - The user who has logged in.
+	/// 
+	/// # Errors
+	/// 
+	/// If the session cannot be read or written, or the user lookup fails, an
+	/// error will be returned.
+	/// 
+	async fn get_impersonated_user<SP, UP>(&self, state: &SP, user: &U) -> Result<Option<U>, AuthError>
+	where
+		SP: StateProvider,
+		UP: UserProvider<User = U>,
+	{
+		let Some(ref session) = self.session else {
+			return Ok(None);
+		};
+		let Some(target_id) = session.get::<U::Id>(SESSION_IMPERSONATED_ID_KEY).await? else {
+			return Ok(None);
+		};
+		let target = UP::find_by_id(state, &target_id).await
+			.map_err(|err| AuthError::UserProviderError(Box::new(err)))?
+			.filter(|found| can_impersonate(&state.config().impersonation, user, found))
+		;
+		if target.is_none() {
+			warn!("Ending impersonation of user {target_id} that is no longer allowed for user: {}", user.to_loggable_string());
+			drop(session.remove_value(SESSION_IMPERSONATED_ID_KEY).await?);
+		}
+		Ok(target)
+	}
+	
+	//		has_expired															
+	/// Checks whether the current login has exceeded the absolute expiry.
+	/// 
+	/// Logins without a recorded login time, or when no absolute expiry is
+	/// configured, are not considered to have expired.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `state` - The application state.
+	/// 
+	async fn has_expired<SP: StateProvider>(&self, state: &SP) -> bool {
+		let (Some(absolute), Some(session)) = (state.config().expiry.absolute, self.session.as_ref()) else {
+			return false;
+		};
+		match session.get::<i64>(SESSION_LOGIN_AT_KEY).await {
+			Ok(Some(login_at)) => {
+				let expires_at = login_at.saturating_add(i64::try_from(absolute).unwrap_or(i64::MAX));
+				OffsetDateTime::now_utc().unix_timestamp() >= expires_at
+			},
+			_                  => false,
+		}
+	}
+	
+	//		impersonate															
+	/// Starts impersonating another user.
+	/// 
+	/// The current user becomes the [impersonator](Self::impersonator()), and
+	/// the target becomes the current user, for this request and for later
+	/// requests in the same session, until
+	/// [`stop_impersonating()`](Self::stop_impersonating()) is called or the
+	/// user logs out. It assumes that the current user has already been
+	/// checked to be allowed to impersonate the target, using
+	/// [`can_impersonate()`].
+	/// 
+	/// Returns `false`, and does nothing, if there is no current user, or if
+	/// they are already impersonating someone.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `target` - The user to impersonate.
+	/// 
+	/// # Errors
+	/// 
+	/// If there is no session, or the session cannot be written, an error
+	/// will be returned.
+	/// 
+	pub async fn impersonate(&mut self, target: U) -> Result<bool, AuthError> {
+		let session = self.session.as_ref().ok_or(AuthError::NoSession)?;
+		if self.current_user.is_none() || self.impersonator.is_some() {
+			return Ok(false);
 		}
-		None
+		session.insert(SESSION_IMPERSONATED_ID_KEY, target.id()).await?;
+		self.impersonator = self.current_user.replace(target);
+		Ok(true)
+	}
+	
+	//		impersonator														
+	/// Gets the user who is impersonating the current user, if any.
+	#[must_use]
+	pub const fn impersonator(&self) -> Option<&U> {
+		self.impersonator.as_ref()
+	}
+	
+	//		is_revoked															
+	/// Checks whether the user's sessions have been revoked since they logged
+	/// in to this session.
+	/// 
+	/// Sessions logged into before revocation was supported, and so without a
+	/// recorded generation, are treated as belonging to the first generation.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `state`   - The application state.
+	/// * `user_id` - The ID of the session's user.
+	/// 
+	/// # Errors
+	/// 
+	/// If the session or the revocation store cannot be read, an error will be
+	/// returned.
+	/// 
+	async fn is_revoked<SP: StateProvider>(&self, state: &SP, user_id: &U::Id) -> Result<bool, AuthError> {
+		let Some(ref session) = self.session else {
+			return Ok(false);
+		};
+		let logged_in = session.get::<u64>(SESSION_GENERATION_KEY).await?.unwrap_or_default();
+		Ok(logged_in < generation(state, user_id).await?)
 	}
 	
 	//		login																
@@ -106,17 +488,30 @@ impl<U: User> Context<U> {
 	/// Logs the user in by setting the session's authentication ID and user ID.
 	/// It assumes that the user's credentials have already been verified.
 	/// 
+	/// To protect against session fixation, the session is given a new ID, and
+	/// a new CSRF token. The user's current
+	/// [revocation generation](super::revocation) is recorded in the session,
+	/// so that it can be revoked along with the user's other sessions.
+	/// 
 	/// # Parameters
 	/// 
-	/// * `user` - The user to log in.
+	/// * `state` - The application state.
+	/// * `user`  - The user to log in.
 	/// 
 	/// # Errors
 	/// 
-	/// If there is an error setting the session's user ID, an error will be
+	/// If there is no session, there is an error updating or saving the
+	/// session, or the revocation store cannot be read, an error will be
 	/// returned.
 	/// 
-	pub async fn login(&mut self, user: &U) -> Result<(), AuthError> {
-		self.session.insert(SESSION_USER_ID_KEY, user.id()).await?;
+	pub async fn login<SP: StateProvider>(&mut self, state: &SP, user: &U) -> Result<(), AuthError> {
+		let session = self.session.as_ref().ok_or(AuthError::NoSession)?;
+		session.cycle_id().await?;
+		session.insert(SESSION_USER_ID_KEY,    user.id()).await?;
+		session.insert(SESSION_LOGIN_AT_KEY,   OffsetDateTime::now_utc().unix_timestamp()).await?;
+		session.insert(SESSION_GENERATION_KEY, generation(state, &user.id()).await?).await?;
+		clear_csrf_token(session).await?;
+		session.save().await?;
 		self.current_user = Some(user.clone());
 		Ok(())
 	}
@@ -124,10 +519,92 @@ impl<U: User> Context<U> {
 	//		logout																
 	/// Logs out the current user.
 	/// 
-	/// Logs the current user out by destroying the session.
+	/// Logs the current user out by destroying the session, which removes all
+	/// of its data and deletes it from the session store. If there is no
+	/// session, this does nothing.
+	/// 
+	/// # Errors
+	/// 
+	/// If the session cannot be deleted, an error will be returned.
 	/// 
-	pub async fn logout(&self) {
-		self.session.clear().await;
+	pub async fn logout(&self) -> Result<(), AuthError> {
+		if let Some(ref session) = self.session {
+			session.flush().await?;
+		}
+		Ok(())
+	}
+	
+	//		render																
+	/// Renders a template, with the authentication details added.
+	/// 
+	/// The details listed under [`add_template_vars()`](Self::add_template_vars())
+	/// are added to the given context, which is then rendered using
+	/// [`AppStateProvider::render()`]. This allows layouts to show who is
+	/// logged in, and forms to include the CSRF token, without each handler
+	/// needing to add them.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `state`    - The application state.
+	/// * `template` - The name of the template to render.
+	/// * `context`  - The context to render the template with.
+	/// 
+	/// # Errors
+	/// 
+	/// If the session cannot be read or written, or the template cannot be
+	/// rendered, an error will be returned.
+	/// 
+	pub async fn render<SP, T>(&self, state: &SP, template: T, mut context: Template) -> Result<String, AppError>
+	where
+		SP: AppStateProvider,
+		T:  AsRef<str> + Send,
+	{
+		self.add_template_vars(&mut context).await?;
+		state.render(template, &context).await
+	}
+	
+	//		response_format														
+	/// Determines the response format for an authentication failure.
+	/// 
+	/// Requests without a session always receive JSON, as there is no login
+	/// page that would be of use to them. Otherwise, the format is determined
+	/// by [`ResponseFormat::select()`].
+	/// 
+	/// # Parameters
+	/// 
+	/// * `extensions` - The request extensions.
+	/// * `headers`    - The request headers.
+	/// 
+	#[must_use]
+	pub fn response_format(&self, extensions: &Extensions, headers: &HeaderMap) -> ResponseFormat {
+		if self.session.is_some() { ResponseFormat::select(extensions, headers) } else { ResponseFormat::Json }
+	}
+	
+	//		session																
+	/// Gets the active session, if any.
+	#[must_use]
+	pub const fn session(&self) -> Option<&Session> {
+		self.session.as_ref()
+	}
+	
+	//		stop_impersonating													
+	/// Stops impersonating another user.
+	/// 
+	/// The impersonator becomes the current user again. Returns the user who
+	/// was being impersonated, or [`None`] if there was no impersonation.
+	/// 
+	/// # Errors
+	/// 
+	/// If the session cannot be written, an error will be returned.
+	/// 
+	pub async fn stop_impersonating(&mut self) -> Result<Option<U>, AuthError> {
+		let Some(impersonator) = self.impersonator.take() else {
+			return Ok(None);
+		};
+		if let Some(ref session) = self.session {
+			drop(session.remove_value(SESSION_IMPERSONATED_ID_KEY).await?);
+		}
+		Ok(self.current_user.replace(impersonator))
 	}
 }
 
@@ -154,6 +631,50 @@ where
 
 
 
+//		TemplateUser															
+/// The details of a user that are made available to templates.
+/// 
+/// See [`Context::add_template_vars()`].
+/// 
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct TemplateUser {
+	//		Public properties													
+	/// The user's ID.
+	pub id:          String,
+	
+	/// The user's name, as given by [`User::to_loggable_string()`].
+	pub name:        String,
+	
+	/// The roles held by the user.
+	pub roles:       Vec<String>,
+	
+	/// The permissions granted to the user.
+	pub permissions: Vec<String>,
+}
+
+//󰭅		TemplateUser															
+impl TemplateUser {
+	//		new																	
+	/// Creates the template details for a user.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `user` - The user.
+	/// 
+	#[must_use]
+	pub fn new<U: User>(user: &U) -> Self {
+		Self {
+			id:          user.id().to_string(),
+			name:        user.to_loggable_string(),
+			roles:       user.roles().to_vec(),
+			permissions: user.permissions().to_vec(),
+		}
+	}
+}
+
+
+
 //		Traits																											
 
 //§		Credentials																
@@ -188,6 +709,35 @@ pub trait User: Clone + Debug + Send + Sync + 'static {
 	/// might be an ID, username, email, or similar.
 	type Id: Clone + Debug + DeserializeOwned + Display + Serialize + Send + Sync + 'static;
 	
+	//		has_permission														
+	/// Checks whether the user has a permission.
+	/// 
+	/// This is used to authorise access to routes that require a permission,
+	/// as added by [`routes_requiring()`](super::routing::RouterExt::routes_requiring()),
+	/// and by the [`Permitted`](super::permissions::Permitted) extractor. By
+	/// default it checks the user's [`permissions()`](User::permissions()),
+	/// but it can be overridden to derive permissions from roles, or to look
+	/// them up in some other way.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `permission` - The permission to check.
+	/// 
+	fn has_permission(&self, permission: &str) -> bool {
+		self.permissions().iter().any(|granted| granted == permission)
+	}
+	
+	//		has_role															
+	/// Checks whether the user has a role.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `role` - The role to check.
+	/// 
+	fn has_role(&self, role: &str) -> bool {
+		self.roles().iter().any(|held| held == role)
+	}
+	
 	//		id																	
 	/// The user's unique identifier.
 	/// 
@@ -196,6 +746,40 @@ pub trait User: Clone + Debug + Send + Sync + 'static {
 	/// 
 	fn id(&self) -> &Self::Id;
 	
+	//		permissions															
+	/// The permissions granted to the user.
+	/// 
+	/// By default the user has no permissions.
+	/// 
+	fn permissions(&self) -> &[String] {
+		&[]
+	}
+	
+	//		requires_two_factor													
+	/// Whether the user needs to supply a second factor to log in.
+	/// 
+	/// If this returns `true`, then once the user's password has been accepted
+	/// they are asked for a code from their authenticator app, or a recovery
+	/// code, before being logged in. See the [`totp`](super::totp) module for
+	/// details. Users who need a second factor but have not enrolled will not
+	/// be able to log in using a password.
+	/// 
+	/// By default no second factor is needed.
+	/// 
+	#[cfg(feature = "totp")]
+	fn requires_two_factor(&self) -> bool {
+		false
+	}
+	
+	//		roles																
+	/// The roles held by the user.
+	/// 
+	/// By default the user has no roles.
+	/// 
+	fn roles(&self) -> &[String] {
+		&[]
+	}
+	
 	//		to_loggable_string													
 	/// Format appropriately for logging.
 	/// 
@@ -213,11 +797,21 @@ pub trait User: Clone + Debug + Send + Sync + 'static {
 
 //§		UserProvider															
 /// A trait for providing basic user data.
+/// 
+/// The lookup methods are asynchronous, so that users can be retrieved from a
+/// database, directory service, or other external source without blocking the
+/// runtime. Lookup failures should be reported as errors, which will be
+/// propagated as [`AuthError::UserProviderError`], rather than being treated
+/// as the user not existing.
+/// 
 pub trait UserProvider: Debug + 'static {
 	/// The credentials data type. This is the type that implements the
 	/// [`Credentials`] trait.
 	type Credentials: Credentials;
 	
+	/// The error type returned when a lookup fails.
+	type Error:       Error + Send + Sync + 'static;
+	
 	/// The user data type. This is the type that implements the [`User`] trait.
 	type User:        User;
 	
@@ -232,10 +826,14 @@ pub trait UserProvider: Debug + 'static {
 	/// * `state`       - The application state.
 	/// * `credentials` - The credentials to check.
 	/// 
+	/// # Errors
+	/// 
+	/// If the lookup fails, an error will be returned.
+	/// 
 	fn find_by_credentials<SP: StateProvider>(
 		state:       &SP,
 		credentials: &Self::Credentials,
-	) -> Option<Self::User>;
+	) -> impl Future<Output = Result<Option<Self::User>, Self::Error>> + Send;
 	
 	//		find_by_id															
 	/// Finds a user by unique identifier.
@@ -250,10 +848,14 @@ pub trait UserProvider: Debug + 'static {
 	/// * `state` - The application state.
 	/// * `id`    - The identifying field to search for.
 	/// 
+	/// # Errors
+	/// 
+	/// If the lookup fails, an error will be returned.
+	/// 
 	fn find_by_id<SP: StateProvider>(
 		state: &SP,
 		id:    &<Self::User as User>::Id,
-	) -> Option<Self::User>;
+	) -> impl Future<Output = Result<Option<Self::User>, Self::Error>> + Send;
 }
 
 
@@ -267,6 +869,10 @@ pub trait UserProvider: Debug + 'static {
 /// context. It retrieves the current user from the session, and stores it in
 /// the request's extensions, so that it can be used by the route handlers.
 /// 
+/// If the session has no user, but the request has a
+/// [remember-me token](super::remember), the user is logged back in using the
+/// token, and the updated token cookie is set on the response.
+/// 
 /// # Parameters
 /// 
 /// * `state`   - The application state.
@@ -274,23 +880,67 @@ pub trait UserProvider: Debug + 'static {
 /// * `request` - The request.
 /// * `next`    - The next middleware.
 /// 
+/// # Errors
+/// 
+/// If the current user cannot be retrieved, an error will be returned.
+/// 
 pub async fn auth_layer<SP, U, UP>(
 	State(state):       State<Arc<SP>>,
 	Extension(session): Extension<Session>,
 	mut request:        Request<Body>,
 	next:               Next,
-) -> Response
+) -> Result<Response, AuthError>
 where
 	SP: StateProvider,
 	U:  User,
 	UP: UserProvider<User = U>,
 {
-	let mut auth_cx = Context::<U>::new(session);
-	let user        = auth_cx.get_user::<SP, UP>(&state).await;
+	let mut auth_cx = Context::<U>::new(session)
+		.with_client(ClientInfo::from_request(request.headers(), request.extensions()))
+	;
+	let mut cookie  = None;
+	let mut user    = auth_cx.get_user::<SP, UP>(&state).await?;
+	if user.is_none() {
+		cookie = restore_login::<SP, U, UP>(&state, &mut auth_cx, request.headers()).await?;
+		user   = auth_cx.current_user.take();
+	}
+	if let Some(ref real) = user {
+		if let Some(target) = auth_cx.get_impersonated_user::<SP, UP>(&state, real).await? {
+			info!("User {} is impersonating user: {}", real.to_loggable_string(), target.to_loggable_string());
+			record_event(&*state, AuthEvent::ImpersonatedRequest {
+				user:   real.to_loggable_string(),
+				target: target.to_loggable_string(),
+				method: request.method().to_string(),
+				path:   request.uri().path().to_owned(),
+			}, auth_cx.client()).await;
+			auth_cx.impersonator = user.replace(target);
+		}
+	}
 	info!("Current user: {}", user.as_ref().map_or(s!("none"), |u| u.id().to_string()));
 	auth_cx.current_user = user;
 	drop(request.extensions_mut().insert(auth_cx));
-	next.run(request).await
+	Ok((
+		AppendHeaders(cookie.map(|set| (SET_COOKIE, set.to_string()))),
+		next.run(request).await,
+	).into_response())
+}
+
+//		forbidden																
+/// Creates a response for a request that lacks permission.
+/// 
+/// API clients receive a JSON [`Problem`] body, whereas browsers receive an
+/// empty 403 response.
+/// 
+/// # Parameters
+/// 
+/// * `format` - The response format.
+/// 
+#[must_use]
+pub fn forbidden(format: ResponseFormat) -> Response {
+	match format {
+		ResponseFormat::Json => Problem::forbidden(),
+		ResponseFormat::Html => StatusCode::FORBIDDEN.into_response(),
+	}
 }
 
 //		protect																	
@@ -300,6 +950,12 @@ where
 /// retrieves the current user from the request's extensions, and if it is
 /// present, it calls the next middleware. Otherwise, it returns a 401 response.
 /// 
+/// The form of the response depends upon the [`ResponseFormat`] for the
+/// request. Browsers are shown the login page, whereas API clients receive a
+/// JSON [`Problem`] body with a `WWW-Authenticate` header. Requests without a
+/// session, i.e. those using token-based authentication, always receive the
+/// latter, as there is no login page that would be of use to them.
+/// 
 /// # Parameters
 /// 
 /// * `state`   - The application state.
@@ -319,15 +975,11 @@ where
 	SP: StateProvider,
 	U:  User,
 {
-	match auth_cx.current_user {
-		Some(_) => next.run(request).await,
-		_       => {
-			(
-				StatusCode::UNAUTHORIZED,
-				get_login(State(state), uri).await,
-			).into_response()
-		},
+	if auth_cx.current_user.is_some() {
+		return next.run(request).await;
 	}
+	let format = auth_cx.response_format(request.extensions(), request.headers());
+	unauthenticated(state, &auth_cx, uri, format).await
 }
 
 //		protected_error_layer													
@@ -336,7 +988,8 @@ where
 /// This function is called when an error occurs.
 /// 
 /// If the error is a 404, it returns either a 404 status code and a 404 page,
-/// or the login page, depending on the user's authentication status.
+/// or a 401 response, depending on the user's authentication status. The 401
+/// response follows the same rules as [`protect()`].
 /// 
 /// # Parameters
 /// 
@@ -361,19 +1014,23 @@ where
 	SP: AppStateProvider,
 	U:  User,
 {
+	let format            = auth_cx.response_format(request.extensions(), request.headers());
 	let response          = next.run(request).await;
 	let (mut parts, body) = response.into_parts();
 	Ok(match parts.status {
-		//		404: Not Found													
+		//		404: Not Found													
 		StatusCode::NOT_FOUND => {
 			if parts.headers.contains_key("protected") && auth_cx.current_user.is_none() {
+				if format == ResponseFormat::Json {
+					return Ok(Problem::unauthorized(auth_cx.challenge()));
+				}
 				drop(parts.headers.remove("content-length"));
 				drop(parts.headers.remove("content-type"));
 				drop(parts.headers.remove("protected"));
 				parts.status = StatusCode::UNAUTHORIZED;
 				return Ok((
 					parts,
-					get_login(State(state), uri).await,
+					get_login(State(state), auth_cx.session.clone().map(Extension), uri).await,
 				).into_response());
 			}
 			(
@@ -381,7 +1038,7 @@ where
 				body,
 			).into_response()
 		},
-		//		Everything else													
+		//		Everything else													
 		_ => {
 			(
 				parts,
@@ -391,4 +1048,83 @@ where
 	})
 }
 
+//		require_permission														
+/// Protects a route from access by users who lack a permission.
+/// 
+/// This middleware behaves in the same way as [`protect()`] for requests that
+/// have no current user. If there is a current user, it checks that they have
+/// the required permission using [`User::has_permission()`], and if not, it
+/// returns a 403 response. The decision is logged.
+/// 
+/// The form of the 403 response depends upon the [`ResponseFormat`] for the
+/// request. API clients receive a JSON [`Problem`] body, whereas browsers
+/// receive an empty response, which can be handled by the error catchers.
+/// 
+/// # Parameters
+/// 
+/// * `state`      - The application state.
+/// * `permission` - The permission required.
+/// * `auth_cx`    - The authentication context.
+/// * `uri`        - The request URI.
+/// * `request`    - The request.
+/// * `next`       - The next middleware.
+/// 
+pub async fn require_permission<SP, U>(
+	State((state, permission)): State<(Arc<SP>, &'static str)>,
+	Extension(auth_cx):         Extension<Context<U>>,
+	uri:                        Uri,
+	request:                    Request<Body>,
+	next:                       Next,
+) -> Response
+where
+	SP: StateProvider,
+	U:  User,
+{
+	let format = auth_cx.response_format(request.extensions(), request.headers());
+	let Some(ref user) = auth_cx.current_user else {
+		return unauthenticated(state, &auth_cx, uri, format).await;
+	};
+	if user.has_permission(permission) {
+		info!("Permission {permission} granted to user: {}", user.to_loggable_string());
+		return next.run(request).await;
+	}
+	warn!("Permission {permission} denied to user: {}", user.to_loggable_string());
+	forbidden(format)
+}
+
+//		unauthenticated															
+/// Creates a response for a request that has no current user.
+/// 
+/// Browsers are shown the login page, whereas API clients receive a JSON
+/// [`Problem`] body with a `WWW-Authenticate` header, as described for
+/// [`protect()`].
+/// 
+/// # Parameters
+/// 
+/// * `state`   - The application state.
+/// * `auth_cx` - The authentication context.
+/// * `uri`     - The request URI.
+/// * `format`  - The response format.
+/// 
+async fn unauthenticated<SP, U>(
+	state:   Arc<SP>,
+	auth_cx: &Context<U>,
+	uri:     Uri,
+	format:  ResponseFormat,
+) -> Response
+where
+	SP: StateProvider,
+	U:  User,
+{
+	match format {
+		ResponseFormat::Json => Problem::unauthorized(auth_cx.challenge()),
+		ResponseFormat::Html => {
+			(
+				StatusCode::UNAUTHORIZED,
+				get_login(State(state), auth_cx.session.clone().map(Extension), uri).await,
+			).into_response()
+		},
+	}
+}
+
 
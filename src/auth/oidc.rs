@@ -0,0 +1,746 @@
+#![allow(clippy::unused_async, reason = "Handler functions need to be async")]
+
+//! Login using an OpenID Connect provider.
+//! 
+//! This provides single sign-on using the authorisation code flow with PKCE,
+//! alongside the username and password login provided by
+//! [`post_login()`](super::handlers::post_login()). The provider is configured
+//! using [`Oidc`], and its endpoints and keys are obtained from its discovery
+//! document. These are held in an [`OidcCache`] for a configurable time, and
+//! the keys are fetched again whenever a token is signed with a key that is
+//! not known, so that key rotation is picked up automatically.
+//! 
+//! A login is started by sending the user to [`get_oidc_login()`], which
+//! records a random state, nonce, and PKCE verifier in the session, and
+//! redirects to the provider. The provider then redirects back to
+//! [`get_oidc_callback()`], which checks the state, exchanges the code for an
+//! ID token, validates the token, and maps its claims to a [`User`] using an
+//! [`OidcUserProvider`]. Both handlers need to be added as public routes, for
+//! instance at `/auth/login` and `/auth/callback`.
+//! 
+//! The session cookie must use a `SameSite` setting of `Lax` or `None`, as
+//! otherwise it will not be sent when the provider redirects back to the
+//! application, and the login will fail.
+
+
+
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/oidc.rs"]
+mod tests;
+
+
+
+//		Packages																										
+
+use super::{
+	audit::{AuthEvent, LoginMethod, record_event},
+	config::{JwtAlgorithm, Oidc},
+	errors::{AuthError, OidcError},
+	middleware::{Context, User, UserProvider},
+	state::StateProvider,
+	utility::{extract_uri_query_parts, generate_token, redirect_target, safe_redirect_uri, tokens_match},
+};
+#[cfg(feature = "totp")]
+use super::totp::start_totp_login;
+use axum::{
+	extract::{Query, State},
+	response::Redirect,
+};
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD as BASE64};
+use core::{future::Future, time::Duration};
+use jsonwebtoken::{
+	Algorithm,
+	DecodingKey,
+	Validation,
+	decode,
+	decode_header,
+	jwk::{Jwk, JwkSet},
+};
+use parking_lot::RwLock;
+use reqwest::{Client, RequestBuilder};
+use rubedo::sugar::s;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Map, Value};
+use sha2::{Digest as _, Sha256};
+use std::{
+	sync::Arc,
+	time::Instant,
+};
+use tracing::{info, warn};
+use url::Url;
+
+
+
+//		Constants																										
+
+/// The number of random bytes in the nonce.
+const NONCE_LENGTH:     usize = 32;
+
+/// The key used to store the details of a login in progress.
+const SESSION_OIDC_KEY: &str  = "_oidc_login";
+
+/// The number of random bytes in the state.
+const STATE_LENGTH:     usize = 32;
+
+/// The number of random bytes in the PKCE code verifier. This gives a
+/// verifier of 43 characters, which is the minimum allowed.
+const VERIFIER_LENGTH:  usize = 32;
+
+
+
+//		Structs																											
+
+//		Cached																	
+/// A value fetched from the provider, along with when it was fetched.
+#[derive(Debug)]
+struct Cached<T> {
+	//		Private properties													
+	/// The value fetched.
+	value:      T,
+	
+	/// When the value was fetched.
+	fetched_at: Instant,
+}
+
+//󰭅		Cached																	
+impl<T: Clone> Cached<T> {
+	//		fresh																
+	/// Gets a copy of the value, if it was fetched within the given lifetime.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `slot`     - The slot that the value is cached in.
+	/// * `lifetime` - How long the value can be used for after it is fetched.
+	/// 
+	fn fresh(slot: &RwLock<Option<Self>>, lifetime: Duration) -> Option<T> {
+		slot.read().as_ref()
+			.filter(|cached| cached.fetched_at.elapsed() < lifetime)
+			.map(|cached| cached.value.clone())
+	}
+	
+	//		store																
+	/// Stores a newly-fetched value.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `slot`  - The slot to cache the value in.
+	/// * `value` - The value fetched.
+	/// 
+	fn store(slot: &RwLock<Option<Self>>, value: T) {
+		*slot.write() = Some(Self { value, fetched_at: Instant::now() });
+	}
+}
+
+//		Discovery																
+/// The parts of a provider's discovery document that are needed to log in.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct Discovery {
+	//		Private properties													
+	/// The issuer identifier, which must match the configured issuer.
+	issuer:                 String,
+	
+	/// The URL that users are sent to in order to log in.
+	authorization_endpoint: String,
+	
+	/// The URL that the authorisation code is exchanged at.
+	token_endpoint:         String,
+	
+	/// The URL of the provider's published signing keys.
+	jwks_uri:               String,
+}
+
+//		IdClaims																
+/// The claims of a validated ID token.
+/// 
+/// The signature, issuer, audience, expiry, and nonce have all been checked by
+/// the time the claims are passed to an [`OidcUserProvider`]. Any claims that
+/// are not standard are available in [`additional`](IdClaims#structfield.additional).
+/// 
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct IdClaims {
+	//		Public properties													
+	/// The issuer identifier of the provider.
+	pub iss:                String,
+	
+	/// The subject, which uniquely identifies the user at the provider.
+	pub sub:                String,
+	
+	/// The time at which the token expires, as a Unix timestamp.
+	pub exp:                u64,
+	
+	/// The nonce sent when the login was started.
+	pub nonce:              Option<String>,
+	
+	/// The user's email address.
+	pub email:              Option<String>,
+	
+	/// Whether the provider has verified the user's email address.
+	pub email_verified:     Option<bool>,
+	
+	/// The user's full name.
+	pub name:               Option<String>,
+	
+	/// The name that the user prefers to be known by.
+	pub preferred_username: Option<String>,
+	
+	/// Any other claims in the token.
+	#[serde(flatten)]
+	pub additional:         Map<String, Value>,
+}
+
+//		OidcCallback															
+/// The query parameters sent by the provider to the callback handler.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct OidcCallback {
+	//		Public properties													
+	/// The authorisation code, if the user was authenticated.
+	pub code:              Option<String>,
+	
+	/// The state sent when the login was started.
+	pub state:             Option<String>,
+	
+	/// The error code, if the user was not authenticated.
+	pub error:             Option<String>,
+	
+	/// A description of the error, if one was given.
+	pub error_description: Option<String>,
+}
+
+//		OidcCache																
+/// A cache of the provider's discovery document and signing keys.
+/// 
+/// These are fetched when first needed, and then reused until they are older
+/// than the configured [`cache_lifetime`](Oidc#structfield.cache_lifetime).
+/// The keys are also fetched again if an ID token is signed with a key that
+/// they do not include, so that new keys are picked up straight away when the
+/// provider rotates them.
+/// 
+#[derive(Debug, Default)]
+pub struct OidcCache {
+	//		Private properties													
+	/// The provider's discovery document.
+	discovery: RwLock<Option<Cached<Discovery>>>,
+	
+	/// The provider's published signing keys.
+	keys:      RwLock<Option<Cached<JwkSet>>>,
+}
+
+//󰭅		OidcCache																
+impl OidcCache {
+	//		new																	
+	/// Creates a new, empty cache.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+
+//		OidcLogin																
+/// The query parameters accepted when starting a login.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct OidcLogin {
+	//		Public properties													
+	/// The URL to redirect to after logging in. Defaults to `/`.
+	pub uri: Option<String>,
+}
+
+//		PendingLogin															
+/// The details of a login in progress, as stored in the session.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct PendingLogin {
+	//		Private properties													
+	/// The state, which ties the callback to the session.
+	state:    String,
+	
+	/// The nonce, which ties the ID token to the session.
+	nonce:    String,
+	
+	/// The PKCE code verifier.
+	verifier: String,
+	
+	/// The URL to redirect to after logging in.
+	uri:      String,
+}
+
+//		TokenResponse															
+/// The parts of the token endpoint's response that are needed to log in.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct TokenResponse {
+	//		Private properties													
+	/// The ID token.
+	id_token: Option<String>,
+}
+
+
+
+//		Traits																											
+
+//§		OidcUserProvider														
+/// A trait for providing user data from the claims of an ID token.
+/// 
+/// This is used by [`get_oidc_callback()`] to map a user who has been
+/// authenticated by the provider to a user of the application. The user could
+/// be looked up using the subject or email address, or created on first login.
+/// 
+pub trait OidcUserProvider: UserProvider {
+	//		find_by_id_claims													
+	/// Finds a user by the claims of a validated ID token.
+	/// 
+	/// Returns [`Some(User)`](Some) if the claims identify a user who may log
+	/// in, otherwise returns [`None`].
+	/// 
+	/// # Parameters
+	/// 
+	/// * `state`  - The application state.
+	/// * `claims` - The claims of the validated ID token.
+	/// 
+	/// # Errors
+	/// 
+	/// If the lookup fails, an error will be returned.
+	/// 
+	fn find_by_id_claims<SP: StateProvider>(
+		state:  &SP,
+		claims: &IdClaims,
+	) -> impl Future<Output = Result<Option<Self::User>, Self::Error>> + Send;
+}
+
+
+
+//		Functions																										
+
+//		algorithm																
+/// Converts a configured algorithm into the equivalent token algorithm.
+/// 
+/// # Parameters
+/// 
+/// * `algorithm` - The configured algorithm.
+/// 
+const fn algorithm(algorithm: JwtAlgorithm) -> Algorithm {
+	match algorithm {
+		JwtAlgorithm::Hs256 => Algorithm::HS256,
+		JwtAlgorithm::Rs256 => Algorithm::RS256,
+		JwtAlgorithm::EdDsa => Algorithm::EdDSA,
+	}
+}
+
+//		authorization_url														
+/// Builds the URL that the user is sent to in order to log in.
+/// 
+/// # Parameters
+/// 
+/// * `config`    - The OIDC configuration.
+/// * `discovery` - The provider's discovery document.
+/// * `pending`   - The details of the login.
+/// 
+fn authorization_url(config: &Oidc, discovery: &Discovery, pending: &PendingLogin) -> Result<Url, OidcError> {
+	let mut scopes = vec!["openid"];
+	scopes.extend(config.scopes.iter().map(String::as_str).filter(|scope| *scope != "openid"));
+	Url::parse_with_params(&discovery.authorization_endpoint, &[
+		("response_type",         "code"),
+		("client_id",             &config.client_id),
+		("redirect_uri",          &config.redirect_uri),
+		("scope",                 &scopes.join(" ")),
+		("state",                 &pending.state),
+		("nonce",                 &pending.nonce),
+		("code_challenge",        &pkce_challenge(&pending.verifier)),
+		("code_challenge_method", "S256"),
+	]).map_err(|err| OidcError::InvalidResponse(format!("Invalid authorisation endpoint: {err}")))
+}
+
+//		client																	
+/// Creates an HTTP client for contacting the provider.
+/// 
+/// # Parameters
+/// 
+/// * `config` - The OIDC configuration.
+/// 
+fn client(config: &Oidc) -> Result<Client, OidcError> {
+	Client::builder()
+		.timeout(Duration::from_secs(config.timeout))
+		.build()
+		.map_err(|err| OidcError::Request(err.to_string()))
+}
+
+//		decoding_key															
+/// Gets the key for verifying an ID token.
+/// 
+/// `HS256` tokens are verified using the client secret. Otherwise, the one of
+/// the provider's published keys identified by the token's key ID is used. If
+/// the token has no key ID, the provider must only publish a single key. The
+/// cached keys are used if they include a match, and otherwise the keys are
+/// fetched again.
+/// 
+/// # Parameters
+/// 
+/// * `client`    - The HTTP client.
+/// * `config`    - The OIDC configuration.
+/// * `cache`     - The cache of the provider's details.
+/// * `discovery` - The provider's discovery document.
+/// * `alg`       - The algorithm that the token is signed with.
+/// * `kid`       - The key ID given in the token's header, if any.
+/// 
+async fn decoding_key(
+	client:    &Client,
+	config:    &Oidc,
+	cache:     &OidcCache,
+	discovery: &Discovery,
+	alg:       Algorithm,
+	kid:       Option<&str>,
+) -> Result<DecodingKey, OidcError> {
+	if alg == Algorithm::HS256 {
+		let secret = config.client_secret.as_ref()
+			.ok_or_else(|| OidcError::InvalidIdToken(s!("HS256 token, but no client secret is configured")))?
+		;
+		return Ok(DecodingKey::from_secret(secret.as_bytes()));
+	}
+	let cached = Cached::fresh(&cache.keys, Duration::from_secs(config.cache_lifetime))
+		.and_then(|keys| find_key(&keys, kid).cloned())
+	;
+	let jwk    = if let Some(known) = cached {
+		known
+	} else {
+		let keys  = fetch::<JwkSet>(client.get(&discovery.jwks_uri)).await?;
+		let found = find_key(&keys, kid).cloned();
+		Cached::store(&cache.keys, keys);
+		found.ok_or_else(|| OidcError::InvalidIdToken(format!("No matching signing key: {}", kid.unwrap_or("none"))))?
+	};
+	DecodingKey::from_jwk(&jwk).map_err(|err| OidcError::InvalidResponse(format!("Invalid signing key: {err}")))
+}
+
+//		discover																
+/// Gets the provider's discovery document, fetching it if the cached copy is
+/// missing or has expired.
+/// 
+/// # Parameters
+/// 
+/// * `client` - The HTTP client.
+/// * `config` - The OIDC configuration.
+/// * `cache`  - The cache of the provider's details.
+/// 
+async fn discover(client: &Client, config: &Oidc, cache: &OidcCache) -> Result<Discovery, OidcError> {
+	if let Some(cached) = Cached::fresh(&cache.discovery, Duration::from_secs(config.cache_lifetime)) {
+		return Ok(cached);
+	}
+	let url       = format!("{}/.well-known/openid-configuration", config.issuer.trim_end_matches('/'));
+	let discovery = fetch::<Discovery>(client.get(url)).await?;
+	if discovery.issuer != config.issuer {
+		return Err(OidcError::InvalidResponse(format!("Issuer mismatch in discovery document: {}", discovery.issuer)));
+	}
+	Cached::store(&cache.discovery, discovery.clone());
+	Ok(discovery)
+}
+
+//		exchange_code															
+/// Exchanges an authorisation code for an ID token.
+/// 
+/// # Parameters
+/// 
+/// * `client`    - The HTTP client.
+/// * `config`    - The OIDC configuration.
+/// * `discovery` - The provider's discovery document.
+/// * `code`      - The authorisation code.
+/// * `verifier`  - The PKCE code verifier.
+/// 
+async fn exchange_code(
+	client:    &Client,
+	config:    &Oidc,
+	discovery: &Discovery,
+	code:      &str,
+	verifier:  &str,
+) -> Result<String, OidcError> {
+	let mut form = vec![
+		("grant_type",    "authorization_code"),
+		("code",          code),
+		("redirect_uri",  &config.redirect_uri),
+		("client_id",     &config.client_id),
+		("code_verifier", verifier),
+	];
+	if let Some(ref secret) = config.client_secret {
+		form.push(("client_secret", secret));
+	}
+	fetch::<TokenResponse>(client.post(&discovery.token_endpoint).form(&form)).await?
+		.id_token
+		.ok_or_else(|| OidcError::InvalidResponse(s!("No ID token in token response")))
+}
+
+//		fetch																	
+/// Sends a request to the provider, and parses the JSON response.
+/// 
+/// # Parameters
+/// 
+/// * `request` - The request to send.
+/// 
+async fn fetch<T: DeserializeOwned>(request: RequestBuilder) -> Result<T, OidcError> {
+	let response = request.send().await.map_err(|err| OidcError::Request(err.to_string()))?;
+	let status   = response.status();
+	if !status.is_success() {
+		let url  = response.url().clone();
+		let body = response.text().await.unwrap_or_default();
+		return Err(OidcError::InvalidResponse(format!("{status} from {url}: {body}")));
+	}
+	response.json::<T>().await.map_err(|err| OidcError::InvalidResponse(err.to_string()))
+}
+
+//		find_key																
+/// Finds the signing key for a token among the provider's published keys.
+/// 
+/// # Parameters
+/// 
+/// * `keys` - The provider's published keys.
+/// * `kid`  - The key ID given in the token's header, if any. If there is
+///            none, the provider must only publish a single key.
+/// 
+fn find_key<'k>(keys: &'k JwkSet, kid: Option<&str>) -> Option<&'k Jwk> {
+	kid.map_or_else(
+		|| match *keys.keys.as_slice() {
+			[ref only] => Some(only),
+			_          => None,
+		},
+		|id| keys.find(id),
+	)
+}
+
+//		get_oidc_callback														
+/// Completes a login using the OpenID Connect provider.
+/// 
+/// Checks that the callback matches the login started in the session,
+/// exchanges the authorisation code for an ID token, validates the token, and
+/// logs in the user that its claims map to. The user is then redirected to
+/// the URL given when the login was started. If the login fails, the reason is
+/// logged, and the redirect has a `failed` parameter, in the same way as for
+/// [`post_login()`](super::handlers::post_login()). When the `totp` feature is
+/// enabled, users who need a second factor are sent to the second login step
+/// instead of being logged in, as they are after a password login.
+/// 
+/// # Parameters
+/// 
+/// * `state`    - The application state.
+/// * `auth`     - The authentication context.
+/// * `callback` - The query parameters sent by the provider.
+/// 
+/// # Errors
+/// 
+/// If there is no session, or the user lookup fails, an error will be
+/// returned. Problems with the provider, such as it being unreachable or
+/// sending an unusable response, are logged rather than returned, so that
+/// their details are not exposed to the user.
+/// 
+pub async fn get_oidc_callback<SP, U, UP>(
+	State(state):    State<Arc<SP>>,
+	mut auth:        Context<U>,
+	Query(callback): Query<OidcCallback>,
+) -> Result<Redirect, AuthError>
+where
+	SP: StateProvider,
+	U:  User,
+	UP: OidcUserProvider<User = U>,
+{
+	let session    = auth.session().ok_or(AuthError::NoSession)?;
+	let pending    = session.remove::<PendingLogin>(SESSION_OIDC_KEY).await?;
+	let uri        = safe_redirect_uri(
+		pending.as_ref().map_or("/", |login| &login.uri),
+		&state.config().redirects.allowed_origins,
+	);
+	let mut params = extract_uri_query_parts(&uri);
+	if pending.is_none() {
+		warn!("OIDC callback received without a login in progress: the session cookie needs SameSite=Lax or None");
+	}
+	let user = match verify_callback(&state.config().oidc, state.oidc_cache(), pending.as_ref(), &callback).await {
+		Ok(claims) => {
+			UP::find_by_id_claims(&*state, &claims).await
+				.map_err(|err| AuthError::UserProviderError(Box::new(err)))?
+				.ok_or_else(|| {
+					warn!("No user found for OIDC subject: {}", claims.sub);
+					claims.sub.clone()
+				})
+		},
+		Err(err)   => {
+			warn!("Failed OIDC login: {err}");
+			Err(s!("unknown"))
+		},
+	};
+	match user {
+		Ok(ref current) => {
+			#[cfg(feature = "totp")]
+			if current.requires_two_factor() {
+				info!("Awaiting second factor for OIDC user: {}", current.to_loggable_string());
+				start_totp_login(&auth, current, &uri.to_string(), false).await?;
+				return Ok(Redirect::to(&state.config().totp.path));
+			}
+			info!("Logging in user via OIDC: {}", current.to_loggable_string());
+			auth.login(&*state, current).await?;
+			record_event(&*state, AuthEvent::Login { user: current.to_loggable_string(), method: LoginMethod::Oidc }, auth.client()).await;
+		},
+		Err(subject)    => {
+			drop(params.insert(s!("failed"), s!("")));
+			record_event(&*state, AuthEvent::LoginFailed { user: subject, method: LoginMethod::Oidc }, auth.client()).await;
+		},
+	}
+	Ok(Redirect::to(&redirect_target(&uri, &params)?))
+}
+
+//		get_oidc_login															
+/// Starts a login using the OpenID Connect provider.
+/// 
+/// Records a random state, nonce, and PKCE code verifier in the session, and
+/// redirects the user to the provider to log in. If the provider's discovery
+/// document cannot be obtained, the reason is logged, and the user is
+/// redirected back to the requested URL with a `failed` parameter instead.
+/// 
+/// # Parameters
+/// 
+/// * `state`  - The application state.
+/// * `auth`   - The authentication context.
+/// * `params` - The query parameters.
+/// 
+/// # Errors
+/// 
+/// If there is no session, or the session cannot be updated, an error will
+/// be returned.
+/// 
+pub async fn get_oidc_login<SP, U>(
+	State(state):  State<Arc<SP>>,
+	auth:          Context<U>,
+	Query(params): Query<OidcLogin>,
+) -> Result<Redirect, AuthError>
+where
+	SP: StateProvider,
+	U:  User,
+{
+	let session = auth.session().ok_or(AuthError::NoSession)?;
+	let config  = &state.config().oidc;
+	let pending = PendingLogin {
+		state:    generate_token(STATE_LENGTH),
+		nonce:    generate_token(NONCE_LENGTH),
+		verifier: generate_token(VERIFIER_LENGTH),
+		uri:      params.uri.unwrap_or_else(|| s!("/")),
+	};
+	let url     = match login_url(config, state.oidc_cache(), &pending).await {
+		Ok(url)  => url,
+		Err(err) => {
+			warn!("Failed to start OIDC login: {err}");
+			let uri        = safe_redirect_uri(&pending.uri, &state.config().redirects.allowed_origins);
+			let mut failed = extract_uri_query_parts(&uri);
+			drop(failed.insert(s!("failed"), s!("")));
+			return Ok(Redirect::to(&redirect_target(&uri, &failed)?));
+		},
+	};
+	session.insert(SESSION_OIDC_KEY, &pending).await?;
+	Ok(Redirect::to(url.as_str()))
+}
+
+//		login_url																
+/// Builds the URL that sends the user to the provider to log in.
+/// 
+/// # Parameters
+/// 
+/// * `config`  - The OIDC configuration.
+/// * `cache`   - The cache of the provider's details.
+/// * `pending` - The login being started.
+/// 
+/// # Errors
+/// 
+/// If the provider's discovery document cannot be obtained, or its
+/// authorisation endpoint is invalid, an error will be returned.
+/// 
+async fn login_url(config: &Oidc, cache: &OidcCache, pending: &PendingLogin) -> Result<Url, OidcError> {
+	let discovery = discover(&client(config)?, config, cache).await?;
+	authorization_url(config, &discovery, pending)
+}
+
+//		pkce_challenge															
+/// Derives the PKCE code challenge from a code verifier, using the `S256`
+/// method.
+/// 
+/// # Parameters
+/// 
+/// * `verifier` - The code verifier.
+/// 
+fn pkce_challenge(verifier: &str) -> String {
+	BASE64.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+//		validate_id_token														
+/// Validates an ID token, and returns its claims.
+/// 
+/// The token must be signed with one of the configured algorithms, by the
+/// provider, and must have been issued by the provider, for this client, for
+/// the login in progress, and not yet have expired.
+/// 
+/// # Parameters
+/// 
+/// * `client`    - The HTTP client.
+/// * `config`    - The OIDC configuration.
+/// * `cache`     - The cache of the provider's details.
+/// * `discovery` - The provider's discovery document.
+/// * `token`     - The ID token.
+/// * `nonce`     - The nonce sent when the login was started.
+/// 
+async fn validate_id_token(
+	client:    &Client,
+	config:    &Oidc,
+	cache:     &OidcCache,
+	discovery: &Discovery,
+	token:     &str,
+	nonce:     &str,
+) -> Result<IdClaims, OidcError> {
+	let header = decode_header(token).map_err(|err| OidcError::InvalidIdToken(err.to_string()))?;
+	if !config.algorithms.iter().any(|&allowed| algorithm(allowed) == header.alg) {
+		return Err(OidcError::InvalidIdToken(format!("Algorithm not allowed: {:?}", header.alg)));
+	}
+	let key                 = decoding_key(client, config, cache, discovery, header.alg, header.kid.as_deref()).await?;
+	let mut validation      = Validation::new(header.alg);
+	validation.leeway       = config.leeway;
+	validation.set_issuer(&[&config.issuer]);
+	validation.set_audience(&[&config.client_id]);
+	validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+	let claims              = decode::<IdClaims>(token, &key, &validation)
+		.map_err(|err| OidcError::InvalidIdToken(err.to_string()))?
+		.claims
+	;
+	if !claims.nonce.as_deref().is_some_and(|claimed| tokens_match(nonce, claimed)) {
+		return Err(OidcError::InvalidIdToken(s!("Nonce mismatch")));
+	}
+	Ok(claims)
+}
+
+//		verify_callback															
+/// Verifies a callback from the provider, and returns the user's claims.
+/// 
+/// # Parameters
+/// 
+/// * `config`   - The OIDC configuration.
+/// * `cache`    - The cache of the provider's details.
+/// * `pending`  - The login in progress, if any.
+/// * `callback` - The query parameters sent by the provider.
+/// 
+async fn verify_callback(
+	config:   &Oidc,
+	cache:    &OidcCache,
+	pending:  Option<&PendingLogin>,
+	callback: &OidcCallback,
+) -> Result<IdClaims, OidcError> {
+	if let Some(ref error) = callback.error {
+		return Err(OidcError::Rejected(callback.error_description.as_ref().map_or_else(
+			|| error.clone(),
+			|description| format!("{error}: {description}"),
+		)));
+	}
+	let (Some(login), Some(code), Some(returned)) = (pending, callback.code.as_ref(), callback.state.as_ref()) else {
+		return Err(OidcError::InvalidState);
+	};
+	if !tokens_match(&login.state, returned) {
+		return Err(OidcError::InvalidState);
+	}
+	let client    = client(config)?;
+	let discovery = discover(&client, config, cache).await?;
+	let token     = exchange_code(&client, config, &discovery, code, &login.verifier).await?;
+	validate_id_token(&client, config, cache, &discovery, &token, &login.nonce).await
+}
+
+
@@ -0,0 +1,278 @@
+//! Password hashing and verification.
+//! 
+//! Passwords are hashed using Argon2id, and stored as [PHC strings](https://github.com/P-H-C/phc-string-format/blob/master/phc-sf-spec.md),
+//! which record the algorithm, parameters, and salt alongside the hash. This
+//! means that the parameters can be changed in future without invalidating
+//! existing hashes. Verification also supports bcrypt hashes, in their usual
+//! `$2b$` modular crypt format, to allow migration from existing systems.
+//! 
+//! Hash comparison is performed in constant time by the underlying
+//! implementations, to avoid leaking information through timing differences.
+//! 
+//! Hashing is deliberately slow, taking tens of milliseconds. When called from
+//! an async context with high concurrency, consider running it using
+//! [`spawn_blocking()`](tokio::task::spawn_blocking()).
+
+
+
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/password.rs"]
+mod tests;
+
+
+
+//		Packages																										
+
+use super::errors::PasswordError;
+use argon2::{
+	Argon2,
+	password_hash::{
+		Error as PhcError,
+		PasswordHash,
+		PasswordHasher as _,
+		PasswordVerifier as _,
+		SaltString,
+		rand_core::OsRng,
+	},
+};
+use bcrypt::{HashParts as BcryptHashParts, verify as bcrypt_verify};
+use core::fmt::{Display, Formatter, self};
+use rubedo::sugar::s;
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as DeError};
+use std::sync::LazyLock;
+use tracing::warn;
+
+
+
+//		Constants																										
+
+/// The prefixes used by bcrypt hashes.
+const BCRYPT_PREFIXES: [&str; 4] = ["$2a$", "$2b$", "$2x$", "$2y$"];
+
+/// A hash of an arbitrary password, used to equalise the time taken to reject
+/// unknown users with the time taken to reject known users.
+static DUMMY_HASH: LazyLock<Option<HashedPassword>> = LazyLock::new(|| {
+	hash_password("dummy password").ok()
+});
+
+
+
+//		Structs																											
+
+//		HashedPassword															
+/// A validated password hash.
+/// 
+/// This wraps a password hash string that is known to be in a supported
+/// format, i.e. an Argon2 PHC string or a bcrypt hash. It is validated when
+/// created or deserialised, so that a malformed hash in configuration is
+/// reported at startup, rather than when a user tries to log in.
+/// 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HashedPassword(String);
+
+//󰭅		HashedPassword															
+impl HashedPassword {
+	//		new																	
+	/// Creates a new validated password hash.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `hash` - The password hash string.
+	/// 
+	/// # Errors
+	/// 
+	/// If the hash cannot be parsed, or uses an unsupported algorithm, an
+	/// error will be returned.
+	/// 
+	pub fn new<S: Into<String>>(hash: S) -> Result<Self, PasswordError> {
+		let string = hash.into();
+		validate_hash(&string)?;
+		Ok(Self(string))
+	}
+	
+	//		as_str																
+	/// Gets the password hash string.
+	#[must_use]
+	pub fn as_str(&self) -> &str {
+		&self.0
+	}
+	
+	//		verify																
+	/// Verifies a password against the hash.
+	/// 
+	/// Returns `true` if the password matches, otherwise `false`.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `password` - The password to verify.
+	/// 
+	#[must_use]
+	pub fn verify(&self, password: &str) -> bool {
+		verify_password(password, &self.0).unwrap_or_else(|err| {
+			warn!("Failed to verify password: {err}");
+			false
+		})
+	}
+}
+
+//󰭅		Deserialize																
+impl<'de> Deserialize<'de> for HashedPassword {
+	//		deserialize															
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		Self::new(String::deserialize(deserializer)?).map_err(DeError::custom)
+	}
+}
+
+//󰭅		Display																	
+impl Display for HashedPassword {
+	//		fmt																	
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.0)
+	}
+}
+
+//󰭅		Serialize																
+impl Serialize for HashedPassword {
+	//		serialize															
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		serializer.serialize_str(&self.0)
+	}
+}
+
+
+
+//		Functions																										
+
+//		hash_password															
+/// Hashes a password.
+/// 
+/// The password is hashed using Argon2id with the default parameters and a
+/// random salt, and returned as a PHC string.
+/// 
+/// # Parameters
+/// 
+/// * `password` - The password to hash.
+/// 
+/// # Errors
+/// 
+/// If the password cannot be hashed, an error will be returned.
+/// 
+pub fn hash_password(password: &str) -> Result<HashedPassword, PasswordError> {
+	let salt = SaltString::generate(&mut OsRng);
+	Argon2::default()
+		.hash_password(password.as_bytes(), &salt)
+		.map(|hash| HashedPassword(hash.to_string()))
+		.map_err(|err| PasswordError::FailedToHash(err.to_string()))
+}
+
+//		is_bcrypt																
+/// Checks whether a password hash is a bcrypt hash.
+/// 
+/// # Parameters
+/// 
+/// * `hash` - The password hash string.
+/// 
+fn is_bcrypt(hash: &str) -> bool {
+	BCRYPT_PREFIXES.iter().any(|prefix| hash.starts_with(prefix))
+}
+
+//		parse_argon2															
+/// Parses an Argon2 PHC string.
+/// 
+/// # Parameters
+/// 
+/// * `hash` - The password hash string.
+/// 
+/// # Errors
+/// 
+/// If the hash cannot be parsed, or is not an Argon2 hash, an error will be
+/// returned.
+/// 
+fn parse_argon2(hash: &str) -> Result<PasswordHash<'_>, PasswordError> {
+	let parsed = PasswordHash::new(hash).map_err(|err| PasswordError::InvalidHash(err.to_string()))?;
+	match parsed.algorithm.as_str() {
+		"argon2id" | "argon2i" | "argon2d" => {},
+		other                              => return Err(PasswordError::UnsupportedAlgorithm(other.to_owned())),
+	}
+	if parsed.salt.is_none() || parsed.hash.is_none() {
+		return Err(PasswordError::InvalidHash(s!("Missing salt or hash")));
+	}
+	Ok(parsed)
+}
+
+//		validate_hash															
+/// Validates that a password hash is in a supported format.
+/// 
+/// # Parameters
+/// 
+/// * `hash` - The password hash string.
+/// 
+/// # Errors
+/// 
+/// If the hash cannot be parsed, or uses an unsupported algorithm, an error
+/// will be returned.
+/// 
+fn validate_hash(hash: &str) -> Result<(), PasswordError> {
+	if is_bcrypt(hash) {
+		return hash.parse::<BcryptHashParts>()
+			.map(|_| ())
+			.map_err(|err| PasswordError::InvalidHash(err.to_string()))
+		;
+	}
+	parse_argon2(hash).map(|_| ())
+}
+
+//		verify_dummy															
+/// Performs a password verification that always fails.
+/// 
+/// This should be called when a login is attempted for a user that does not
+/// exist, so that the time taken to reject the attempt is the same as for a
+/// user that does exist but supplied the wrong password. Without this, the
+/// difference in timing would reveal which usernames are valid.
+/// 
+/// # Parameters
+/// 
+/// * `password` - The password that was supplied.
+/// 
+pub fn verify_dummy(password: &str) {
+	if let Some(ref hash) = *DUMMY_HASH {
+		_ = hash.verify(password);
+	}
+}
+
+//		verify_password															
+/// Verifies a password against a hash.
+/// 
+/// The hash may be an Argon2 PHC string, or a bcrypt hash. Returns `true` if
+/// the password matches, otherwise `false`.
+/// 
+/// # Parameters
+/// 
+/// * `password` - The password to verify.
+/// * `hash`     - The password hash string.
+/// 
+/// # Errors
+/// 
+/// If the hash cannot be parsed, or uses an unsupported algorithm, an error
+/// will be returned.
+/// 
+pub fn verify_password(password: &str, hash: &str) -> Result<bool, PasswordError> {
+	if is_bcrypt(hash) {
+		return bcrypt_verify(password, hash).map_err(|err| PasswordError::InvalidHash(err.to_string()));
+	}
+	match Argon2::default().verify_password(password.as_bytes(), &parse_argon2(hash)?) {
+		Ok(())                  => Ok(true),
+		Err(PhcError::Password) => Ok(false),
+		Err(err)                => Err(PasswordError::InvalidHash(err.to_string())),
+	}
+}
+
+
@@ -0,0 +1,116 @@
+//! Permission-based authorisation.
+//! 
+//! Users can hold roles and permissions, as described by the [`User`] trait.
+//! Routes that require a permission can be added using
+//! [`routes_requiring()`](super::routing::RouterExt::routes_requiring()), and
+//! individual handlers can require a permission using the [`Permitted`]
+//! extractor. In both cases, users who lack the permission receive a 403
+//! response, and the decision is logged.
+
+
+
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/permissions.rs"]
+mod tests;
+
+
+
+//		Packages																										
+
+use super::{
+	middleware::{Context, User, forbidden},
+	responses::{Problem, ResponseFormat},
+};
+use axum::{
+	extract::FromRequestParts,
+	http::{StatusCode, request::Parts},
+	response::{IntoResponse, Response},
+};
+use core::marker::PhantomData;
+use tracing::{info, warn};
+
+
+
+//		Structs																											
+
+//		Permitted																
+/// A user who has been granted a permission.
+/// 
+/// This extractor obtains the current user from the authentication context,
+/// and checks that they have the permission specified by `P`, using
+/// [`User::has_permission()`]. If there is no current user, the request is
+/// rejected with a 401 response, and if the user lacks the permission, it is
+/// rejected with a 403 response. The form of the response depends upon the
+/// [`ResponseFormat`](super::responses::ResponseFormat) for the request.
+/// 
+/// Unlike [`routes_requiring()`](super::routing::RouterExt::routes_requiring()),
+/// this does not show the login page to unauthenticated browsers, and so is
+/// best used on routes that are already protected.
+/// 
+#[derive(Clone, Debug)]
+pub struct Permitted<U: User, P: Permission> {
+	//		Public properties													
+	/// The current user.
+	pub user:   U,
+	
+	//		Private properties													
+	/// The permission that was checked.
+	permission: PhantomData<P>,
+}
+
+//󰭅		FromRequestParts														
+impl<S, U, P> FromRequestParts<S> for Permitted<U, P>
+where
+	S: Send + Sync,
+	U: User,
+	P: Permission,
+{
+	type Rejection = Response;
+	
+	//		from_request_parts													
+	/// Creates a permitted user from the request parts.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `parts` - The request parts.
+	/// * `state` - The application state.
+	/// 
+	async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+		let auth_cx = Context::<U>::from_request_parts(parts, state).await.map_err(IntoResponse::into_response)?;
+		let format  = auth_cx.response_format(&parts.extensions, &parts.headers);
+		let Some(user) = auth_cx.current_user else {
+			return Err(match format {
+				ResponseFormat::Json => Problem::unauthorized(auth_cx.challenge()),
+				ResponseFormat::Html => StatusCode::UNAUTHORIZED.into_response(),
+			});
+		};
+		if !user.has_permission(P::NAME) {
+			warn!("Permission {} denied to user: {}", P::NAME, user.to_loggable_string());
+			return Err(forbidden(format));
+		}
+		info!("Permission {} granted to user: {}", P::NAME, user.to_loggable_string());
+		Ok(Self {
+			user,
+			permission: PhantomData,
+		})
+	}
+}
+
+
+
+//		Traits																											
+
+//§		Permission																
+/// A permission that can be required by the [`Permitted`] extractor.
+/// 
+/// This is typically implemented on a unit struct for each permission, so that
+/// the permission required by a handler can be specified by type.
+/// 
+pub trait Permission: Send + Sync + 'static {
+	/// The name of the permission, as checked by [`User::has_permission()`].
+	const NAME: &'static str;
+}
+
+
@@ -0,0 +1,537 @@
+//! Persistent "remember me" logins.
+//! 
+//! When a user logs in with the "remember me" box ticked, they are issued a
+//! long-lived cookie alongside the session cookie, which is used by
+//! [`auth_layer()`](super::middleware::auth_layer()) to log them back in once
+//! their session has ended, for instance because the browser was closed or the
+//! session expired.
+//! 
+//! The cookie holds a token made up of two random parts: a selector, which
+//! identifies the token in the [`RememberStore`], and a validator, of which
+//! only a SHA-256 hash is stored. This means that tokens cannot be used to log
+//! in if the contents of the store are leaked.
+//! 
+//! Each time a token is used, its validator is replaced and the cookie is
+//! updated. If a validator that has already been replaced is presented, the
+//! cookie must have been copied and used by someone else, and so all of the
+//! user's tokens and sessions are revoked. As browsers may send several
+//! requests at once with the same cookie, the previous validator is still
+//! accepted for a short grace period after it has been replaced. The policy is
+//! configured using [`Remember`](super::config::Remember).
+
+
+
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/remember.rs"]
+mod tests;
+
+
+
+//		Packages																										
+
+use super::{
+	audit::{AuthEvent, LoginMethod, record_event},
+	config::Config,
+	errors::AuthError,
+	middleware::{Context, User, UserProvider},
+	revocation::revoke_sessions,
+	state::StateProvider,
+	utility::{generate_token, tokens_match},
+};
+use axum::http::{HeaderMap, header::COOKIE};
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD as BASE64};
+use core::{
+	convert::Infallible,
+	error::Error,
+	fmt::Debug,
+	future::Future,
+	mem,
+};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use std::collections::HashMap;
+use tower_sessions::cookie::{Cookie, time::{Duration, OffsetDateTime}};
+use tracing::{info, warn};
+
+
+
+//		Constants																										
+
+/// The number of random bytes in a token's selector.
+const SELECTOR_LENGTH:  usize = 12;
+
+/// The number of random bytes in a token's validator.
+const VALIDATOR_LENGTH: usize = 32;
+
+
+
+//		Enums																											
+
+//		Verdict																	
+/// The result of checking a validator against a stored token.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Verdict {
+	/// The validator is the current one.
+	Current,
+	
+	/// The token has expired.
+	Expired,
+	
+	/// The validator is the previous one, and is within the grace period.
+	Previous,
+	
+	/// The validator does not match, so the token may have been stolen.
+	Stolen,
+}
+
+
+
+//		Structs																											
+
+//		MemoryRememberStore														
+/// A remember-me token store that holds tokens in memory.
+/// 
+/// Tokens are lost when the application restarts, so users will need to log in
+/// again at that point. Expired tokens are removed whenever a token is saved.
+/// 
+#[derive(Debug, Default)]
+pub struct MemoryRememberStore {
+	//		Private properties													
+	/// The tokens, keyed by selector.
+	tokens: Mutex<HashMap<String, RememberToken>>,
+}
+
+//󰭅		MemoryRememberStore														
+impl MemoryRememberStore {
+	//		new																	
+	/// Creates a new in-memory remember-me token store.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+
+//󰭅		RememberStore															
+impl RememberStore for MemoryRememberStore {
+	type Error = Infallible;
+	
+	//		delete																
+	async fn delete(&self, selector: &str) -> Result<(), Self::Error> {
+		drop(self.tokens.lock().remove(selector));
+		Ok(())
+	}
+	
+	//		delete_for_user														
+	async fn delete_for_user(&self, user_id: &str) -> Result<usize, Self::Error> {
+		let mut tokens = self.tokens.lock();
+		let before     = tokens.len();
+		tokens.retain(|_, token| token.user_id != user_id);
+		Ok(before.saturating_sub(tokens.len()))
+	}
+	
+	//		load																
+	async fn load(&self, selector: &str) -> Result<Option<RememberToken>, Self::Error> {
+		Ok(self.tokens.lock().get(selector).cloned())
+	}
+	
+	//		save																
+	async fn save(&self, token: &RememberToken) -> Result<(), Self::Error> {
+		let now        = OffsetDateTime::now_utc().unix_timestamp();
+		let mut tokens = self.tokens.lock();
+		tokens.retain(|_, stored| stored.expires_at > now);
+		drop(tokens.insert(token.selector.clone(), token.clone()));
+		drop(tokens);
+		Ok(())
+	}
+}
+
+//		RememberToken															
+/// A remember-me token, as held in a [`RememberStore`].
+/// 
+/// Only hashes of the validators are held, and so a token cannot be used to
+/// log in without the cookie that it was issued in.
+/// 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[expect(clippy::exhaustive_structs, reason = "Needs to be constructable by store implementations")]
+pub struct RememberToken {
+	//		Public properties													
+	/// The selector, which identifies the token.
+	pub selector:       String,
+	
+	/// The hash of the current validator.
+	pub validator_hash: String,
+	
+	/// The hash of the previous validator, if it has been replaced.
+	pub previous_hash:  Option<String>,
+	
+	/// The time at which the validator was last replaced, or the token was
+	/// issued, as a Unix timestamp.
+	pub rotated_at:     i64,
+	
+	/// The ID of the user that the token logs in, serialised as JSON.
+	pub user_id:        String,
+	
+	/// The time at which the token expires, as a Unix timestamp.
+	pub expires_at:     i64,
+}
+
+
+
+//		Traits																											
+
+//§		RememberStore															
+/// A trait for storing remember-me tokens.
+/// 
+/// Tokens should ideally be held in a persistent store, such as a database, so
+/// that users remain logged in across restarts of the application.
+/// [`MemoryRememberStore`] can be used where that is not required.
+/// 
+pub trait RememberStore: Debug + Send + Sync + 'static {
+	/// The error type returned when the store cannot be accessed.
+	type Error: Error + Send + Sync + 'static;
+	
+	//		delete																
+	/// Deletes a token.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `selector` - The token's selector.
+	/// 
+	/// # Errors
+	/// 
+	/// If the store cannot be accessed, an error will be returned.
+	/// 
+	fn delete(&self, selector: &str) -> impl Future<Output = Result<(), Self::Error>> + Send;
+	
+	//		delete_for_user														
+	/// Deletes all of a user's tokens.
+	/// 
+	/// Returns the number of tokens that were deleted.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `user_id` - The user's ID, serialised as JSON.
+	/// 
+	/// # Errors
+	/// 
+	/// If the store cannot be accessed, an error will be returned.
+	/// 
+	fn delete_for_user(&self, user_id: &str) -> impl Future<Output = Result<usize, Self::Error>> + Send;
+	
+	//		load																
+	/// Loads a token.
+	/// 
+	/// Returns [`Some(RememberToken)`](Some) if the token exists, otherwise
+	/// returns [`None`]. Expired tokens may be returned, and will be deleted by
+	/// the caller.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `selector` - The token's selector.
+	/// 
+	/// # Errors
+	/// 
+	/// If the store cannot be accessed, an error will be returned.
+	/// 
+	fn load(&self, selector: &str) -> impl Future<Output = Result<Option<RememberToken>, Self::Error>> + Send;
+	
+	//		save																
+	/// Saves a token, replacing any existing token with the same selector.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `token` - The token to save.
+	/// 
+	/// # Errors
+	/// 
+	/// If the store cannot be accessed, an error will be returned.
+	/// 
+	fn save(&self, token: &RememberToken) -> impl Future<Output = Result<(), Self::Error>> + Send;
+}
+
+
+
+//		Functions																										
+
+//		forget_login															
+/// Forgets the remember-me token supplied with a request.
+/// 
+/// The token is deleted from the store, and a cookie that removes it from the
+/// browser is returned, to be set on the response. If no token was supplied,
+/// there is nothing to forget, and [`None`] is returned. This is called when
+/// the user logs out.
+/// 
+/// # Parameters
+/// 
+/// * `state`   - The application state.
+/// * `headers` - The request headers.
+/// 
+/// # Errors
+/// 
+/// If the store cannot be accessed, an error will be returned.
+/// 
+pub async fn forget_login<SP: StateProvider>(state: &SP, headers: &HeaderMap) -> Result<Option<Cookie<'static>>, AuthError> {
+	let config = state.config();
+	let Some((selector, _)) = token_from_headers(headers, &config.remember.cookie_name) else {
+		return Ok(None);
+	};
+	state.remember_store().delete(&selector).await.map_err(store_error)?;
+	Ok(Some(removal_cookie(config)))
+}
+
+//		forget_user																
+/// Forgets all of a user's remember-me tokens.
+/// 
+/// Returns the number of tokens that were forgotten. This is called by
+/// [`revoke_sessions()`], so that revoked users are not logged back in.
+/// 
+/// # Parameters
+/// 
+/// * `state`   - The application state.
+/// * `user_id` - The user's ID.
+/// 
+/// # Errors
+/// 
+/// If the user ID cannot be serialised, or the store cannot be accessed, an
+/// error will be returned.
+/// 
+pub async fn forget_user<SP, I>(state: &SP, user_id: &I) -> Result<usize, AuthError>
+where
+	SP: StateProvider,
+	I:  Serialize + Sync + ?Sized,
+{
+	let user_key = serde_json::to_string(user_id).map_err(store_error)?;
+	state.remember_store().delete_for_user(&user_key).await.map_err(store_error)
+}
+
+//		hash_validator															
+/// Hashes a validator for storage.
+/// 
+/// # Parameters
+/// 
+/// * `validator` - The validator to hash.
+/// 
+fn hash_validator(validator: &str) -> String {
+	BASE64.encode(Sha256::digest(validator.as_bytes()))
+}
+
+//		remember_login															
+/// Issues a remember-me token for a user.
+/// 
+/// The token is saved in the store, and a cookie holding it is returned, to be
+/// set on the response. This is called when the user logs in with the
+/// "remember me" box ticked.
+/// 
+/// # Parameters
+/// 
+/// * `state` - The application state.
+/// * `user`  - The user who has logged in.
+/// 
+/// # Errors
+/// 
+/// If the user ID cannot be serialised, or the store cannot be accessed, an
+/// error will be returned.
+/// 
+pub async fn remember_login<SP, U>(state: &SP, user: &U) -> Result<Cookie<'static>, AuthError>
+where
+	SP: StateProvider,
+	U:  User,
+{
+	let config    = state.config();
+	let now       = OffsetDateTime::now_utc().unix_timestamp();
+	let lifetime  = i64::try_from(config.remember.lifetime).unwrap_or(i64::MAX);
+	let selector  = generate_token(SELECTOR_LENGTH);
+	let validator = generate_token(VALIDATOR_LENGTH);
+	let token     = RememberToken {
+		selector:       selector.clone(),
+		validator_hash: hash_validator(&validator),
+		previous_hash:  None,
+		rotated_at:     now,
+		user_id:        serde_json::to_string(user.id()).map_err(store_error)?,
+		expires_at:     now.saturating_add(lifetime),
+	};
+	state.remember_store().save(&token).await.map_err(store_error)?;
+	Ok(token_cookie(config, format!("{selector}:{validator}"), lifetime))
+}
+
+//		removal_cookie															
+/// Creates a cookie that removes the remember-me token from the browser.
+/// 
+/// # Parameters
+/// 
+/// * `config` - The authentication configuration.
+/// 
+fn removal_cookie(config: &Config) -> Cookie<'static> {
+	token_cookie(config, String::new(), 0)
+}
+
+//		restore_login															
+/// Logs a user back in using the remember-me token supplied with a request.
+/// 
+/// If the token is valid, the user it belongs to is logged in to the session
+/// in the authentication context, and its validator is replaced. A cookie is
+/// returned to be set on the response, which holds the new validator if the
+/// token was valid, or removes the token from the browser if it was not. If no
+/// token was supplied, or the previous validator was supplied within the grace
+/// period, no cookie is returned.
+/// 
+/// If a validator that has been replaced is supplied outside of the grace
+/// period, the token is assumed to have been stolen, and all of the user's
+/// tokens and sessions are revoked.
+/// 
+/// # Parameters
+/// 
+/// * `state`   - The application state.
+/// * `auth_cx` - The authentication context.
+/// * `headers` - The request headers.
+/// 
+/// # Errors
+/// 
+/// If the store cannot be accessed, the user lookup fails, or the session
+/// cannot be updated, an error will be returned.
+/// 
+pub async fn restore_login<SP, U, UP>(
+	state:   &SP,
+	auth_cx: &mut Context<U>,
+	headers: &HeaderMap,
+) -> Result<Option<Cookie<'static>>, AuthError>
+where
+	SP: StateProvider,
+	U:  User,
+	UP: UserProvider<User = U>,
+{
+	let config = state.config();
+	if !config.remember.enabled || auth_cx.session().is_none() {
+		return Ok(None);
+	}
+	let Some((selector, validator)) = token_from_headers(headers, &config.remember.cookie_name) else {
+		return Ok(None);
+	};
+	let store = state.remember_store();
+	let now   = OffsetDateTime::now_utc().unix_timestamp();
+	let Some(mut token) = store.load(&selector).await.map_err(store_error)? else {
+		info!("Unknown remember-me token");
+		return Ok(Some(removal_cookie(config)));
+	};
+	let verdict = verify(&token, &validator, now, config.remember.grace_period);
+	match verdict {
+		Verdict::Current | Verdict::Previous => {},
+		Verdict::Expired                     => {
+			info!("Expired remember-me token for user: {}", token.user_id);
+			store.delete(&selector).await.map_err(store_error)?;
+			return Ok(Some(removal_cookie(config)));
+		},
+		Verdict::Stolen                      => {
+			warn!("Reused remember-me token for user: {}: revoking all of their tokens and sessions", token.user_id);
+			match serde_json::from_str::<U::Id>(&token.user_id) {
+				Ok(user_id) => revoke_sessions(state, &user_id).await?,
+				Err(_)      => _ = store.delete_for_user(&token.user_id).await.map_err(store_error)?,
+			}
+			return Ok(Some(removal_cookie(config)));
+		},
+	}
+	let user_id = serde_json::from_str::<U::Id>(&token.user_id).map_err(store_error)?;
+	let Some(user) = UP::find_by_id(state, &user_id).await
+		.map_err(|err| AuthError::UserProviderError(Box::new(err)))?
+	else {
+		store.delete(&selector).await.map_err(store_error)?;
+		return Ok(Some(removal_cookie(config)));
+	};
+	let cookie = if verdict == Verdict::Current {
+		let new_validator   = generate_token(VALIDATOR_LENGTH);
+		token.previous_hash = Some(mem::replace(&mut token.validator_hash, hash_validator(&new_validator)));
+		token.rotated_at    = now;
+		store.save(&token).await.map_err(store_error)?;
+		Some(token_cookie(config, format!("{selector}:{new_validator}"), token.expires_at.saturating_sub(now)))
+	} else {
+		None
+	};
+	auth_cx.login(state, &user).await?;
+	info!("Restored login from remember-me token for user: {}", user.to_loggable_string());
+	record_event(state, AuthEvent::Login { user: user.to_loggable_string(), method: LoginMethod::RememberMe }, auth_cx.client()).await;
+	Ok(cookie)
+}
+
+//		store_error																
+/// Converts an error from the store into an [`AuthError`].
+/// 
+/// # Parameters
+/// 
+/// * `err` - The error to convert.
+/// 
+fn store_error<E: Error + Send + Sync + 'static>(err: E) -> AuthError {
+	AuthError::RememberStoreError(Box::new(err))
+}
+
+//		token_cookie															
+/// Creates a cookie holding a remember-me token.
+/// 
+/// # Parameters
+/// 
+/// * `config`  - The authentication configuration.
+/// * `value`   - The cookie's value.
+/// * `max_age` - The cookie's lifetime, in seconds.
+/// 
+fn token_cookie(config: &Config, value: String, max_age: i64) -> Cookie<'static> {
+	let mut builder = Cookie::build((config.remember.cookie_name.clone(), value))
+		.path(config.cookie.path.clone())
+		.secure(config.cookie.secure)
+		.http_only(true)
+		.same_site(config.cookie.same_site.into())
+		.max_age(Duration::seconds(max_age))
+	;
+	if let Some(ref domain) = config.cookie.domain {
+		builder = builder.domain(domain.clone());
+	}
+	builder.build()
+}
+
+//		token_from_headers														
+/// Gets the selector and validator from the remember-me cookie, if present.
+/// 
+/// # Parameters
+/// 
+/// * `headers` - The request headers.
+/// * `name`    - The name of the cookie.
+/// 
+fn token_from_headers(headers: &HeaderMap, name: &str) -> Option<(String, String)> {
+	let cookie                = headers.get_all(COOKIE).iter()
+		.filter_map(|value| value.to_str().ok())
+		.flat_map(Cookie::split_parse)
+		.filter_map(Result::ok)
+		.find(|cookie| cookie.name() == name)?
+	;
+	let (selector, validator) = cookie.value().split_once(':')?;
+	(!selector.is_empty() && !validator.is_empty()).then(|| (selector.to_owned(), validator.to_owned()))
+}
+
+//		verify																	
+/// Checks a validator against a stored token.
+/// 
+/// # Parameters
+/// 
+/// * `token`        - The stored token.
+/// * `validator`    - The supplied validator.
+/// * `now`          - The current time, as a Unix timestamp.
+/// * `grace_period` - How long the previous validator is accepted for, in
+///                    seconds.
+/// 
+fn verify(token: &RememberToken, validator: &str, now: i64, grace_period: u64) -> Verdict {
+	if now >= token.expires_at {
+		return Verdict::Expired;
+	}
+	let hash = hash_validator(validator);
+	if tokens_match(&token.validator_hash, &hash) {
+		return Verdict::Current;
+	}
+	let in_grace = now < token.rotated_at.saturating_add(i64::try_from(grace_period).unwrap_or(i64::MAX));
+	match token.previous_hash {
+		Some(ref previous) if in_grace && tokens_match(previous, &hash) => Verdict::Previous,
+		_                                                               => Verdict::Stolen,
+	}
+}
+
+
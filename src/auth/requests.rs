@@ -26,6 +26,10 @@ pub struct PostLogin<C: Credentials> {
 	
 	/// The URL to redirect to after logging in.
 	pub uri:         String,
+	
+	/// Whether the "remember me" box was ticked, to keep the user logged in
+	/// after their session ends.
+	pub remember:    bool,
 }
 
 //󰭅		Deserialize																
@@ -44,6 +48,8 @@ where
 			#[serde(flatten)]
 			credentials: C,
 			uri:         String,
+			#[serde(default)]
+			remember:    Option<String>,
 		}
 		
 		let helper = Helper::deserialize(deserializer)?;
@@ -51,6 +57,7 @@ where
 		Ok(Self {
 			credentials: helper.credentials,
 			uri:         helper.uri,
+			remember:    helper.remember.is_some(),
 		})
 	}
 }
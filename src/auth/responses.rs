@@ -0,0 +1,220 @@
+//! Response data for authentication functionality.
+
+
+
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/responses.rs"]
+mod tests;
+
+
+
+//		Packages																										
+
+use axum::{
+	Json,
+	body::Body,
+	http::{
+		Extensions,
+		HeaderMap,
+		HeaderValue,
+		Request,
+		StatusCode,
+		header::{ACCEPT, CONTENT_TYPE, WWW_AUTHENTICATE},
+	},
+	response::{AppendHeaders, IntoResponse, Response},
+};
+use rubedo::sugar::s;
+use serde::Serialize;
+
+
+
+//		Enums																											
+
+//		ResponseFormat															
+/// The format to use for authentication failure responses.
+/// 
+/// By default this is negotiated from the request's `Accept` header, using
+/// [`for_request()`](ResponseFormat::for_request()). A specific format can
+/// be chosen for a set of routes by adding it to the request extensions, using
+/// [`RouterExt::set_response_format()`](super::routing::RouterExt::set_response_format()),
+/// which will take precedence over the `Accept` header.
+/// 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[expect(clippy::exhaustive_enums, reason = "Exhaustive")]
+pub enum ResponseFormat {
+	/// HTML, for browsers. Unauthenticated requests are shown the login page.
+	Html,
+	
+	/// JSON, for API clients. Unauthenticated or unauthorised requests receive
+	/// a [`Problem`] body.
+	Json,
+}
+
+//󰭅		ResponseFormat															
+impl ResponseFormat {
+	//		for_request															
+	/// Determines the response format for a request.
+	/// 
+	/// If a format has been set in the request extensions, that is used.
+	/// Otherwise, the format is negotiated from the `Accept` header.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `request` - The request.
+	/// 
+	#[must_use]
+	pub fn for_request(request: &Request<Body>) -> Self {
+		Self::select(request.extensions(), request.headers())
+	}
+	
+	//		negotiate															
+	/// Negotiates the response format from the `Accept` header.
+	/// 
+	/// JSON is chosen only if the client prefers `application/json` (or a JSON
+	/// problem body) over `text/html`, taking quality values into account.
+	/// If there is no `Accept` header, or the preferences are equal, as with
+	/// `*/*`, HTML is chosen, so that browsers continue to see the login page.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `headers` - The request headers.
+	/// 
+	#[must_use]
+	pub fn negotiate(headers: &HeaderMap) -> Self {
+		let mut html = 0.0_f32;
+		let mut json = 0.0_f32;
+		for value in headers.get_all(ACCEPT) {
+			let Ok(accept) = value.to_str() else {
+				continue;
+			};
+			for range in accept.split(',') {
+				let mut params = range.split(';').map(str::trim);
+				let media_type = params.next().unwrap_or_default().to_ascii_lowercase();
+				let quality    = params
+					.find_map(|param| param.strip_prefix("q="))
+					.and_then(|q| q.parse::<f32>().ok())
+					.unwrap_or(1.0)
+				;
+				match media_type.as_str() {
+					"text/html" | "application/xhtml+xml"           => html = html.max(quality),
+					"application/json" | "application/problem+json" => json = json.max(quality),
+					"*/*"                                           => {
+						html = html.max(quality);
+						json = json.max(quality);
+					},
+					_                                               => {},
+				}
+			}
+		}
+		if json > html { Self::Json } else { Self::Html }
+	}
+	
+	//		select																
+	/// Determines the response format from request extensions and headers.
+	/// 
+	/// This is the same as [`for_request()`](ResponseFormat::for_request()),
+	/// but can be used where only the parts of the request are available, such
+	/// as in extractors.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `extensions` - The request extensions.
+	/// * `headers`    - The request headers.
+	/// 
+	#[must_use]
+	pub fn select(extensions: &Extensions, headers: &HeaderMap) -> Self {
+		extensions.get::<Self>().copied().unwrap_or_else(|| Self::negotiate(headers))
+	}
+}
+
+
+
+//		Structs																											
+
+//		Problem																	
+/// A problem details response body, as defined by [RFC 9457](https://www.rfc-editor.org/rfc/rfc9457).
+/// 
+/// This is returned to API clients when authentication or authorisation fails,
+/// and is served with the `application/problem+json` content type.
+/// 
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct Problem {
+	//		Public properties													
+	/// A URI reference that identifies the problem type.
+	#[serde(rename = "type")]
+	pub kind:   String,
+	
+	/// A short, human-readable summary of the problem type.
+	pub title:  String,
+	
+	/// The HTTP status code.
+	pub status: u16,
+	
+	/// A human-readable explanation specific to this occurrence of the problem.
+	pub detail: String,
+}
+
+//󰭅		Problem																	
+impl Problem {
+	//		new																	
+	/// Creates a new problem details response body.
+	/// 
+	/// The title is taken from the status code's canonical reason.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `status` - The HTTP status code.
+	/// * `detail` - An explanation of the problem.
+	/// 
+	#[must_use]
+	pub fn new<S: Into<String>>(status: StatusCode, detail: S) -> Self {
+		Self {
+			kind:   s!("about:blank"),
+			title:  status.canonical_reason().unwrap_or_default().to_owned(),
+			status: status.as_u16(),
+			detail: detail.into(),
+		}
+	}
+	
+	//		forbidden															
+	/// Creates a response for a request that lacks permission.
+	#[must_use]
+	pub fn forbidden() -> Response {
+		Self::new(StatusCode::FORBIDDEN, "You do not have permission to access this resource").into_response()
+	}
+	
+	//		unauthorized														
+	/// Creates a response for a request that needs authentication.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `challenge` - The value for the `WWW-Authenticate` header, which
+	///                 indicates how the client should authenticate. If this is
+	///                 not a valid header value, the header is omitted.
+	/// 
+	#[must_use]
+	pub fn unauthorized(challenge: &str) -> Response {
+		(
+			AppendHeaders(HeaderValue::from_str(challenge).ok().map(|value| (WWW_AUTHENTICATE, value))),
+			Self::new(StatusCode::UNAUTHORIZED, "Authentication is required to access this resource"),
+		).into_response()
+	}
+}
+
+//󰭅		IntoResponse															
+impl IntoResponse for Problem {
+	//		into_response														
+	fn into_response(self) -> Response {
+		let status = StatusCode::from_u16(self.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+		(
+			status,
+			[(CONTENT_TYPE, HeaderValue::from_static("application/problem+json"))],
+			Json(self),
+		).into_response()
+	}
+}
+
+
@@ -0,0 +1,217 @@
+//! Revocation of all of the sessions belonging to a user.
+//! 
+//! Session stores are keyed by session ID, and do not provide a way to find the
+//! sessions that belong to a particular user. Instead, each user has a
+//! revocation generation, held in the [`RevocationStore`], which is recorded in
+//! the session when the user logs in. Revoking a user's sessions using
+//! [`revoke_sessions()`], for instance from an admin handler when an account is
+//! disabled or its password is changed, advances their generation, and any
+//! session that was logged into under an earlier generation is logged out on
+//! its next request, by [`Context::get_user()`](super::middleware::Context::get_user()).
+//! 
+//! As the generation is checked on every request that has a logged-in session,
+//! the store should be fast to query. It should also be persistent, and shared
+//! by every instance of the application, such as a table in the application's
+//! database, so that revocation reaches sessions created before a restart or
+//! on another instance. [`MemoryRevocationStore`] can be used where the
+//! application runs as a single instance and uses an in-memory session store.
+
+
+
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/revocation.rs"]
+mod tests;
+
+
+
+//		Packages																										
+
+use super::{
+	errors::AuthError,
+	remember::forget_user,
+	state::StateProvider,
+};
+use core::{
+	convert::Infallible,
+	error::Error,
+	fmt::Debug,
+	future::Future,
+};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::collections::HashMap;
+use tracing::info;
+
+
+
+//		Structs																											
+
+//		MemoryRevocationStore													
+/// A revocation store that holds users' revocation generations in memory.
+/// 
+/// Generations are lost when the application restarts, and are not shared
+/// between instances, so this is only suitable for applications that run as a
+/// single instance, with an in-memory session store.
+/// 
+#[derive(Debug, Default)]
+pub struct MemoryRevocationStore {
+	//		Private properties													
+	/// The revocation generation of each user who has had their sessions
+	/// revoked, keyed by user ID.
+	generations: Mutex<HashMap<String, u64>>,
+}
+
+//󰭅		MemoryRevocationStore													
+impl MemoryRevocationStore {
+	//		new																	
+	/// Creates a new in-memory revocation store.
+	#[must_use]
+	pub fn new() -> Self {
+		Self::default()
+	}
+}
+
+//󰭅		RevocationStore															
+impl RevocationStore for MemoryRevocationStore {
+	type Error = Infallible;
+	
+	//		generation															
+	async fn generation(&self, user_id: &str) -> Result<u64, Self::Error> {
+		Ok(self.generations.lock().get(user_id).copied().unwrap_or_default())
+	}
+	
+	//		revoke																
+	async fn revoke(&self, user_id: &str) -> Result<u64, Self::Error> {
+		let mut generations = self.generations.lock();
+		let generation      = generations.entry(user_id.to_owned()).or_default();
+		*generation         = generation.saturating_add(1);
+		let revoked         = *generation;
+		drop(generations);
+		Ok(revoked)
+	}
+}
+
+
+
+//		Traits																											
+
+//§		RevocationStore															
+/// A trait for storing the revocation generation of each user.
+/// 
+/// A user's generation starts at zero, and is advanced each time their sessions
+/// are revoked. Generations should ideally be held in a persistent store that
+/// is shared by every instance of the application, such as a database, so that
+/// revocation applies across restarts and instances. [`MemoryRevocationStore`]
+/// can be used where that is not required.
+/// 
+pub trait RevocationStore: Debug + Send + Sync + 'static {
+	/// The error type returned when the store cannot be accessed.
+	type Error: Error + Send + Sync + 'static;
+	
+	//		generation															
+	/// Gets a user's current revocation generation.
+	/// 
+	/// Returns zero if the user's sessions have never been revoked.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `user_id` - The user's ID, serialised as JSON.
+	/// 
+	/// # Errors
+	/// 
+	/// If the store cannot be accessed, an error will be returned.
+	/// 
+	fn generation(&self, user_id: &str) -> impl Future<Output = Result<u64, Self::Error>> + Send;
+	
+	//		revoke																
+	/// Advances a user's revocation generation, and returns the new one.
+	/// 
+	/// This must be atomic, so that concurrent revocations, including those
+	/// made by other instances of the application, each advance the generation.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `user_id` - The user's ID, serialised as JSON.
+	/// 
+	/// # Errors
+	/// 
+	/// If the store cannot be accessed, an error will be returned.
+	/// 
+	fn revoke(&self, user_id: &str) -> impl Future<Output = Result<u64, Self::Error>> + Send;
+}
+
+
+
+//		Functions																										
+
+//		generation																
+/// Gets a user's current revocation generation.
+/// 
+/// This is recorded in the session when the user logs in, and checked on each
+/// request, so that sessions logged into before the user's sessions were last
+/// revoked are not accepted.
+/// 
+/// # Parameters
+/// 
+/// * `state`   - The application state.
+/// * `user_id` - The user's ID.
+/// 
+/// # Errors
+/// 
+/// If the user ID cannot be serialised, or the store cannot be accessed, an
+/// error will be returned.
+/// 
+pub async fn generation<SP, I>(state: &SP, user_id: &I) -> Result<u64, AuthError>
+where
+	SP: StateProvider,
+	I:  Serialize + Sync + ?Sized,
+{
+	let user_key = serde_json::to_string(user_id).map_err(store_error)?;
+	state.revocation_store().generation(&user_key).await.map_err(store_error)
+}
+
+//		revoke_sessions															
+/// Revokes all the sessions belonging to a user.
+/// 
+/// The user's revocation generation is advanced, which means that they will be
+/// logged out on their next request, from every device, including sessions
+/// created by other instances of the application. Any
+/// [remember-me tokens](super::remember) that they hold are also deleted, so
+/// that they are not logged straight back in.
+/// 
+/// # Parameters
+/// 
+/// * `state`   - The application state.
+/// * `user_id` - The user's ID.
+/// 
+/// # Errors
+/// 
+/// If the user ID cannot be serialised, or the remember-me tokens or the
+/// revocation cannot be stored, an error will be returned.
+/// 
+pub async fn revoke_sessions<SP, I>(state: &SP, user_id: &I) -> Result<(), AuthError>
+where
+	SP: StateProvider,
+	I:  Serialize + Sync + ?Sized,
+{
+	_ = forget_user(state, user_id).await?;
+	let user_key   = serde_json::to_string(user_id).map_err(store_error)?;
+	let generation = state.revocation_store().revoke(&user_key).await.map_err(store_error)?;
+	info!("Revoked sessions for user {user_key}, now at generation {generation}");
+	Ok(())
+}
+
+//		store_error																
+/// Converts an error from the store into an [`AuthError`].
+/// 
+/// # Parameters
+/// 
+/// * `err` - The error to convert.
+/// 
+fn store_error<E: Error + Send + Sync + 'static>(err: E) -> AuthError {
+	AuthError::RevocationStoreError(Box::new(err))
+}
+
+
@@ -2,24 +2,44 @@
 
 
 
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/routing.rs"]
+mod tests;
+
+
+
 //		Packages																										
 
 use super::{
-	middleware::{User, UserProvider as UserProvider, auth_layer, protect, protected_error_layer},
+	api_keys::{ApiKeyProvider, api_key_layer},
+	basic::basic_auth_layer,
+	config::SessionKey as ConfigSessionKey,
+	csrf::csrf_layer,
+	middleware::{User, UserProvider, auth_layer, protect, protected_error_layer, require_permission},
+	responses::ResponseFormat,
 	state::StateProvider,
 };
 use crate::app::state::StateProvider as AppStateProvider;
 use axum::{
+	Extension,
 	Router,
 	middleware::from_fn_with_state,
 	routing::MethodRouter,
 };
 use std::sync::Arc;
 use tower_sessions::{
-	MemoryStore as SessionMemoryStore,
+	Expiry,
 	SessionManagerLayer,
-	cookie::Key as SessionKey,
+	cookie::{Key as SessionKey, time::Duration},
 };
+use tracing::warn;
+
+#[cfg(feature = "jwt")]
+use super::jwt::{TokenUserProvider, TokenVerifier, token_auth_layer};
+#[cfg(feature = "jwt")]
+use tracing::error;
 
 
 
@@ -46,6 +66,69 @@ where
 		UP: UserProvider<User = U>,
 	;
 	
+	//		add_api_key_authentication											
+	/// Adds the API key authentication layer.
+	/// 
+	/// This is an alternative to [`add_authentication()`](RouterExt::add_authentication()),
+	/// for service-to-service calls. It is added per router, along with the
+	/// scopes that the router's routes require, and can be combined with
+	/// [`protected_routes()`](RouterExt::protected_routes()) in the same way.
+	/// See the [`api_keys`](super::api_keys) module for details.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `scopes` - The scopes that API keys must have, as checked by
+	///              [`ApiKey::has_scope()`](super::api_keys::ApiKey::has_scope()).
+	/// * `state`  - The application state.
+	/// 
+	#[must_use]
+	fn add_api_key_authentication<SP, U, UP>(self, scopes: &'static [&'static str], state: &Arc<SP>) -> Self
+	where
+		SP: StateProvider,
+		U:  User,
+		UP: ApiKeyProvider<User = U>,
+	;
+	
+	//		add_basic_authentication											
+	/// Adds the HTTP Basic authentication layer.
+	/// 
+	/// This is an alternative to [`add_authentication()`](RouterExt::add_authentication()),
+	/// for machine clients that can only send Basic credentials. It is added
+	/// per router, so that it only applies to the routes that need it, and can
+	/// be combined with [`protected_routes()`](RouterExt::protected_routes())
+	/// in the same way. See the [`basic`](super::basic) module for details.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `state` - The application state.
+	/// 
+	#[must_use]
+	fn add_basic_authentication<SP, U, UP>(self, state: &Arc<SP>) -> Self
+	where
+		SP: StateProvider,
+		U:  User,
+		UP: UserProvider<User = U>,
+	;
+	
+	//		add_csrf_protection													
+	/// Adds the CSRF protection layer.
+	/// 
+	/// This checks that requests using unsafe methods supply the CSRF token for
+	/// their session, as described in the [`csrf`](super::csrf) module. It
+	/// needs the authentication context, and so needs to be added before the
+	/// authentication layers, so that it runs after them.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `state` - The application state.
+	/// 
+	#[must_use]
+	fn add_csrf_protection<SP, U>(self, state: &Arc<SP>) -> Self
+	where
+		SP: StateProvider,
+		U:  User,
+	;
+	
 	//		add_protected_error_catcher											
 	/// Adds an error handler that protects sensitive errors.
 	/// 
@@ -60,6 +143,31 @@ where
 		U:  User,
 	;
 	
+	//		add_token_authentication											
+	/// Adds the bearer token authentication layer.
+	/// 
+	/// This is an alternative to [`add_authentication()`](RouterExt::add_authentication()),
+	/// suitable for APIs, which authenticates requests using JSON Web Tokens
+	/// rather than sessions. It can be combined with
+	/// [`protected_routes()`](RouterExt::protected_routes()) in the same way.
+	/// 
+	/// The configured key is parsed once, when the layer is added. If it cannot
+	/// be parsed, an error is logged, and requests with a bearer token will be
+	/// rejected.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `state` - The application state.
+	/// 
+	#[cfg(feature = "jwt")]
+	#[must_use]
+	fn add_token_authentication<SP, U, UP>(self, state: &Arc<SP>) -> Self
+	where
+		SP: StateProvider,
+		U:  User,
+		UP: TokenUserProvider<User = U>,
+	;
+	
 	//		protected_routes													
 	/// Adds protected routes to the router.
 	/// 
@@ -81,6 +189,50 @@ where
 		SP: StateProvider,
 		U:  User,
 	;
+	
+	//		routes_requiring													
+	/// Adds routes that require a permission to the router.
+	/// 
+	/// This is a convenience method that adds the given routes to the router,
+	/// then adds a middleware layer to protect them. Requests without a current
+	/// user are treated in the same way as for
+	/// [`protected_routes()`](RouterExt::protected_routes()), and requests from
+	/// users who lack the permission receive a 403 response.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `permission` - The permission required, as checked by
+	///                  [`User::has_permission()`].
+	/// * `routes`     - The routes to add.
+	/// * `state`      - The application state.
+	/// 
+	#[must_use]
+	fn routes_requiring<SP, U>(
+		self,
+		permission: &'static str,
+		routes:     Vec<(&str, MethodRouter<S>)>,
+		state:      &Arc<SP>,
+	) -> Self
+	where
+		SP: StateProvider,
+		U:  User,
+	;
+	
+	//		set_response_format													
+	/// Sets the format of authentication failure responses.
+	/// 
+	/// By default, the format is negotiated from each request's `Accept`
+	/// header. This overrides that for all routes that have already been added
+	/// to the router, so that for instance API routes always receive JSON. It
+	/// therefore needs to be called after the routes and the relevant
+	/// authentication layers have been added.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `format` - The response format to use.
+	/// 
+	#[must_use]
+	fn set_response_format(self, format: ResponseFormat) -> Self;
 }
 
 //󰭅		RouterExt																
@@ -96,11 +248,67 @@ where
 		U:  User,
 		UP: UserProvider<User = U>,
 	{
-		let session_key   = SessionKey::generate();
-		let session_store = SessionMemoryStore::default();
+		let config      = state.config();
+		let session_key = config.session_key.as_ref().map_or_else(
+			|| {
+				warn!("No session key configured: generating a random one, so sessions will not survive a restart");
+				SessionKey::generate()
+			},
+			ConfigSessionKey::to_key,
+		);
+		let mut session_layer = SessionManagerLayer::new(state.session_store())
+			.with_name(config.cookie.name.clone())
+			.with_path(config.cookie.path.clone())
+			.with_secure(config.cookie.secure)
+			.with_http_only(config.cookie.http_only)
+			.with_same_site(config.cookie.same_site.into())
+		;
+		if let Some(ref domain) = config.cookie.domain {
+			session_layer = session_layer.with_domain(domain.clone());
+		}
+		//	Reading a session does not count as activity, so the session needs to be
+		//	saved on every request for the inactivity expiry to be extended
+		if let Some(inactivity) = config.expiry.inactivity {
+			session_layer = session_layer
+				.with_expiry(Expiry::OnInactivity(Duration::seconds(i64::try_from(inactivity).unwrap_or(i64::MAX))))
+				.with_always_save(true)
+			;
+		}
 		self
 			.layer(from_fn_with_state(Arc::clone(state), auth_layer::<_, U, UP>))
-			.layer(SessionManagerLayer::new(session_store).with_secure(false).with_signed(session_key))
+			.layer(session_layer.with_signed(session_key))
+	}
+	
+	//		add_api_key_authentication											
+	fn add_api_key_authentication<SP, U, UP>(self, scopes: &'static [&'static str], state: &Arc<SP>) -> Self
+	where
+		SP: StateProvider,
+		U:  User,
+		UP: ApiKeyProvider<User = U>,
+	{
+		self
+			.layer(from_fn_with_state((Arc::clone(state), scopes), api_key_layer::<_, U, UP>))
+	}
+	
+	//		add_basic_authentication											
+	fn add_basic_authentication<SP, U, UP>(self, state: &Arc<SP>) -> Self
+	where
+		SP: StateProvider,
+		U:  User,
+		UP: UserProvider<User = U>,
+	{
+		self
+			.layer(from_fn_with_state(Arc::clone(state), basic_auth_layer::<_, U, UP>))
+	}
+	
+	//		add_csrf_protection													
+	fn add_csrf_protection<SP, U>(self, state: &Arc<SP>) -> Self
+	where
+		SP: StateProvider,
+		U:  User,
+	{
+		self
+			.layer(from_fn_with_state(Arc::clone(state), csrf_layer::<_, U>))
 	}
 	
 	//		add_protected_error_catcher											
@@ -113,6 +321,23 @@ where
 			.layer(from_fn_with_state(Arc::clone(state), protected_error_layer::<_, U>))
 	}
 	
+	//		add_token_authentication											
+	#[cfg(feature = "jwt")]
+	fn add_token_authentication<SP, U, UP>(self, state: &Arc<SP>) -> Self
+	where
+		SP: StateProvider,
+		U:  User,
+		UP: TokenUserProvider<User = U>,
+	{
+		let verifier = TokenVerifier::new(&state.config().jwt)
+			.inspect_err(|err| error!("Invalid JWT key: bearer token authentication will fail: {err}"))
+			.ok()
+			.map(Arc::new)
+		;
+		self
+			.layer(from_fn_with_state((Arc::clone(state), verifier), token_auth_layer::<_, U, UP>))
+	}
+	
 	//		protected_routes													
 	fn protected_routes<SP, U>(self, routes: Vec<(&str, MethodRouter<S>)>, state: &Arc<SP>) -> Self
 	where
@@ -126,6 +351,31 @@ where
 		router
 			.route_layer(from_fn_with_state(Arc::clone(state), protect::<_, U>))
 	}
+	
+	//		routes_requiring													
+	fn routes_requiring<SP, U>(
+		self,
+		permission: &'static str,
+		routes:     Vec<(&str, MethodRouter<S>)>,
+		state:      &Arc<SP>,
+	) -> Self
+	where
+		SP: StateProvider,
+		U:  User,
+	{
+		let mut router = self;
+		for (path, method_router) in routes {
+			router = router.route(path, method_router);
+		}
+		router
+			.route_layer(from_fn_with_state((Arc::clone(state), permission), require_permission::<_, U>))
+	}
+	
+	//		set_response_format													
+	fn set_response_format(self, format: ResponseFormat) -> Self {
+		self
+			.layer(Extension(format))
+	}
 }
 
 
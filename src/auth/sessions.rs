@@ -0,0 +1,19 @@
+//! Persistent session stores.
+//! 
+//! These implement [`SessionStore`](tower_sessions::SessionStore), and can be
+//! supplied through the authentication
+//! [`StateProvider`](super::state::StateProvider) so that sessions survive
+//! application restarts, and can be shared between multiple instances of the
+//! application.
+//! 
+
+
+
+//		Modules																											
+
+#[cfg(feature = "file-store")]
+pub mod file;
+#[cfg(feature = "sqlite-store")]
+pub mod sqlite;
+
+
@@ -0,0 +1,223 @@
+//! File-backed session store.
+
+
+
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/file.rs"]
+mod tests;
+
+
+
+//		Packages																										
+
+use async_trait::async_trait;
+use std::{
+	io::{Error as IoError, ErrorKind as IoErrorKind},
+	path::PathBuf,
+};
+use tokio::fs::{self, OpenOptions};
+use tower_sessions::{
+	ExpiredDeletion,
+	SessionStore,
+	cookie::time::OffsetDateTime,
+	session::{Id, Record},
+	session_store::{Error as SessionStoreError, Result as SessionStoreResult},
+};
+use tracing::warn;
+
+
+
+//		Structs																											
+
+//		FileStore																
+/// A session store that persists sessions as files on disk.
+/// 
+/// Each session is stored as a JSON file named after the session ID, inside
+/// the configured directory. Writes are performed atomically by writing to a
+/// temporary file and then renaming it, so a session file is never left
+/// partially written.
+/// 
+/// This is suitable for single-instance deployments, or for multiple instances
+/// that share a network filesystem. Expired sessions are ignored when loaded,
+/// and can be cleaned up periodically using
+/// [`ExpiredDeletion::continuously_delete_expired()`].
+/// 
+#[derive(Clone, Debug)]
+pub struct FileStore {
+	//		Private properties													
+	/// The directory in which to store the session files.
+	path: PathBuf,
+}
+
+//󰭅		FileStore																
+impl FileStore {
+	//		new																	
+	/// Creates a new file-backed session store.
+	/// 
+	/// The directory will be created when the first session is saved, if it
+	/// does not already exist.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `path` - The directory in which to store the session files.
+	/// 
+	pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+		Self {
+			path: path.into(),
+		}
+	}
+	
+	//		record_path															
+	/// Gets the path of the file for a given session ID.
+	/// 
+	/// Session IDs are URL-safe base64 strings, so are safe to use directly as
+	/// filenames.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `id` - The session ID.
+	/// 
+	fn record_path(&self, id: &Id) -> PathBuf {
+		self.path.join(format!("{id}.json"))
+	}
+	
+	//		read_record															
+	/// Reads a session record from a file.
+	/// 
+	/// Returns [`None`] if the file does not exist.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `path` - The path of the session file.
+	/// 
+	/// # Errors
+	/// 
+	/// If the file cannot be read or decoded, an error will be returned.
+	/// 
+	async fn read_record(path: &PathBuf) -> SessionStoreResult<Option<Record>> {
+		match fs::read(path).await {
+			Ok(contents)                                    => Ok(Some(
+				serde_json::from_slice(&contents).map_err(|err| SessionStoreError::Decode(err.to_string()))?
+			)),
+			Err(err) if err.kind() == IoErrorKind::NotFound => Ok(None),
+			Err(err)                                        => Err(backend_error(&err)),
+		}
+	}
+	
+	//		remove_file															
+	/// Removes a session file, ignoring it if it does not exist.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `path` - The path of the session file.
+	/// 
+	/// # Errors
+	/// 
+	/// If the file exists but cannot be removed, an error will be returned.
+	/// 
+	async fn remove_file(path: &PathBuf) -> SessionStoreResult<()> {
+		match fs::remove_file(path).await {
+			Ok(())                                          => Ok(()),
+			Err(err) if err.kind() == IoErrorKind::NotFound => Ok(()),
+			Err(err)                                        => Err(backend_error(&err)),
+		}
+	}
+}
+
+//󰭅		ExpiredDeletion															
+#[async_trait]
+impl ExpiredDeletion for FileStore {
+	//		delete_expired														
+	async fn delete_expired(&self) -> SessionStoreResult<()> {
+		let mut entries = match fs::read_dir(&self.path).await {
+			Ok(entries)                                     => entries,
+			Err(err) if err.kind() == IoErrorKind::NotFound => return Ok(()),
+			Err(err)                                        => return Err(backend_error(&err)),
+		};
+		let now         = OffsetDateTime::now_utc();
+		while let Some(entry) = entries.next_entry().await.map_err(|err| backend_error(&err))? {
+			let path = entry.path();
+			if path.extension().is_none_or(|ext| ext != "json") {
+				continue;
+			}
+			match Self::read_record(&path).await {
+				Ok(Some(record)) if record.expiry_date <= now => Self::remove_file(&path).await?,
+				Ok(_)                                         => {},
+				Err(err)                                      => warn!("Skipping unreadable session file {}: {err}", path.display()),
+			}
+		}
+		Ok(())
+	}
+}
+
+//󰭅		SessionStore															
+#[async_trait]
+impl SessionStore for FileStore {
+	//		create																
+	async fn create(&self, session_record: &mut Record) -> SessionStoreResult<()> {
+		fs::create_dir_all(&self.path).await.map_err(|err| backend_error(&err))?;
+		//	Claim the file for the ID, so that it cannot collide with an existing
+		//	session, even one being created at the same time
+		loop {
+			match OpenOptions::new().write(true).create_new(true).open(self.record_path(&session_record.id)).await {
+				Ok(_)                                                => break,
+				Err(err) if err.kind() == IoErrorKind::AlreadyExists => session_record.id = Id::default(),
+				Err(err)                                             => return Err(backend_error(&err)),
+			}
+		}
+		self.save(session_record).await
+	}
+	
+	//		save																
+	async fn save(&self, session_record: &Record) -> SessionStoreResult<()> {
+		fs::create_dir_all(&self.path).await.map_err(|err| backend_error(&err))?;
+		let contents  = serde_json::to_vec(session_record).map_err(|err| SessionStoreError::Encode(err.to_string()))?;
+		let path      = self.record_path(&session_record.id);
+		//	Each save has its own temporary file, so that concurrent saves of the
+		//	same session cannot interfere with each other
+		let temp_path = self.path.join(format!("{}.{}.tmp", session_record.id, Id::default()));
+		fs::write(&temp_path, contents).await.map_err(|err| backend_error(&err))?;
+		if let Err(err) = fs::rename(&temp_path, &path).await {
+			drop(fs::remove_file(&temp_path).await);
+			return Err(backend_error(&err));
+		}
+		Ok(())
+	}
+	
+	//		load																
+	async fn load(&self, session_id: &Id) -> SessionStoreResult<Option<Record>> {
+		let path = self.record_path(session_id);
+		Ok(match Self::read_record(&path).await? {
+			Some(record) if record.expiry_date > OffsetDateTime::now_utc() => Some(record),
+			Some(_)                                                          => {
+				Self::remove_file(&path).await?;
+				None
+			},
+			None                                                             => None,
+		})
+	}
+	
+	//		delete																
+	async fn delete(&self, session_id: &Id) -> SessionStoreResult<()> {
+		Self::remove_file(&self.record_path(session_id)).await
+	}
+}
+
+
+
+//		Functions																										
+
+//		backend_error															
+/// Converts an I/O error into a session store error.
+/// 
+/// # Parameters
+/// 
+/// * `err` - The I/O error.
+/// 
+fn backend_error(err: &IoError) -> SessionStoreError {
+	SessionStoreError::Backend(err.to_string())
+}
+
+
@@ -0,0 +1,220 @@
+//! SQLite-backed session store.
+
+
+
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/sqlite.rs"]
+mod tests;
+
+
+
+//		Packages																										
+
+use async_trait::async_trait;
+use sqlx::{
+	Error as SqlxError,
+	SqliteConnection,
+	SqlitePool,
+	query,
+	query_as,
+	query_scalar,
+};
+use tower_sessions::{
+	ExpiredDeletion,
+	SessionStore,
+	cookie::time::OffsetDateTime,
+	session::{Id, Record},
+	session_store::{Error as SessionStoreError, Result as SessionStoreResult},
+};
+
+
+
+//		Structs																											
+
+//		SqliteStore																
+/// A session store that persists sessions in an SQLite database.
+/// 
+/// Sessions are stored in a table called `sessions`, which is created by
+/// [`migrate()`](SqliteStore::migrate()). The session data is stored as JSON,
+/// and the expiry date as a Unix timestamp. Expired sessions are ignored when
+/// loaded, and can be cleaned up periodically using
+/// [`ExpiredDeletion::continuously_delete_expired()`].
+/// 
+#[derive(Clone, Debug)]
+pub struct SqliteStore {
+	//		Private properties													
+	/// The database connection pool.
+	pool: SqlitePool,
+}
+
+//󰭅		SqliteStore																
+impl SqliteStore {
+	//		new																	
+	/// Creates a new SQLite-backed session store.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `pool` - The database connection pool to use.
+	/// 
+	#[must_use]
+	pub const fn new(pool: SqlitePool) -> Self {
+		Self {
+			pool,
+		}
+	}
+	
+	//		migrate																
+	/// Creates the sessions table, if it does not already exist.
+	/// 
+	/// This should be called once when the application starts, before the
+	/// store is used.
+	/// 
+	/// # Errors
+	/// 
+	/// If the table cannot be created, an error will be returned.
+	/// 
+	pub async fn migrate(&self) -> Result<(), SqlxError> {
+		_ = query(r"
+			CREATE TABLE IF NOT EXISTS sessions (
+				id          TEXT    PRIMARY KEY NOT NULL,
+				data        TEXT    NOT NULL,
+				expiry_date INTEGER NOT NULL
+			)
+		").execute(&self.pool).await?;
+		Ok(())
+	}
+	
+	//		id_exists															
+	/// Checks whether a session with the given ID exists.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `conn` - The database connection.
+	/// * `id`   - The session ID.
+	/// 
+	/// # Errors
+	/// 
+	/// If the query fails, an error will be returned.
+	/// 
+	async fn id_exists(conn: &mut SqliteConnection, id: &Id) -> SessionStoreResult<bool> {
+		query_scalar::<_, bool>("SELECT EXISTS(SELECT 1 FROM sessions WHERE id = ?)")
+			.bind(id.to_string())
+			.fetch_one(conn)
+			.await
+			.map_err(|err| backend_error(&err))
+	}
+	
+	//		upsert																
+	/// Inserts or updates a session record.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `conn`   - The database connection.
+	/// * `record` - The session record.
+	/// 
+	/// # Errors
+	/// 
+	/// If the record cannot be encoded or stored, an error will be returned.
+	/// 
+	async fn upsert(conn: &mut SqliteConnection, record: &Record) -> SessionStoreResult<()> {
+		let data = serde_json::to_string(&record.data).map_err(|err| SessionStoreError::Encode(err.to_string()))?;
+		_ = query(r"
+			INSERT INTO sessions (id, data, expiry_date) VALUES (?, ?, ?)
+			ON CONFLICT(id) DO UPDATE SET
+				data        = excluded.data,
+				expiry_date = excluded.expiry_date
+		")
+			.bind(record.id.to_string())
+			.bind(data)
+			.bind(record.expiry_date.unix_timestamp())
+			.execute(conn)
+			.await
+			.map_err(|err| backend_error(&err))?
+		;
+		Ok(())
+	}
+}
+
+//󰭅		ExpiredDeletion															
+#[async_trait]
+impl ExpiredDeletion for SqliteStore {
+	//		delete_expired														
+	async fn delete_expired(&self) -> SessionStoreResult<()> {
+		_ = query("DELETE FROM sessions WHERE expiry_date <= ?")
+			.bind(OffsetDateTime::now_utc().unix_timestamp())
+			.execute(&self.pool)
+			.await
+			.map_err(|err| backend_error(&err))?
+		;
+		Ok(())
+	}
+}
+
+//󰭅		SessionStore															
+#[async_trait]
+impl SessionStore for SqliteStore {
+	//		create																
+	async fn create(&self, session_record: &mut Record) -> SessionStoreResult<()> {
+		let mut tx = self.pool.begin().await.map_err(|err| backend_error(&err))?;
+		//	Make sure that the ID does not collide with an existing session
+		while Self::id_exists(&mut tx, &session_record.id).await? {
+			session_record.id = Id::default();
+		}
+		Self::upsert(&mut tx, session_record).await?;
+		tx.commit().await.map_err(|err| backend_error(&err))
+	}
+	
+	//		save																
+	async fn save(&self, session_record: &Record) -> SessionStoreResult<()> {
+		let mut conn = self.pool.acquire().await.map_err(|err| backend_error(&err))?;
+		Self::upsert(&mut conn, session_record).await
+	}
+	
+	//		load																
+	async fn load(&self, session_id: &Id) -> SessionStoreResult<Option<Record>> {
+		let row = query_as::<_, (String, i64)>("SELECT data, expiry_date FROM sessions WHERE id = ? AND expiry_date > ?")
+			.bind(session_id.to_string())
+			.bind(OffsetDateTime::now_utc().unix_timestamp())
+			.fetch_optional(&self.pool)
+			.await
+			.map_err(|err| backend_error(&err))?
+		;
+		row.map(|(data, expiry_date)| {
+			Ok(Record {
+				id:          *session_id,
+				data:        serde_json::from_str(&data).map_err(|err| SessionStoreError::Decode(err.to_string()))?,
+				expiry_date: OffsetDateTime::from_unix_timestamp(expiry_date).map_err(|err| SessionStoreError::Decode(err.to_string()))?,
+			})
+		}).transpose()
+	}
+	
+	//		delete																
+	async fn delete(&self, session_id: &Id) -> SessionStoreResult<()> {
+		_ = query("DELETE FROM sessions WHERE id = ?")
+			.bind(session_id.to_string())
+			.execute(&self.pool)
+			.await
+			.map_err(|err| backend_error(&err))?
+		;
+		Ok(())
+	}
+}
+
+
+
+//		Functions																										
+
+//		backend_error															
+/// Converts a database error into a session store error.
+/// 
+/// # Parameters
+/// 
+/// * `err` - The database error.
+/// 
+fn backend_error(err: &SqlxError) -> SessionStoreError {
+	SessionStoreError::Backend(err.to_string())
+}
+
+
@@ -0,0 +1,130 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use rubedo::sugar::s;
+use serde_json::json;
+use std::{collections::HashMap, env::temp_dir};
+use tower_sessions::cookie::time::Duration;
+
+
+
+//		Functions																										
+
+//		record																	
+fn record(expiry_date: OffsetDateTime) -> Record {
+	Record {
+		id:          Id::default(),
+		data:        HashMap::from([(s!("user"), json!("joe"))]),
+		expiry_date,
+	}
+}
+
+//		store																	
+fn store() -> FileStore {
+	FileStore::new(temp_dir().join(format!("terracotta-sessions-{}", OffsetDateTime::now_utc().unix_timestamp_nanos())))
+}
+
+
+
+//		Tests																											
+
+//		create__collision														
+#[tokio::test]
+async fn create__collision() {
+	let store      = store();
+	let existing   = record(OffsetDateTime::now_utc() + Duration::hours(1));
+	store.save(&existing).await.unwrap();
+	let mut record = Record { data: HashMap::new(), ..existing.clone() };
+	store.create(&mut record).await.unwrap();
+	assert_ne!(record.id, existing.id);
+	assert_eq!(store.load(&existing.id).await.unwrap(), Some(existing));
+	assert_eq!(store.load(&record.id).await.unwrap(),   Some(record));
+	fs::remove_dir_all(&store.path).await.unwrap();
+}
+
+//		delete_expired															
+#[tokio::test]
+async fn delete_expired() {
+	let store   = store();
+	let expired = record(OffsetDateTime::now_utc() - Duration::hours(1));
+	let current = record(OffsetDateTime::now_utc() + Duration::hours(1));
+	store.save(&expired).await.unwrap();
+	store.save(&current).await.unwrap();
+	fs::write(store.path.join("unrelated.txt"), b"").await.unwrap();
+	store.delete_expired().await.unwrap();
+	assert!(!store.record_path(&expired.id).exists());
+	assert!( store.record_path(&current.id).exists());
+	assert!( store.path.join("unrelated.txt").exists());
+	fs::remove_dir_all(&store.path).await.unwrap();
+}
+
+//		delete_expired__missing_directory										
+#[tokio::test]
+async fn delete_expired__missing_directory() {
+	assert!(store().delete_expired().await.is_ok());
+}
+
+//		load__expired															
+#[tokio::test]
+async fn load__expired() {
+	let store  = store();
+	let record = record(OffsetDateTime::now_utc() - Duration::hours(1));
+	store.save(&record).await.unwrap();
+	assert_eq!(store.load(&record.id).await.unwrap(), None);
+	//	The expired file is removed when it is found
+	assert!(!store.record_path(&record.id).exists());
+	fs::remove_dir_all(&store.path).await.unwrap();
+}
+
+//		load__missing															
+#[tokio::test]
+async fn load__missing() {
+	assert_eq!(store().load(&Id::default()).await.unwrap(), None);
+}
+
+//		save__round_trip														
+#[tokio::test]
+async fn save__round_trip() {
+	let store      = store();
+	let mut record = record(OffsetDateTime::now_utc() + Duration::hours(1));
+	store.save(&record).await.unwrap();
+	assert_eq!(store.load(&record.id).await.unwrap(), Some(record.clone()));
+	//	Saving again replaces the existing record
+	drop(record.data.insert(s!("theme"), json!("dark")));
+	store.save(&record).await.unwrap();
+	assert_eq!(store.load(&record.id).await.unwrap(), Some(record.clone()));
+	store.delete(&record.id).await.unwrap();
+	assert_eq!(store.load(&record.id).await.unwrap(), None);
+	//	Deleting a session that does not exist is not an error
+	assert!(store.delete(&record.id).await.is_ok());
+	fs::remove_dir_all(&store.path).await.unwrap();
+}
+
+//		save__concurrent														
+#[tokio::test]
+async fn save__concurrent() {
+	let store     = store();
+	let record    = record(OffsetDateTime::now_utc() + Duration::hours(1));
+	let mut saves = Vec::new();
+	for _ in 0..10 {
+		let task_store  = store.clone();
+		let task_record = record.clone();
+		saves.push(tokio::spawn(async move { task_store.save(&task_record).await }));
+	}
+	for save in saves {
+		save.await.unwrap().unwrap();
+	}
+	assert_eq!(store.load(&record.id).await.unwrap(), Some(record));
+	//	No temporary files are left behind
+	let mut entries = fs::read_dir(&store.path).await.unwrap();
+	let mut count   = 0_usize;
+	while entries.next_entry().await.unwrap().is_some() {
+		count += 1;
+	}
+	assert_eq!(count, 1);
+	fs::remove_dir_all(&store.path).await.unwrap();
+}
+
+
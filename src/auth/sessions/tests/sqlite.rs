@@ -0,0 +1,104 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use rubedo::sugar::s;
+use serde_json::json;
+use sqlx::sqlite::SqlitePoolOptions;
+use std::collections::HashMap;
+use tower_sessions::cookie::time::Duration;
+
+
+
+//		Functions																										
+
+//		record																	
+fn record(expiry_date: OffsetDateTime) -> Record {
+	Record {
+		id:          Id::default(),
+		data:        HashMap::from([(s!("user"), json!("joe"))]),
+		//	Expiry dates are stored to the second
+		expiry_date: expiry_date.replace_nanosecond(0).unwrap(),
+	}
+}
+
+//		store																	
+async fn store() -> SqliteStore {
+	//	Each connection to an in-memory database gets its own database, so only
+	//	one connection can be used
+	let pool  = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+	let store = SqliteStore::new(pool);
+	store.migrate().await.unwrap();
+	store
+}
+
+
+
+//		Tests																											
+
+//		create__collision														
+#[tokio::test]
+async fn create__collision() {
+	let store      = store().await;
+	let existing   = record(OffsetDateTime::now_utc() + Duration::hours(1));
+	store.save(&existing).await.unwrap();
+	let mut record = Record { data: HashMap::new(), ..existing.clone() };
+	store.create(&mut record).await.unwrap();
+	assert_ne!(record.id, existing.id);
+	assert_eq!(store.load(&existing.id).await.unwrap(), Some(existing));
+	assert_eq!(store.load(&record.id).await.unwrap(),   Some(record));
+}
+
+//		delete_expired															
+#[tokio::test]
+async fn delete_expired() {
+	let store   = store().await;
+	let expired = record(OffsetDateTime::now_utc() - Duration::hours(1));
+	let current = record(OffsetDateTime::now_utc() + Duration::hours(1));
+	store.save(&expired).await.unwrap();
+	store.save(&current).await.unwrap();
+	store.delete_expired().await.unwrap();
+	let ids: Vec<String> = query_scalar("SELECT id FROM sessions").fetch_all(&store.pool).await.unwrap();
+	assert_eq!(ids, vec![current.id.to_string()]);
+}
+
+//		load__expired															
+#[tokio::test]
+async fn load__expired() {
+	let store  = store().await;
+	let record = record(OffsetDateTime::now_utc() - Duration::hours(1));
+	store.save(&record).await.unwrap();
+	assert_eq!(store.load(&record.id).await.unwrap(), None);
+}
+
+//		load__missing															
+#[tokio::test]
+async fn load__missing() {
+	assert_eq!(store().await.load(&Id::default()).await.unwrap(), None);
+}
+
+//		migrate																	
+#[tokio::test]
+async fn migrate() {
+	let store = store().await;
+	//	Migrating again leaves the existing table in place
+	assert!(store.migrate().await.is_ok());
+}
+
+//		save__round_trip														
+#[tokio::test]
+async fn save__round_trip() {
+	let store      = store().await;
+	let mut record = record(OffsetDateTime::now_utc() + Duration::hours(1));
+	store.save(&record).await.unwrap();
+	assert_eq!(store.load(&record.id).await.unwrap(), Some(record.clone()));
+	//	Saving again replaces the existing record
+	drop(record.data.insert(s!("theme"), json!("dark")));
+	store.save(&record).await.unwrap();
+	assert_eq!(store.load(&record.id).await.unwrap(), Some(record.clone()));
+	store.delete(&record.id).await.unwrap();
+	assert_eq!(store.load(&record.id).await.unwrap(), None);
+}
+
+
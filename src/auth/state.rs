@@ -4,8 +4,18 @@
 
 //		Packages																										
 
+use super::{
+	audit::AuditSink,
+	config::Config,
+	lockout::LockoutTracker,
+	remember::RememberStore,
+	revocation::RevocationStore,
+	users::ConfigUserStore,
+};
+#[cfg(feature = "oidc")]
+use super::oidc::OidcCache;
 use crate::app::state::StateProvider as AppStateProvider;
-use std::collections::HashMap;
+use tower_sessions::SessionStore;
 
 
 
@@ -14,9 +24,73 @@ use std::collections::HashMap;
 //§		StateProvider															
 /// A trait for providing the application state aspects for authentication.
 pub trait StateProvider: AppStateProvider + Send + Sync + 'static {
+	/// The audit sink type. This is where authentication events, such as
+	/// logins and failed logins, will be recorded. A
+	/// [`TracingAuditSink`](super::audit::TracingAuditSink) can be used to
+	/// include them in the application logs.
+	type AuditSink: AuditSink;
+	
+	/// The remember-me token store type. This is where the tokens used for
+	/// persistent logins will be kept, and ideally should be persistent, so
+	/// that users stay logged in across restarts. A
+	/// [`MemoryRememberStore`](super::remember::MemoryRememberStore) can be
+	/// used if that is not required.
+	type RememberStore: RememberStore;
+	
+	/// The revocation store type. This is where each user's revocation
+	/// generation, which is used to revoke all of their sessions at once, will
+	/// be kept. It is checked on every request with a logged-in session, and
+	/// should be persistent and shared by every instance of the application, so
+	/// that revocation applies across restarts and instances. A
+	/// [`MemoryRevocationStore`](super::revocation::MemoryRevocationStore) can
+	/// be used if the application runs as a single instance, with an in-memory
+	/// session store.
+	type RevocationStore: RevocationStore;
+	
+	/// The session store type. This is where session data will be persisted,
+	/// and could be in memory, on disk, or in a database. The `file-store` and
+	/// `sqlite-store` features provide ready-made persistent stores, and any
+	/// other [`SessionStore`] implementation can also be used.
+	type SessionStore: SessionStore + Clone;
+	
+	//		audit_sink															
+	/// Gets the sink that authentication events are recorded to.
+	fn audit_sink(&self) -> &Self::AuditSink;
+	
+	//		config																
+	/// Gets the authentication configuration.
+	fn config(&self) -> &Config;
+	
+	//		lockout																
+	/// Gets the tracker used to protect logins against brute-force attacks.
+	fn lockout(&self) -> &LockoutTracker;
+	
+	//		oidc_cache															
+	/// Gets the cache of the OpenID Connect provider's discovery document and
+	/// signing keys.
+	#[cfg(feature = "oidc")]
+	fn oidc_cache(&self) -> &OidcCache;
+	
+	//		remember_store														
+	/// Gets the store of remember-me tokens.
+	fn remember_store(&self) -> &Self::RememberStore;
+	
+	//		revocation_store													
+	/// Gets the store of users' revocation generations.
+	fn revocation_store(&self) -> &Self::RevocationStore;
+	
+	//		session_store														
+	/// Gets the session store.
+	/// 
+	/// This is called once, when the authentication layer is added to the
+	/// router. Session stores are cheaply cloneable, with the clones sharing
+	/// the same underlying storage.
+	/// 
+	fn session_store(&self) -> Self::SessionStore;
+	
 	//		users																
-	/// Gets list of users.
-	fn users(&self) -> &HashMap<String, String>;
+	/// Gets the list of users defined in configuration.
+	fn users(&self) -> &ConfigUserStore;
 }
 
 
@@ -0,0 +1,485 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use crate::{
+	app::{config::HtmlTemplates, errors::AppError, state::StateProvider as AppStateProvider},
+	auth::{
+		audit::{AuditEntry, AuditSink},
+		config::{Config, Lockout},
+		lockout::LockoutTracker,
+		middleware::Credentials,
+		remember::MemoryRememberStore,
+		revocation::MemoryRevocationStore,
+		routing::RouterExt as _,
+		users::ConfigUserStore,
+	},
+};
+use axum::{
+	Router,
+	body::to_bytes,
+	extract::Path,
+	http::header::{COOKIE, SET_COOKIE},
+	routing::get,
+};
+use core::{
+	convert::Infallible,
+	net::{IpAddr, Ipv4Addr, SocketAddr},
+};
+use parking_lot::Mutex;
+use rubedo::sugar::s;
+use serde::Deserialize;
+use tera::{Context as Template, Tera};
+use tower::ServiceExt as _;
+use tower_sessions::MemoryStore;
+
+#[cfg(feature = "oidc")]
+use crate::auth::oidc::OidcCache;
+
+
+
+//		Constants																										
+
+/// An API key belonging to `joe`, with the `admin:write` scope.
+const API_KEY:         &str = "valid-api-key";
+
+/// An API key belonging to `joe`, which has expired.
+const EXPIRED_API_KEY: &str = "expired-api-key";
+
+
+
+//		Structs																											
+
+//		RecordingAuditSink														
+#[derive(Debug, Default)]
+struct RecordingAuditSink {
+	events: Mutex<Vec<AuthEvent>>,
+}
+
+//󰭅		AuditSink																
+impl AuditSink for RecordingAuditSink {
+	type Error = Infallible;
+	
+	//		record																
+	async fn record(&self, entry: &AuditEntry) -> Result<(), Self::Error> {
+		self.events.lock().push(entry.event.clone());
+		Ok(())
+	}
+}
+
+//		TestCredentials															
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct TestCredentials {
+	username: String,
+	password: String,
+}
+
+//󰭅		Credentials																
+impl Credentials for TestCredentials {
+	//		to_loggable_string													
+	fn to_loggable_string(&self) -> String {
+		self.username.clone()
+	}
+}
+
+//		TestState																
+#[derive(Debug, Default)]
+struct TestState {
+	audit:      RecordingAuditSink,
+	config:     Config,
+	lockout:    LockoutTracker,
+	#[cfg(feature = "oidc")]
+	oidc:       OidcCache,
+	remember:   MemoryRememberStore,
+	revocation: MemoryRevocationStore,
+	sessions:   MemoryStore,
+	templates:  HtmlTemplates,
+	tera:       Tera,
+	title:      String,
+	users:      ConfigUserStore,
+}
+
+//󰭅		AppStateProvider														
+impl AppStateProvider for TestState {
+	//		address																
+	fn address(&self) -> Option<SocketAddr> {
+		None
+	}
+	
+	//		html_templates_config												
+	fn html_templates_config(&self) -> &HtmlTemplates {
+		&self.templates
+	}
+	
+	//		host																
+	fn host(&self) -> IpAddr {
+		IpAddr::V4(Ipv4Addr::LOCALHOST)
+	}
+	
+	//		port																
+	fn port(&self) -> u16 {
+		0
+	}
+	
+	//		render																
+	async fn render<T: AsRef<str> + Send>(&self, template: T, _context: &Template) -> Result<String, AppError> {
+		Ok(template.as_ref().to_owned())
+	}
+	
+	//		set_address															
+	fn set_address(&self, _address: Option<SocketAddr>) {}
+	
+	//		tera																
+	fn tera(&self) -> &Tera {
+		&self.tera
+	}
+	
+	//		title																
+	fn title(&self) -> &String {
+		&self.title
+	}
+}
+
+//󰭅		StateProvider															
+impl StateProvider for TestState {
+	type AuditSink       = RecordingAuditSink;
+	type RememberStore   = MemoryRememberStore;
+	type RevocationStore = MemoryRevocationStore;
+	type SessionStore    = MemoryStore;
+	
+	//		audit_sink															
+	fn audit_sink(&self) -> &RecordingAuditSink {
+		&self.audit
+	}
+	
+	//		config																
+	fn config(&self) -> &Config {
+		&self.config
+	}
+	
+	//		lockout																
+	fn lockout(&self) -> &LockoutTracker {
+		&self.lockout
+	}
+	
+	//		oidc_cache															
+	#[cfg(feature = "oidc")]
+	fn oidc_cache(&self) -> &OidcCache {
+		&self.oidc
+	}
+	
+	//		remember_store														
+	fn remember_store(&self) -> &MemoryRememberStore {
+		&self.remember
+	}
+	
+	//		revocation_store													
+	fn revocation_store(&self) -> &MemoryRevocationStore {
+		&self.revocation
+	}
+	
+	//		session_store														
+	fn session_store(&self) -> MemoryStore {
+		self.sessions.clone()
+	}
+	
+	//		users																
+	fn users(&self) -> &ConfigUserStore {
+		&self.users
+	}
+}
+
+//		TestUser																
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct TestUser {
+	id:          String,
+	permissions: Vec<String>,
+}
+
+//󰭅		ApiKeyProvider															
+impl ApiKeyProvider for TestUser {
+	//		find_by_api_key_hash												
+	async fn find_by_api_key_hash<SP: StateProvider>(
+		_state: &SP,
+		hash:   &str,
+	) -> Result<Option<ApiKey<Self>>, Self::Error> {
+		let key = |id: &str, expires_at| ApiKey {
+			id:           s!(id),
+			user:         Self { id: s!("joe"), permissions: vec![] },
+			scopes:       vec![s!("admin:write")],
+			expires_at,
+			last_used_at: None,
+		};
+		Ok(if hash == super::hash_api_key(API_KEY) {
+			Some(key("valid", None))
+		} else if hash == super::hash_api_key(EXPIRED_API_KEY) {
+			Some(key("expired", Some(0)))
+		} else {
+			None
+		})
+	}
+	
+	//		record_api_key_use													
+	async fn record_api_key_use<SP: StateProvider>(
+		_state:   &SP,
+		_key:     &ApiKey<Self>,
+		_used_at: i64,
+	) -> Result<(), Self::Error> {
+		Ok(())
+	}
+}
+
+//󰭅		User																	
+impl User for TestUser {
+	type Id = String;
+	
+	fn id(&self) -> &Self::Id {
+		&self.id
+	}
+	
+	fn permissions(&self) -> &[String] {
+		&self.permissions
+	}
+	
+	fn to_loggable_string(&self) -> String {
+		self.id.clone()
+	}
+}
+
+//󰭅		UserProvider															
+impl UserProvider for TestUser {
+	type Credentials = TestCredentials;
+	type Error       = Infallible;
+	type User        = Self;
+	
+	//		find_by_credentials													
+	async fn find_by_credentials<SP: StateProvider>(
+		_state:       &SP,
+		_credentials: &Self::Credentials,
+	) -> Result<Option<Self>, Self::Error> {
+		Ok(None)
+	}
+	
+	//		find_by_id															
+	async fn find_by_id<SP: StateProvider>(
+		_state: &SP,
+		id:     &String,
+	) -> Result<Option<Self>, Self::Error> {
+		let permissions = if id == "admin" { vec![s!("admin:write")] } else { vec![] };
+		Ok(Some(Self { id: id.clone(), permissions }))
+	}
+}
+
+
+
+//		Functions																										
+
+//		body																	
+async fn body(response: Response) -> String {
+	String::from_utf8(to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()).unwrap()
+}
+
+//		cookie																	
+fn cookie(response: &Response, name: &str) -> Option<String> {
+	response.headers().get_all(SET_COOKIE).iter()
+		.filter_map(|value| value.to_str().ok())
+		.filter_map(|value| value.split(';').next())
+		.find(|pair| pair.split_once('=').is_some_and(|(key, _)| key == name))
+		.map(ToOwned::to_owned)
+}
+
+//		current_user															
+async fn current_user(auth: Context<TestUser>) -> String {
+	auth.current_user.map_or_else(|| s!("anonymous"), |user| user.id)
+}
+
+//		get_request																
+fn get_request(uri: &str) -> Request<Body> {
+	Request::get(uri).body(Body::empty()).unwrap()
+}
+
+//		key																		
+fn key(scopes: &[&str], expires_at: Option<i64>) -> ApiKey<TestUser> {
+	ApiKey {
+		id:           s!("key-1"),
+		user:         TestUser { id: s!("joe"), permissions: vec![] },
+		scopes:       scopes.iter().map(ToString::to_string).collect(),
+		expires_at,
+		last_used_at: None,
+	}
+}
+
+//		key_request																
+fn key_request(key: &str) -> Request<Body> {
+	Request::get("/user").header("X-API-Key", key).body(Body::empty()).unwrap()
+}
+
+//		log_in																	
+async fn log_in(
+	State(state): State<Arc<TestState>>,
+	mut auth:     Context<TestUser>,
+	Path(name):   Path<String>,
+) -> StatusCode {
+	auth.login(&*state, &TestUser { id: name, permissions: vec![] }).await.unwrap();
+	StatusCode::OK
+}
+
+//		router																	
+fn router(config: Config) -> (Arc<TestState>, Router) {
+	let state  = Arc::new(TestState { config, ..Default::default() });
+	let router = Router::new()
+		.route("/user", get(current_user))
+		.add_api_key_authentication::<_, TestUser, TestUser>(&["admin:write"], &state)
+		.route("/login/{name}", get(log_in))
+		.add_authentication::<_, TestUser, TestUser>(&state)
+		.with_state(Arc::clone(&state))
+	;
+	(state, router)
+}
+
+//		send																	
+async fn send(router: &Router, mut request: Request<Body>, cookie: Option<&str>) -> Response {
+	if let Some(value) = cookie {
+		drop(request.headers_mut().insert(COOKIE, value.parse().unwrap()));
+	}
+	router.clone().oneshot(request).await.unwrap()
+}
+
+
+
+//		Tests																											
+
+//		api_key__has_scope														
+#[test]
+fn api_key__has_scope() {
+	let api_key = key(&["read", "write"], None);
+	assert!( api_key.has_scope("read"));
+	assert!( api_key.has_scope("write"));
+	assert!(!api_key.has_scope("admin"));
+}
+
+//		api_key__is_expired_at													
+#[test]
+fn api_key__is_expired_at() {
+	assert!(!key(&[], None).is_expired_at(1_000));
+	assert!(!key(&[], Some(1_000)).is_expired_at(999));
+	assert!( key(&[], Some(1_000)).is_expired_at(1_000));
+	assert!( key(&[], Some(1_000)).is_expired_at(1_001));
+}
+
+//		api_key_layer__audit													
+#[tokio::test]
+async fn api_key_layer__audit() {
+	let (state, router) = router(Config::default());
+	let valid           = send(&router, key_request(API_KEY), None).await;
+	assert_eq!(body(valid).await, "joe");
+	let unknown         = send(&router, key_request("unknown-api-key"), None).await;
+	assert_eq!(unknown.status(), StatusCode::UNAUTHORIZED);
+	let expired         = send(&router, key_request(EXPIRED_API_KEY), None).await;
+	assert_eq!(expired.status(), StatusCode::UNAUTHORIZED);
+	assert_eq!(*state.audit.events.lock(), vec![
+		AuthEvent::Login       { user: s!("joe"),                              method: LoginMethod::ApiKey },
+		AuthEvent::LoginFailed { user: super::hash_api_key("unknown-api-key"), method: LoginMethod::ApiKey },
+		AuthEvent::LoginFailed { user: s!("joe"),                              method: LoginMethod::ApiKey },
+	]);
+}
+
+//		api_key_layer__lockout													
+#[tokio::test]
+async fn api_key_layer__lockout() {
+	let (state, router) = router(Config { lockout: Lockout { user_threshold: 2, ..Default::default() }, ..Default::default() });
+	for _ in 0..2 {
+		let response = send(&router, key_request("guessed-api-key"), None).await;
+		assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+	}
+	//	Once locked out, the key is not looked up
+	let locked          = send(&router, key_request("guessed-api-key"), None).await;
+	assert_eq!(locked.status(), StatusCode::UNAUTHORIZED);
+	assert!(body(locked).await.contains("Too many failed attempts"));
+	let hash            = super::hash_api_key("guessed-api-key");
+	let events          = state.audit.events.lock().clone();
+	assert_eq!(events[..3], [
+		AuthEvent::LoginFailed { user: hash.clone(), method: LoginMethod::ApiKey },
+		AuthEvent::LoginFailed { user: hash.clone(), method: LoginMethod::ApiKey },
+		AuthEvent::LockedOut   { user: hash.clone(), seconds: 30 },
+	]);
+	assert!(matches!(events[3], AuthEvent::LockedOut { ref user, .. } if *user == hash));
+	assert_eq!(events.len(), 4);
+}
+
+//		api_key_layer__session													
+#[tokio::test]
+async fn api_key_layer__session() {
+	let (_, router) = router(Config::default());
+	//	Users logged in by a session need to hold the scopes as permissions
+	let admin       = cookie(&send(&router, get_request("/login/admin"), None).await, "id").unwrap();
+	assert_eq!(body(send(&router, get_request("/user"), Some(&admin)).await).await, "admin");
+	let joe         = cookie(&send(&router, get_request("/login/joe"), None).await, "id").unwrap();
+	assert_eq!(send(&router, get_request("/user"), Some(&joe)).await.status(), StatusCode::FORBIDDEN);
+	//	Anonymous requests are left for the routes to protect
+	assert_eq!(body(send(&router, get_request("/user"), None).await).await, "anonymous");
+}
+
+//		generate_api_key														
+#[test]
+fn generate_api_key() {
+	let (one, hash) = super::generate_api_key();
+	let (two, _)    = super::generate_api_key();
+	assert_eq!(one.len(),                 43);
+	assert_ne!(one,                       two);
+	assert_eq!(super::hash_api_key(&one), hash);
+	assert_ne!(super::hash_api_key(&two), hash);
+}
+
+//		hash_api_key															
+#[test]
+fn hash_api_key() {
+	assert_eq!(super::hash_api_key("abc"), "ungWv48Bz-pBQUDeXa4iI7ADYaOWF3qctBD_YfIAFa0");
+}
+
+//		missing_permission														
+#[test]
+fn missing_permission() {
+	let user = TestUser { id: s!("joe"), permissions: vec![s!("read"), s!("write")] };
+	assert_eq!(super::missing_permission(&user, &[]),                None);
+	assert_eq!(super::missing_permission(&user, &["read", "write"]), None);
+	assert_eq!(super::missing_permission(&user, &["read", "admin"]), Some("admin"));
+}
+
+//		missing_scope															
+#[test]
+fn missing_scope() {
+	let api_key = key(&["read", "write"], None);
+	assert_eq!(super::missing_scope(&api_key, &[]),                None);
+	assert_eq!(super::missing_scope(&api_key, &["read", "write"]), None);
+	assert_eq!(super::missing_scope(&api_key, &["read", "admin"]), Some("admin"));
+}
+
+//		supplied_key__header													
+#[test]
+fn supplied_key__header() {
+	let config      = ApiKeys::default();
+	let uri         = Uri::from_static("/api?api_key=query");
+	let mut headers = HeaderMap::new();
+	assert_eq!(supplied_key(&config, &headers, &uri), None);
+	drop(headers.insert("x-api-key", HeaderValue::from_static(" secret ")));
+	assert_eq!(supplied_key(&config, &headers, &uri), Some(s!("secret")));
+	drop(headers.insert("x-api-key", HeaderValue::from_static("")));
+	assert_eq!(supplied_key(&config, &headers, &uri), None);
+}
+
+//		supplied_key__query														
+#[test]
+fn supplied_key__query() {
+	let config  = ApiKeys { query_param: Some(s!("api_key")), ..Default::default() };
+	let headers = HeaderMap::new();
+	assert_eq!(supplied_key(&config, &headers, &Uri::from_static("/api?a=1&api_key=query%2B1")), Some(s!("query+1")));
+	assert_eq!(supplied_key(&config, &headers, &Uri::from_static("/api?api_key=")),              None);
+	assert_eq!(supplied_key(&config, &headers, &Uri::from_static("/api")),                       None);
+	let mut with_header = HeaderMap::new();
+	drop(with_header.insert("x-api-key", HeaderValue::from_static("header")));
+	assert_eq!(supplied_key(&config, &with_header, &Uri::from_static("/api?api_key=query")),     Some(s!("header")));
+}
+
+
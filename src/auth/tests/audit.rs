@@ -0,0 +1,91 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use axum::http::HeaderValue;
+use core::net::Ipv4Addr;
+use rubedo::sugar::s;
+use serde_json::json;
+use std::env::temp_dir;
+use tokio::fs;
+
+
+
+//		Functions																										
+
+//		entry																	
+fn entry() -> AuditEntry {
+	AuditEntry {
+		at:     1_700_000_000,
+		event:  AuthEvent::Login { user: s!("joe"), method: LoginMethod::Password },
+		client: ClientInfo {
+			ip:         Some(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+			user_agent: Some(s!("curl/8.0")),
+		},
+	}
+}
+
+
+
+//		Tests																											
+
+//		audit_entry__serialize													
+#[test]
+fn audit_entry__serialize() {
+	assert_eq!(serde_json::to_value(entry()).unwrap(), json!({
+		"at":         1_700_000_000,
+		"event":      "login",
+		"user":       "joe",
+		"method":     "password",
+		"ip":         "127.0.0.1",
+		"user_agent": "curl/8.0",
+	}));
+	let lockout = AuditEntry {
+		event: AuthEvent::LockedOut { user: s!("joe"), seconds: 30 },
+		..entry()
+	};
+	assert_eq!(serde_json::to_value(&lockout).unwrap()["event"], "locked_out");
+	assert_eq!(serde_json::from_value::<AuditEntry>(serde_json::to_value(&lockout).unwrap()).unwrap(), lockout);
+}
+
+//		auth_event__display														
+#[test]
+fn auth_event__display() {
+	assert_eq!(AuthEvent::Login { user: s!("joe"), method: LoginMethod::RememberMe }.to_string(),  "Login by user joe using remember-me");
+	assert_eq!(AuthEvent::LoginFailed { user: s!("joe"), method: LoginMethod::Oidc }.to_string(),  "Failed login by user joe using OIDC");
+	assert_eq!(AuthEvent::Login { user: s!("joe"), method: LoginMethod::Basic }.to_string(),       "Login by user joe using Basic");
+	assert_eq!(AuthEvent::LoginFailed { user: s!("joe"), method: LoginMethod::ApiKey }.to_string(), "Failed login by user joe using API key");
+	assert_eq!(AuthEvent::LockedOut { user: s!("joe"), seconds: 30 }.to_string(),                  "Locked out user joe for 30s");
+	assert_eq!(AuthEvent::Logout { user: s!("joe") }.to_string(),                                  "Logout by user joe");
+	assert_eq!(AuthEvent::SessionExpired { user: s!("joe") }.to_string(),                          "Login expired for user joe");
+	assert_eq!(AuthEvent::ImpersonationStarted { user: s!("admin"), target: s!("joe") }.to_string(), "User admin started impersonating user joe");
+}
+
+//		client_info__from_request												
+#[test]
+fn client_info__from_request() {
+	let mut headers    = HeaderMap::new();
+	let mut extensions = Extensions::new();
+	assert_eq!(ClientInfo::from_request(&headers, &extensions), ClientInfo::default());
+	drop(headers.insert(USER_AGENT, HeaderValue::from_static("curl/8.0")));
+	_ = extensions.insert(ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 1234))));
+	assert_eq!(ClientInfo::from_request(&headers, &extensions), entry().client);
+}
+
+//		json_lines_audit_sink__record											
+#[tokio::test]
+async fn json_lines_audit_sink__record() {
+	let path = temp_dir().join(format!("terracotta-audit-{}.jsonl", OffsetDateTime::now_utc().unix_timestamp_nanos()));
+	let sink = JsonLinesAuditSink::open(&path).await.unwrap();
+	sink.record(&entry()).await.unwrap();
+	sink.record(&AuditEntry { event: AuthEvent::Logout { user: s!("joe") }, ..entry() }).await.unwrap();
+	let contents = fs::read_to_string(&path).await.unwrap();
+	fs::remove_file(&path).await.unwrap();
+	let lines    = contents.lines().collect::<Vec<_>>();
+	assert_eq!(lines.len(), 2);
+	assert_eq!(serde_json::from_str::<AuditEntry>(lines[0]).unwrap(), entry());
+	assert_eq!(serde_json::from_str::<serde_json::Value>(lines[1]).unwrap()["event"], "logout");
+}
+
+
@@ -0,0 +1,356 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use crate::{
+	app::{config::HtmlTemplates, errors::AppError, state::StateProvider as AppStateProvider},
+	auth::{
+		audit::{AuditEntry, AuditSink},
+		config::Config,
+		lockout::LockoutTracker,
+		remember::MemoryRememberStore,
+		revocation::MemoryRevocationStore,
+		routing::RouterExt as _,
+		users::ConfigUserStore,
+	},
+};
+use axum::{
+	Router,
+	body::to_bytes,
+	http::HeaderValue,
+	routing::get,
+};
+use core::{
+	convert::Infallible,
+	net::{IpAddr, Ipv4Addr, SocketAddr},
+};
+use parking_lot::Mutex;
+use rubedo::sugar::s;
+use serde::Deserialize;
+use tera::{Context as Template, Tera};
+use tower::ServiceExt as _;
+use tower_sessions::MemoryStore;
+
+#[cfg(feature = "oidc")]
+use crate::auth::oidc::OidcCache;
+
+
+
+//		Structs																											
+
+//		RecordingAuditSink														
+#[derive(Debug, Default)]
+struct RecordingAuditSink {
+	events: Mutex<Vec<AuthEvent>>,
+}
+
+//󰭅		AuditSink																
+impl AuditSink for RecordingAuditSink {
+	type Error = Infallible;
+	
+	//		record																
+	async fn record(&self, entry: &AuditEntry) -> Result<(), Self::Error> {
+		self.events.lock().push(entry.event.clone());
+		Ok(())
+	}
+}
+
+//		TestCredentials															
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct TestCredentials {
+	email:  String,
+	secret: String,
+}
+
+//󰭅		Credentials																
+impl Credentials for TestCredentials {
+	fn to_loggable_string(&self) -> String {
+		self.email.clone()
+	}
+}
+
+//		TestState																
+#[derive(Debug, Default)]
+struct TestState {
+	audit:      RecordingAuditSink,
+	config:     Config,
+	lockout:    LockoutTracker,
+	#[cfg(feature = "oidc")]
+	oidc:       OidcCache,
+	remember:   MemoryRememberStore,
+	revocation: MemoryRevocationStore,
+	sessions:   MemoryStore,
+	templates:  HtmlTemplates,
+	tera:       Tera,
+	title:      String,
+	users:      ConfigUserStore,
+}
+
+//󰭅		AppStateProvider														
+impl AppStateProvider for TestState {
+	//		address																
+	fn address(&self) -> Option<SocketAddr> {
+		None
+	}
+	
+	//		html_templates_config												
+	fn html_templates_config(&self) -> &HtmlTemplates {
+		&self.templates
+	}
+	
+	//		host																
+	fn host(&self) -> IpAddr {
+		IpAddr::V4(Ipv4Addr::LOCALHOST)
+	}
+	
+	//		port																
+	fn port(&self) -> u16 {
+		0
+	}
+	
+	//		render																
+	async fn render<T: AsRef<str> + Send>(&self, template: T, _context: &Template) -> Result<String, AppError> {
+		Ok(template.as_ref().to_owned())
+	}
+	
+	//		set_address															
+	fn set_address(&self, _address: Option<SocketAddr>) {}
+	
+	//		tera																
+	fn tera(&self) -> &Tera {
+		&self.tera
+	}
+	
+	//		title																
+	fn title(&self) -> &String {
+		&self.title
+	}
+}
+
+//󰭅		StateProvider															
+impl StateProvider for TestState {
+	type AuditSink       = RecordingAuditSink;
+	type RememberStore   = MemoryRememberStore;
+	type RevocationStore = MemoryRevocationStore;
+	type SessionStore    = MemoryStore;
+	
+	//		audit_sink															
+	fn audit_sink(&self) -> &RecordingAuditSink {
+		&self.audit
+	}
+	
+	//		config																
+	fn config(&self) -> &Config {
+		&self.config
+	}
+	
+	//		lockout																
+	fn lockout(&self) -> &LockoutTracker {
+		&self.lockout
+	}
+	
+	//		oidc_cache															
+	#[cfg(feature = "oidc")]
+	fn oidc_cache(&self) -> &OidcCache {
+		&self.oidc
+	}
+	
+	//		remember_store														
+	fn remember_store(&self) -> &MemoryRememberStore {
+		&self.remember
+	}
+	
+	//		revocation_store													
+	fn revocation_store(&self) -> &MemoryRevocationStore {
+		&self.revocation
+	}
+	
+	//		session_store														
+	fn session_store(&self) -> MemoryStore {
+		self.sessions.clone()
+	}
+	
+	//		users																
+	fn users(&self) -> &ConfigUserStore {
+		&self.users
+	}
+}
+
+//		TestUser																
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct TestUser {
+	name: String,
+}
+
+//󰭅		User																	
+impl User for TestUser {
+	type Id = String;
+	
+	//		id																	
+	fn id(&self) -> &Self::Id {
+		&self.name
+	}
+	
+	//		requires_two_factor													
+	#[cfg(feature = "totp")]
+	fn requires_two_factor(&self) -> bool {
+		self.name.starts_with("2fa")
+	}
+	
+	//		to_loggable_string													
+	fn to_loggable_string(&self) -> String {
+		self.name.clone()
+	}
+}
+
+//󰭅		UserProvider															
+impl UserProvider for TestUser {
+	type Credentials = TestCredentials;
+	type Error       = Infallible;
+	type User        = Self;
+	
+	//		find_by_credentials													
+	async fn find_by_credentials<SP: StateProvider>(
+		_state:      &SP,
+		credentials: &Self::Credentials,
+	) -> Result<Option<Self>, Self::Error> {
+		Ok((credentials.secret == "secret").then(|| Self { name: credentials.email.clone() }))
+	}
+	
+	//		find_by_id															
+	async fn find_by_id<SP: StateProvider>(
+		_state: &SP,
+		id:     &String,
+	) -> Result<Option<Self>, Self::Error> {
+		Ok(Some(Self { name: id.clone() }))
+	}
+}
+
+
+
+//		Functions																										
+
+//		body																	
+async fn body(response: Response) -> String {
+	String::from_utf8(to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()).unwrap()
+}
+
+//		config																	
+fn config() -> Basic {
+	Basic {
+		realm:          s!("Tools"),
+		username_field: s!("email"),
+		password_field: s!("secret"),
+	}
+}
+
+//		current_user															
+async fn current_user(auth: Context<TestUser>) -> String {
+	auth.current_user.map_or_else(|| s!("anonymous"), |user| user.name)
+}
+
+//		headers																	
+fn headers(value: &'static str) -> HeaderMap {
+	let mut headers = HeaderMap::new();
+	drop(headers.insert(AUTHORIZATION, HeaderValue::from_static(value)));
+	headers
+}
+
+//		router																	
+fn router() -> (Arc<TestState>, Router) {
+	let state  = Arc::new(TestState { config: Config { basic: config(), ..Default::default() }, ..Default::default() });
+	let router = Router::new()
+		.route("/user", get(current_user))
+		.add_basic_authentication::<_, TestUser, TestUser>(&state)
+		.with_state(Arc::clone(&state))
+	;
+	(state, router)
+}
+
+//		user_request															
+fn user_request(authorization: &str) -> Request<Body> {
+	Request::get("/user").header(AUTHORIZATION, authorization).body(Body::empty()).unwrap()
+}
+
+
+
+//		Tests																											
+
+//		basic_auth_layer__audit													
+#[tokio::test]
+async fn basic_auth_layer__audit() {
+	let (state, router) = router();
+	//	joe:secret
+	let valid           = router.clone().oneshot(user_request("Basic am9lOnNlY3JldA==")).await.unwrap();
+	assert_eq!(body(valid).await, "joe");
+	//	joe:wrong
+	let invalid         = router.oneshot(user_request("Basic am9lOndyb25n")).await.unwrap();
+	assert_eq!(body(invalid).await, "anonymous");
+	assert_eq!(*state.audit.events.lock(), vec![
+		AuthEvent::Login       { user: s!("joe"), method: LoginMethod::Basic },
+		AuthEvent::LoginFailed { user: s!("joe"), method: LoginMethod::Basic },
+	]);
+}
+
+//		basic_auth_layer__two_factor											
+#[cfg(feature = "totp")]
+#[tokio::test]
+async fn basic_auth_layer__two_factor() {
+	let (state, router) = router();
+	//	2fa-basic:secret
+	let response        = router.oneshot(user_request("Basic MmZhLWJhc2ljOnNlY3JldA==")).await.unwrap();
+	assert_eq!(body(response).await, "anonymous");
+	assert_eq!(*state.audit.events.lock(), vec![
+		AuthEvent::LoginFailed { user: s!("2fa-basic"), method: LoginMethod::Basic },
+	]);
+}
+
+//		basic_credentials__valid												
+#[test]
+fn basic_credentials__valid() {
+	//	joe:secret
+	assert_eq!(basic_credentials(&headers("Basic am9lOnNlY3JldA==")), Some((s!("joe"), s!("secret"))));
+	assert_eq!(basic_credentials(&headers("basic am9lOnNlY3JldA==")), Some((s!("joe"), s!("secret"))));
+	//	joe:pass:word
+	assert_eq!(basic_credentials(&headers("Basic am9lOnBhc3M6d29yZA==")), Some((s!("joe"), s!("pass:word"))));
+	//	joe:
+	assert_eq!(basic_credentials(&headers("Basic am9lOg==")), Some((s!("joe"), s!(""))));
+}
+
+//		basic_credentials__invalid												
+#[test]
+fn basic_credentials__invalid() {
+	assert_eq!(basic_credentials(&HeaderMap::new()),                  None);
+	assert_eq!(basic_credentials(&headers("Bearer am9lOnNlY3JldA==")), None);
+	assert_eq!(basic_credentials(&headers("Basic")),                   None);
+	assert_eq!(basic_credentials(&headers("Basic not-base64!")),       None);
+	//	joesecret
+	assert_eq!(basic_credentials(&headers("Basic am9lc2VjcmV0")),      None);
+	//	Invalid UTF-8
+	assert_eq!(basic_credentials(&headers("Basic //46eA==")),          None);
+}
+
+//		challenge																
+#[test]
+fn challenge() {
+	assert_eq!(super::challenge(&config()),                                   r#"Basic realm="Tools", charset="UTF-8""#);
+	assert_eq!(super::challenge(&Basic { realm: s!(r#"A "b" \c"#), ..config() }), r#"Basic realm="A \"b\" \\c", charset="UTF-8""#);
+}
+
+//		to_credentials__valid													
+#[test]
+fn to_credentials__valid() {
+	assert_eq!(
+		to_credentials::<TestCredentials>(&config(), "joe@example.com", "secret"),
+		Some(TestCredentials { email: s!("joe@example.com"), secret: s!("secret") }),
+	);
+}
+
+//		to_credentials__wrong_fields											
+#[test]
+fn to_credentials__wrong_fields() {
+	assert_eq!(to_credentials::<TestCredentials>(&Basic::default(), "joe", "secret"), None);
+}
+
+
@@ -0,0 +1,410 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use crate::{
+	app::{config::HtmlTemplates, errors::AppError, state::StateProvider as AppStateProvider},
+	auth::{
+		api_keys::{ApiKey, ApiKeyProvider, hash_api_key},
+		audit::TracingAuditSink,
+		config::Config,
+		lockout::LockoutTracker,
+		middleware::{Credentials, UserProvider},
+		remember::MemoryRememberStore,
+		revocation::MemoryRevocationStore,
+		routing::RouterExt as _,
+		users::ConfigUserStore,
+	},
+};
+use axum::{
+	Router,
+	extract::Path,
+	http::{StatusCode, header::{COOKIE, SET_COOKIE}},
+	routing::{get, post},
+};
+use core::{
+	convert::Infallible,
+	net::{IpAddr, Ipv4Addr, SocketAddr},
+};
+use rubedo::sugar::s;
+use serde::Deserialize;
+use tera::{Context as Template, Tera};
+use tower::ServiceExt as _;
+use tower_sessions::MemoryStore;
+
+#[cfg(feature = "oidc")]
+use crate::auth::oidc::OidcCache;
+
+
+
+//		Constants																										
+
+/// An API key belonging to `joe`, with the `admin:write` scope.
+const API_KEY:  &str = "valid-api-key";
+
+/// The password accepted for every user.
+const PASSWORD: &str = "secret";
+
+
+
+//		Structs																											
+
+//		TestCredentials															
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct TestCredentials {
+	username: String,
+	password: String,
+}
+
+//󰭅		Credentials																
+impl Credentials for TestCredentials {
+	//		to_loggable_string													
+	fn to_loggable_string(&self) -> String {
+		self.username.clone()
+	}
+}
+
+//		TestState																
+#[derive(Debug, Default)]
+struct TestState {
+	audit:      TracingAuditSink,
+	config:     Config,
+	lockout:    LockoutTracker,
+	#[cfg(feature = "oidc")]
+	oidc:       OidcCache,
+	remember:   MemoryRememberStore,
+	revocation: MemoryRevocationStore,
+	sessions:   MemoryStore,
+	templates:  HtmlTemplates,
+	tera:       Tera,
+	title:      String,
+	users:      ConfigUserStore,
+}
+
+//󰭅		AppStateProvider														
+impl AppStateProvider for TestState {
+	//		address																
+	fn address(&self) -> Option<SocketAddr> {
+		None
+	}
+	
+	//		html_templates_config												
+	fn html_templates_config(&self) -> &HtmlTemplates {
+		&self.templates
+	}
+	
+	//		host																
+	fn host(&self) -> IpAddr {
+		IpAddr::V4(Ipv4Addr::LOCALHOST)
+	}
+	
+	//		port																
+	fn port(&self) -> u16 {
+		0
+	}
+	
+	//		render																
+	async fn render<T: AsRef<str> + Send>(&self, template: T, _context: &Template) -> Result<String, AppError> {
+		Ok(template.as_ref().to_owned())
+	}
+	
+	//		set_address															
+	fn set_address(&self, _address: Option<SocketAddr>) {}
+	
+	//		tera																
+	fn tera(&self) -> &Tera {
+		&self.tera
+	}
+	
+	//		title																
+	fn title(&self) -> &String {
+		&self.title
+	}
+}
+
+//󰭅		StateProvider															
+impl StateProvider for TestState {
+	type AuditSink       = TracingAuditSink;
+	type RememberStore   = MemoryRememberStore;
+	type RevocationStore = MemoryRevocationStore;
+	type SessionStore    = MemoryStore;
+	
+	//		audit_sink															
+	fn audit_sink(&self) -> &TracingAuditSink {
+		&self.audit
+	}
+	
+	//		config																
+	fn config(&self) -> &Config {
+		&self.config
+	}
+	
+	//		lockout																
+	fn lockout(&self) -> &LockoutTracker {
+		&self.lockout
+	}
+	
+	//		oidc_cache															
+	#[cfg(feature = "oidc")]
+	fn oidc_cache(&self) -> &OidcCache {
+		&self.oidc
+	}
+	
+	//		remember_store														
+	fn remember_store(&self) -> &MemoryRememberStore {
+		&self.remember
+	}
+	
+	//		revocation_store													
+	fn revocation_store(&self) -> &MemoryRevocationStore {
+		&self.revocation
+	}
+	
+	//		session_store														
+	fn session_store(&self) -> MemoryStore {
+		self.sessions.clone()
+	}
+	
+	//		users																
+	fn users(&self) -> &ConfigUserStore {
+		&self.users
+	}
+}
+
+//		TestUser																
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct TestUser {
+	name: String,
+}
+
+//󰭅		ApiKeyProvider															
+impl ApiKeyProvider for TestUser {
+	//		find_by_api_key_hash												
+	async fn find_by_api_key_hash<SP: StateProvider>(
+		_state: &SP,
+		hash:   &str,
+	) -> Result<Option<ApiKey<Self>>, Self::Error> {
+		Ok((hash == hash_api_key(API_KEY)).then(|| ApiKey {
+			id:           s!("key-1"),
+			user:         Self { name: s!("joe") },
+			scopes:       vec![s!("admin:write")],
+			expires_at:   None,
+			last_used_at: None,
+		}))
+	}
+	
+	//		record_api_key_use													
+	async fn record_api_key_use<SP: StateProvider>(
+		_state:   &SP,
+		_key:     &ApiKey<Self>,
+		_used_at: i64,
+	) -> Result<(), Self::Error> {
+		Ok(())
+	}
+}
+
+//󰭅		User																	
+impl User for TestUser {
+	type Id = String;
+	
+	//		id																	
+	fn id(&self) -> &Self::Id {
+		&self.name
+	}
+	
+	//		to_loggable_string													
+	fn to_loggable_string(&self) -> String {
+		self.name.clone()
+	}
+}
+
+//󰭅		UserProvider															
+impl UserProvider for TestUser {
+	type Credentials = TestCredentials;
+	type Error       = Infallible;
+	type User        = Self;
+	
+	//		find_by_credentials													
+	async fn find_by_credentials<SP: StateProvider>(
+		_state:      &SP,
+		credentials: &Self::Credentials,
+	) -> Result<Option<Self>, Self::Error> {
+		if credentials.password == PASSWORD {
+			Ok(Some(Self { name: credentials.username.clone() }))
+		} else {
+			Ok(None)
+		}
+	}
+	
+	//		find_by_id															
+	async fn find_by_id<SP: StateProvider>(
+		_state: &SP,
+		id:     &String,
+	) -> Result<Option<Self>, Self::Error> {
+		Ok(Some(Self { name: id.clone() }))
+	}
+}
+
+
+
+//		Functions																										
+
+//		body																	
+async fn body(response: Response) -> String {
+	String::from_utf8(to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()).unwrap()
+}
+
+//		cookie																	
+fn cookie(response: &Response, name: &str) -> Option<String> {
+	response.headers().get_all(SET_COOKIE).iter()
+		.filter_map(|value| value.to_str().ok())
+		.filter_map(|value| value.split(';').next())
+		.find(|pair| pair.split_once('=').is_some_and(|(key, _)| key == name))
+		.map(ToOwned::to_owned)
+}
+
+//		csrf_token_for															
+async fn csrf_token_for(auth: Context<TestUser>) -> String {
+	auth.csrf_token().await.unwrap().unwrap()
+}
+
+//		current_user															
+async fn current_user(auth: Context<TestUser>) -> String {
+	auth.current_user.map_or_else(|| s!("anonymous"), |user| user.name)
+}
+
+//		log_in																	
+async fn log_in(
+	State(state): State<Arc<TestState>>,
+	mut auth:     Context<TestUser>,
+	Path(name):   Path<String>,
+) -> StatusCode {
+	auth.login(&*state, &TestUser { name }).await.unwrap();
+	StatusCode::OK
+}
+
+//		post_request															
+fn post_request(uri: &str, headers: &[(&str, &str)]) -> Request<Body> {
+	headers.iter()
+		.fold(Request::post(uri), |builder, &(name, value)| builder.header(name, value))
+		.body(Body::empty())
+		.unwrap()
+}
+
+//		router																	
+/// Creates a router laid out in the same way as
+/// [`app_full()`](crate::app::create::app_full()), with the CSRF protection
+/// outside of the API key and Basic authentication layers.
+fn router() -> Router {
+	let state = Arc::new(TestState::default());
+	let api   = Router::new()
+		.route("/api", post(current_user))
+		.add_api_key_authentication::<_, TestUser, TestUser>(&["admin:write"], &state)
+	;
+	let basic = Router::new()
+		.route("/basic", post(current_user))
+		.add_basic_authentication::<_, TestUser, TestUser>(&state)
+	;
+	Router::new()
+		.route("/action",       post(current_user))
+		.route("/login/{name}", get(log_in))
+		.route("/token",        get(csrf_token_for))
+		.merge(api)
+		.merge(basic)
+		.add_csrf_protection::<_, TestUser>(&state)
+		.add_authentication::<_, TestUser, TestUser>(&state)
+		.with_state(state)
+}
+
+//		send																	
+async fn send(router: &Router, mut request: Request<Body>, cookie: Option<&str>) -> Response {
+	if let Some(value) = cookie {
+		drop(request.headers_mut().insert(COOKIE, value.parse().unwrap()));
+	}
+	router.clone().oneshot(request).await.unwrap()
+}
+
+
+
+//		Tests																											
+
+//		csrf_layer__api_key														
+#[tokio::test]
+async fn csrf_layer__api_key() {
+	let router  = router();
+	let session = cookie(&send(&router, Request::get("/login/admin").body(Body::empty()).unwrap(), None).await, "id").unwrap();
+	let keyed   = send(&router, post_request("/api", &[("X-API-Key", API_KEY)]), None).await;
+	assert_eq!(body(keyed).await, "joe");
+	//	The key is used rather than the session
+	let both    = send(&router, post_request("/api", &[("X-API-Key", API_KEY)]), Some(&session)).await;
+	assert_eq!(body(both).await, "joe");
+}
+
+//		csrf_layer__basic														
+#[tokio::test]
+async fn csrf_layer__basic() {
+	let router   = router();
+	//	joe:secret
+	let response = send(&router, post_request("/basic", &[("Authorization", "Basic am9lOnNlY3JldA==")]), None).await;
+	assert_eq!(response.status(), StatusCode::FORBIDDEN);
+}
+
+//		csrf_layer__bearer														
+#[tokio::test]
+async fn csrf_layer__bearer() {
+	let router   = router();
+	let session  = cookie(&send(&router, Request::get("/login/admin").body(Body::empty()).unwrap(), None).await, "id").unwrap();
+	//	The session cannot be used alongside a bearer token to avoid the check
+	let response = send(&router, post_request("/action", &[("Authorization", "Bearer forged")]), Some(&session)).await;
+	assert_eq!(body(response).await, "anonymous");
+}
+
+//		csrf_layer__session														
+#[tokio::test]
+async fn csrf_layer__session() {
+	let router   = router();
+	let session  = cookie(&send(&router, Request::get("/login/admin").body(Body::empty()).unwrap(), None).await, "id").unwrap();
+	let missing  = send(&router, post_request("/action", &[]), Some(&session)).await;
+	assert_eq!(missing.status(), StatusCode::FORBIDDEN);
+	let token    = body(send(&router, Request::get("/token").body(Body::empty()).unwrap(), Some(&session)).await).await;
+	let supplied = send(&router, post_request("/action", &[("X-CSRF-Token", &token)]), Some(&session)).await;
+	assert_eq!(body(supplied).await, "admin");
+}
+
+//		csrf_token__stable														
+#[tokio::test]
+async fn csrf_token__stable() {
+	let session = Session::new(None, Arc::new(MemoryStore::default()), None);
+	let token   = csrf_token(&session).await.unwrap();
+	assert_eq!(csrf_token(&session).await.unwrap(), token);
+	assert_eq!(session.get::<String>(SESSION_CSRF_TOKEN_KEY).await.unwrap(), Some(token));
+}
+
+//		csrf_token__per_session													
+#[tokio::test]
+async fn csrf_token__per_session() {
+	let store = Arc::new(MemoryStore::default());
+	let one   = Session::new(None, Arc::clone(&store), None);
+	let two   = Session::new(None, store, None);
+	assert_ne!(csrf_token(&one).await.unwrap(), csrf_token(&two).await.unwrap());
+}
+
+//		form_token																
+#[test]
+fn form_token() {
+	assert_eq!(super::form_token(b"username=joe&csrf_token=abc%2Bdef&uri=%2F"), Some(s!("abc+def")));
+	assert_eq!(super::form_token(b"username=joe&uri=%2F"),                       None);
+	assert_eq!(super::form_token(b""),                                           None);
+}
+
+//		is_safe_method															
+#[test]
+fn is_safe_method() {
+	assert!( super::is_safe_method(&Method::GET));
+	assert!( super::is_safe_method(&Method::HEAD));
+	assert!(!super::is_safe_method(&Method::POST));
+	assert!(!super::is_safe_method(&Method::DELETE));
+}
+
+
@@ -0,0 +1,137 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use crate::auth::middleware::{CURRENT_USER_TEMPLATE_VAR, IMPERSONATOR_TEMPLATE_VAR};
+use rubedo::sugar::s;
+use tera::Context as Template;
+use tower_sessions::{MemoryStore, Session};
+
+
+
+//		Structs																											
+
+//		TestUser																
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct TestUser {
+	name:        String,
+	permissions: Vec<String>,
+	roles:       Vec<String>,
+}
+
+//󰭅		User																	
+impl User for TestUser {
+	type Id = String;
+	
+	//		id																	
+	fn id(&self) -> &Self::Id {
+		&self.name
+	}
+	
+	//		permissions															
+	fn permissions(&self) -> &[String] {
+		&self.permissions
+	}
+	
+	//		roles																
+	fn roles(&self) -> &[String] {
+		&self.roles
+	}
+	
+	//		to_loggable_string													
+	fn to_loggable_string(&self) -> String {
+		self.name.clone()
+	}
+}
+
+
+
+//		Functions																										
+
+//		admin																	
+fn admin() -> TestUser {
+	TestUser { name: s!("admin"), permissions: vec![s!("impersonate")], roles: vec![] }
+}
+
+//		context																	
+fn context(user: Option<TestUser>) -> Context<TestUser> {
+	let mut auth_cx      = Context::new(Session::new(None, Arc::new(MemoryStore::default()), None));
+	auth_cx.current_user = user;
+	auth_cx
+}
+
+//		joe																		
+fn joe() -> TestUser {
+	TestUser { name: s!("joe"), permissions: vec![], roles: vec![] }
+}
+
+
+
+//		Tests																											
+
+//		can_impersonate															
+#[test]
+fn can_impersonate() {
+	let config = Impersonation::default();
+	assert!( super::can_impersonate(&config, &admin(), &joe()));
+	assert!(!super::can_impersonate(&config, &joe(),   &admin()));
+	assert!(!super::can_impersonate(&config, &joe(),   &joe()));
+	assert!(!super::can_impersonate(&config, &admin(), &admin()));
+	let other_admin = TestUser { name: s!("other"), ..admin() };
+	assert!(!super::can_impersonate(&config, &admin(), &other_admin));
+	assert!( super::can_impersonate(&Impersonation { permission: s!("support") }, &TestUser { permissions: vec![s!("support"), s!("impersonate")], ..joe() }, &admin()));
+	//	Targets with privileges that the user does not hold cannot be impersonated
+	let billing = TestUser { permissions: vec![s!("billing:write")], ..joe() };
+	let owner   = TestUser { roles: vec![s!("superadmin")], ..joe() };
+	assert!(!super::can_impersonate(&config, &admin(), &billing));
+	assert!(!super::can_impersonate(&config, &admin(), &owner));
+	let senior  = TestUser { permissions: vec![s!("impersonate"), s!("billing:write")], roles: vec![s!("superadmin")], ..admin() };
+	assert!( super::can_impersonate(&config, &senior, &billing));
+	assert!( super::can_impersonate(&config, &senior, &owner));
+}
+
+//		context__add_template_vars												
+#[tokio::test]
+async fn context__add_template_vars() {
+	let mut auth_cx  = context(Some(admin()));
+	let mut template = Template::new();
+	auth_cx.add_template_vars(&mut template).await.unwrap();
+	assert_eq!(template.get(CURRENT_USER_TEMPLATE_VAR).unwrap()["name"], "admin");
+	assert!(template.get(IMPERSONATOR_TEMPLATE_VAR).unwrap().is_null());
+	assert!(auth_cx.impersonate(joe()).await.unwrap());
+	auth_cx.add_template_vars(&mut template).await.unwrap();
+	assert_eq!(template.get(CURRENT_USER_TEMPLATE_VAR).unwrap()["name"],           "joe");
+	assert_eq!(template.get(IMPERSONATOR_TEMPLATE_VAR).unwrap()["name"],           "admin");
+	assert_eq!(template.get(IMPERSONATOR_TEMPLATE_VAR).unwrap()["permissions"][0], "impersonate");
+}
+
+//		context__impersonate													
+#[tokio::test]
+async fn context__impersonate() {
+	let mut auth_cx = context(Some(admin()));
+	assert!(auth_cx.impersonate(joe()).await.unwrap());
+	assert_eq!(auth_cx.current_user,   Some(joe()));
+	assert_eq!(auth_cx.impersonator(), Some(&admin()));
+	//	Nested impersonation is not possible
+	assert!(!auth_cx.impersonate(admin()).await.unwrap());
+	assert_eq!(auth_cx.current_user,   Some(joe()));
+	assert_eq!(auth_cx.stop_impersonating().await.unwrap(), Some(joe()));
+	assert_eq!(auth_cx.current_user,   Some(admin()));
+	assert_eq!(auth_cx.impersonator(), None);
+	assert_eq!(auth_cx.stop_impersonating().await.unwrap(), None);
+}
+
+//		context__impersonate__no_user											
+#[tokio::test]
+async fn context__impersonate__no_user() {
+	let mut auth_cx = context(None);
+	assert!(!auth_cx.impersonate(joe()).await.unwrap());
+	assert_eq!(auth_cx.current_user, None);
+	assert!(matches!(
+		Context::<TestUser>::without_session(Some(admin())).impersonate(joe()).await,
+		Err(AuthError::NoSession),
+	));
+}
+
+
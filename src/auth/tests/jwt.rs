@@ -0,0 +1,175 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use axum::http::HeaderValue;
+use jsonwebtoken::{EncodingKey, Header, encode, get_current_timestamp};
+use rubedo::sugar::s;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+
+
+//		Structs																											
+
+//		Claims																	
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct Claims {
+	sub: String,
+	exp: u64,
+	nbf: u64,
+	iss: String,
+	aud: String,
+}
+
+
+
+//		Functions																										
+
+//		config																	
+fn config() -> Jwt {
+	Jwt {
+		key:      s!("secret"),
+		issuer:   Some(s!("terracotta")),
+		audience: vec![s!("api")],
+		leeway:   0,
+		..Default::default()
+	}
+}
+
+//		claims																	
+fn claims() -> Claims {
+	let now = get_current_timestamp();
+	Claims {
+		sub: s!("joe"),
+		exp: now + 60,
+		nbf: now - 60,
+		iss: s!("terracotta"),
+		aud: s!("api"),
+	}
+}
+
+//		token																	
+fn token(claims: &Claims, secret: &str) -> String {
+	encode(&Header::default(), claims, &EncodingKey::from_secret(secret.as_bytes())).unwrap()
+}
+
+//		verifier																
+fn verifier() -> TokenVerifier {
+	TokenVerifier::new(&config()).unwrap()
+}
+
+
+
+//		Tests																											
+
+//		bearer_token__valid														
+#[test]
+fn bearer_token__valid() {
+	let mut headers = HeaderMap::new();
+	drop(headers.insert(AUTHORIZATION, HeaderValue::from_static("bearer abc.def.ghi")));
+	assert_eq!(bearer_token(&headers), Some("abc.def.ghi"));
+}
+
+//		bearer_token__invalid													
+#[test]
+fn bearer_token__invalid() {
+	let mut headers = HeaderMap::new();
+	assert_eq!(bearer_token(&headers), None);
+	drop(headers.insert(AUTHORIZATION, HeaderValue::from_static("Basic am9lOnNlY3JldA==")));
+	assert_eq!(bearer_token(&headers), None);
+	drop(headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer ")));
+	assert_eq!(bearer_token(&headers), None);
+}
+
+//		decode__valid															
+#[test]
+fn decode__valid() {
+	let claims = claims();
+	let token  = token(&claims, "secret");
+	assert_eq!(verifier().decode::<Claims>(&token), Some(claims));
+}
+
+//		decode__wrong_key														
+#[test]
+fn decode__wrong_key() {
+	let token = token(&claims(), "wrong");
+	assert_eq!(verifier().decode::<Claims>(&token), None);
+}
+
+//		decode__expired															
+#[test]
+fn decode__expired() {
+	let mut claims = claims();
+	claims.exp     = get_current_timestamp() - 10;
+	let token      = token(&claims, "secret");
+	assert_eq!(verifier().decode::<Claims>(&token), None);
+}
+
+//		decode__not_yet_valid													
+#[test]
+fn decode__not_yet_valid() {
+	let mut claims = claims();
+	claims.nbf     = get_current_timestamp() + 30;
+	let token      = token(&claims, "secret");
+	assert_eq!(verifier().decode::<Claims>(&token), None);
+}
+
+//		decode__wrong_audience													
+#[test]
+fn decode__wrong_audience() {
+	let mut claims = claims();
+	claims.aud     = s!("other");
+	let token      = token(&claims, "secret");
+	assert_eq!(verifier().decode::<Claims>(&token), None);
+}
+
+//		decode__wrong_issuer													
+#[test]
+fn decode__wrong_issuer() {
+	let mut claims = claims();
+	claims.iss     = s!("other");
+	let token      = token(&claims, "secret");
+	assert_eq!(verifier().decode::<Claims>(&token), None);
+}
+
+//		deserialize__invalid_key												
+#[test]
+fn deserialize__invalid_key() {
+	let config = json!({
+		"algorithm": "RS256",
+		"key":       "-----BEGIN PUBLIC KEY-----\nnot a key\n-----END PUBLIC KEY-----",
+		"issuer":    null,
+		"audience":  [],
+		"leeway":    60,
+	});
+	assert!(serde_json::from_value::<Jwt>(config).is_err());
+}
+
+//		deserialize__missing_key												
+#[test]
+fn deserialize__missing_key() {
+	let config = serde_json::to_value(Jwt::default()).unwrap();
+	assert_eq!(serde_json::from_value::<Jwt>(config).unwrap(), Jwt::default());
+}
+
+//		new__invalid_key														
+#[test]
+fn new__invalid_key() {
+	let config = Jwt {
+		algorithm: JwtAlgorithm::Rs256,
+		key:       s!("-----BEGIN PUBLIC KEY-----\nnot a key\n-----END PUBLIC KEY-----"),
+		..config()
+	};
+	assert!(TokenVerifier::new(&config).is_err());
+}
+
+//		new__missing_key														
+#[test]
+fn new__missing_key() {
+	let config = Jwt { key: s!(""), ..config() };
+	assert!(TokenVerifier::new(&config).is_err());
+}
+
+
@@ -0,0 +1,141 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use core::net::Ipv4Addr;
+use rubedo::sugar::s;
+use std::sync::Mutex as StdMutex;
+
+
+
+//		Constants																										
+
+/// The IP address used for login attempts.
+const IP: Option<IpAddr> = Some(IpAddr::V4(Ipv4Addr::LOCALHOST));
+
+
+
+//		Functions																										
+
+//		config																	
+fn config() -> LockoutConfig {
+	LockoutConfig {
+		enabled:        true,
+		user_threshold: 3,
+		ip_threshold:   5,
+		window:         900,
+		duration:       10,
+		max_duration:   35,
+	}
+}
+
+
+
+//		Tests																											
+
+//		check__unknown															
+#[test]
+fn check__unknown() {
+	let tracker = LockoutTracker::new();
+	assert_eq!(tracker.check(&config(), "joe", IP), None);
+}
+
+//		record_failure__user_threshold											
+#[test]
+fn record_failure__user_threshold() {
+	let tracker = LockoutTracker::new();
+	let config  = config();
+	assert_eq!(tracker.record_failure(&config, "joe", None), None);
+	assert_eq!(tracker.record_failure(&config, "joe", None), None);
+	assert_eq!(tracker.check(&config, "joe", None),          None);
+	assert_eq!(tracker.record_failure(&config, "joe", None), Some(Duration::from_secs(10)));
+	assert!(tracker.check(&config, "joe", None).is_some());
+	assert_eq!(tracker.check(&config, "bob", None),          None);
+}
+
+//		record_failure__ip_threshold											
+#[test]
+fn record_failure__ip_threshold() {
+	let tracker = LockoutTracker::new();
+	let config  = config();
+	for username in ["a", "b", "c", "d"] {
+		assert_eq!(tracker.record_failure(&config, username, IP), None);
+	}
+	assert_eq!(tracker.record_failure(&config, "e", IP), Some(Duration::from_secs(10)));
+	assert!(tracker.check(&config, "f", IP).is_some());
+	assert_eq!(tracker.check(&config, "f", None), None);
+}
+
+//		record_failure__backoff													
+#[test]
+fn record_failure__backoff() {
+	let tracker = LockoutTracker::new();
+	let config  = config();
+	for _ in 0..2 {
+		_ = tracker.record_failure(&config, "joe", None);
+	}
+	assert_eq!(tracker.record_failure(&config, "joe", None), Some(Duration::from_secs(10)));
+	assert_eq!(tracker.record_failure(&config, "joe", None), Some(Duration::from_secs(20)));
+	assert_eq!(tracker.record_failure(&config, "joe", None), Some(Duration::from_secs(35)));
+	assert_eq!(tracker.record_failure(&config, "joe", None), Some(Duration::from_secs(35)));
+}
+
+//		record_failure__disabled												
+#[test]
+fn record_failure__disabled() {
+	let tracker = LockoutTracker::new();
+	let config  = LockoutConfig { enabled: false, ..config() };
+	for _ in 0..10 {
+		assert_eq!(tracker.record_failure(&config, "joe", IP), None);
+	}
+	assert_eq!(tracker.check(&config, "joe", IP), None);
+}
+
+//		record_failure__hook													
+#[test]
+fn record_failure__hook() {
+	let events  = Arc::new(StdMutex::new(vec![]));
+	let tracker = LockoutTracker::new().with_hook({
+		let recorded = Arc::clone(&events);
+		move |event: &LockoutEvent| recorded.lock().unwrap().push(event.clone())
+	});
+	let config  = LockoutConfig { ip_threshold: 3, ..config() };
+	for _ in 0..3 {
+		_ = tracker.record_failure(&config, "joe", IP);
+	}
+	assert_eq!(*events.lock().unwrap(), vec![
+		LockoutEvent { key: LockoutKey::Username(s!("joe")),                 failures: 3, duration: Duration::from_secs(10) },
+		LockoutEvent { key: LockoutKey::Ip(IpAddr::V4(Ipv4Addr::LOCALHOST)), failures: 3, duration: Duration::from_secs(10) },
+	]);
+}
+
+//		record_success															
+#[test]
+fn record_success() {
+	let tracker = LockoutTracker::new();
+	let config  = config();
+	for _ in 0..3 {
+		_ = tracker.record_failure(&config, "joe", IP);
+	}
+	assert!(tracker.check(&config, "joe", IP).is_some());
+	tracker.record_success("joe");
+	assert_eq!(tracker.check(&config, "joe", IP), None);
+	assert_eq!(tracker.record_failure(&config, "joe", IP), None);
+}
+
+//		record_success__keeps_ip_failures										
+#[test]
+fn record_success__keeps_ip_failures() {
+	let tracker = LockoutTracker::new();
+	let config  = config();
+	for username in ["a", "b", "c", "d"] {
+		assert_eq!(tracker.record_failure(&config, username, IP), None);
+	}
+	//	Logging in to another account must not reset the count for the IP
+	tracker.record_success("joe");
+	assert_eq!(tracker.record_failure(&config, "e", IP), Some(Duration::from_secs(10)));
+	assert!(tracker.check(&config, "joe", IP).is_some());
+}
+
+
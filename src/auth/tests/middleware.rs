@@ -0,0 +1,368 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use crate::{
+	app::{config::HtmlTemplates, errors::AppError, state::StateProvider as AppStateProvider},
+	auth::{
+		audit::TracingAuditSink,
+		config::Config,
+		lockout::LockoutTracker,
+		remember::MemoryRememberStore,
+		revocation::{MemoryRevocationStore, RevocationStore as _},
+		routing::RouterExt as _,
+		users::ConfigUserStore,
+	},
+};
+use axum::{
+	Router,
+	body::to_bytes,
+	extract::Path,
+	http::header::COOKIE,
+	routing::get,
+};
+use core::net::{IpAddr, Ipv4Addr, SocketAddr};
+use tera::Tera;
+use thiserror::Error as ThisError;
+use tower::ServiceExt as _;
+use tower_sessions::MemoryStore;
+
+#[cfg(feature = "oidc")]
+use crate::auth::oidc::OidcCache;
+
+
+
+//		Structs																											
+
+//		TestCredentials															
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct TestCredentials {
+	username: String,
+	password: String,
+}
+
+//󰭅		Credentials																
+impl Credentials for TestCredentials {
+	//		to_loggable_string													
+	fn to_loggable_string(&self) -> String {
+		self.username.clone()
+	}
+}
+
+//		TestError																
+#[derive(Clone, Copy, Debug, ThisError)]
+#[error("The user lookup failed")]
+struct TestError;
+
+//		TestState																
+#[derive(Debug, Default)]
+struct TestState {
+	audit:      TracingAuditSink,
+	config:     Config,
+	lockout:    LockoutTracker,
+	#[cfg(feature = "oidc")]
+	oidc:       OidcCache,
+	remember:   MemoryRememberStore,
+	revocation: MemoryRevocationStore,
+	sessions:   MemoryStore,
+	templates:  HtmlTemplates,
+	tera:       Tera,
+	title:      String,
+	users:      ConfigUserStore,
+}
+
+//󰭅		AppStateProvider														
+impl AppStateProvider for TestState {
+	//		address																
+	fn address(&self) -> Option<SocketAddr> {
+		None
+	}
+	
+	//		html_templates_config												
+	fn html_templates_config(&self) -> &HtmlTemplates {
+		&self.templates
+	}
+	
+	//		host																
+	fn host(&self) -> IpAddr {
+		IpAddr::V4(Ipv4Addr::LOCALHOST)
+	}
+	
+	//		port																
+	fn port(&self) -> u16 {
+		0
+	}
+	
+	//		render																
+	async fn render<T: AsRef<str> + Send>(&self, template: T, _context: &Template) -> Result<String, AppError> {
+		Ok(template.as_ref().to_owned())
+	}
+	
+	//		set_address															
+	fn set_address(&self, _address: Option<SocketAddr>) {}
+	
+	//		tera																
+	fn tera(&self) -> &Tera {
+		&self.tera
+	}
+	
+	//		title																
+	fn title(&self) -> &String {
+		&self.title
+	}
+}
+
+//󰭅		StateProvider															
+impl StateProvider for TestState {
+	type AuditSink       = TracingAuditSink;
+	type RememberStore   = MemoryRememberStore;
+	type RevocationStore = MemoryRevocationStore;
+	type SessionStore    = MemoryStore;
+	
+	//		audit_sink															
+	fn audit_sink(&self) -> &TracingAuditSink {
+		&self.audit
+	}
+	
+	//		config																
+	fn config(&self) -> &Config {
+		&self.config
+	}
+	
+	//		lockout																
+	fn lockout(&self) -> &LockoutTracker {
+		&self.lockout
+	}
+	
+	//		oidc_cache															
+	#[cfg(feature = "oidc")]
+	fn oidc_cache(&self) -> &OidcCache {
+		&self.oidc
+	}
+	
+	//		remember_store														
+	fn remember_store(&self) -> &MemoryRememberStore {
+		&self.remember
+	}
+	
+	//		revocation_store													
+	fn revocation_store(&self) -> &MemoryRevocationStore {
+		&self.revocation
+	}
+	
+	//		session_store														
+	fn session_store(&self) -> MemoryStore {
+		self.sessions.clone()
+	}
+	
+	//		users																
+	fn users(&self) -> &ConfigUserStore {
+		&self.users
+	}
+}
+
+//		TestUser																
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct TestUser {
+	name: String,
+}
+
+//󰭅		User																	
+impl User for TestUser {
+	type Id = String;
+	
+	//		id																	
+	fn id(&self) -> &Self::Id {
+		&self.name
+	}
+	
+	//		to_loggable_string													
+	fn to_loggable_string(&self) -> String {
+		self.name.clone()
+	}
+}
+
+//󰭅		UserProvider															
+impl UserProvider for TestUser {
+	type Credentials = TestCredentials;
+	type Error       = TestError;
+	type User        = Self;
+	
+	//		find_by_credentials													
+	async fn find_by_credentials<SP: StateProvider>(
+		_state:       &SP,
+		_credentials: &Self::Credentials,
+	) -> Result<Option<Self>, Self::Error> {
+		Ok(None)
+	}
+	
+	//		find_by_id															
+	async fn find_by_id<SP: StateProvider>(
+		_state: &SP,
+		id:     &String,
+	) -> Result<Option<Self>, Self::Error> {
+		if id == "broken" {
+			Err(TestError)
+		} else {
+			Ok(Some(Self { name: id.clone() }))
+		}
+	}
+}
+
+
+
+//		Functions																										
+
+//		body																	
+async fn body(response: Response) -> String {
+	String::from_utf8(to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()).unwrap()
+}
+
+//		context																	
+fn context(user: Option<TestUser>) -> Context<TestUser> {
+	let mut auth_cx      = Context::new(Session::new(None, Arc::new(MemoryStore::default()), None));
+	auth_cx.current_user = user;
+	auth_cx
+}
+
+//		cookie																	
+fn cookie(response: &Response, name: &str) -> Option<String> {
+	response.headers().get_all(SET_COOKIE).iter()
+		.filter_map(|value| value.to_str().ok())
+		.filter_map(|value| value.split(';').next())
+		.find(|pair| pair.split_once('=').is_some_and(|(key, _)| key == name))
+		.map(ToOwned::to_owned)
+}
+
+//		current_user															
+async fn current_user(auth: Context<TestUser>) -> String {
+	auth.current_user.map_or_else(|| s!("anonymous"), |user| user.name)
+}
+
+//		get_request																
+fn get_request(uri: &str) -> Request<Body> {
+	Request::get(uri).body(Body::empty()).unwrap()
+}
+
+//		log_in																	
+async fn log_in(
+	State(state): State<Arc<TestState>>,
+	mut auth:     Context<TestUser>,
+	Path(name):   Path<String>,
+) -> StatusCode {
+	auth.login(&*state, &TestUser { name }).await.unwrap();
+	StatusCode::OK
+}
+
+//		router																	
+fn router() -> (Arc<TestState>, Router) {
+	let state  = Arc::new(TestState::default());
+	let router = Router::new()
+		.route("/login/{name}", get(log_in))
+		.route("/user",         get(current_user))
+		.add_authentication::<_, TestUser, TestUser>(&state)
+		.with_state(Arc::clone(&state))
+	;
+	(state, router)
+}
+
+//		send																	
+async fn send(router: &Router, mut request: Request<Body>, cookie: Option<&str>) -> Response {
+	if let Some(value) = cookie {
+		drop(request.headers_mut().insert(COOKIE, value.parse().unwrap()));
+	}
+	router.clone().oneshot(request).await.unwrap()
+}
+
+
+
+//		Tests																											
+
+//		auth_layer__user_provider_error											
+#[tokio::test]
+async fn auth_layer__user_provider_error() {
+	let (_, router) = router();
+	let login       = send(&router, get_request("/login/broken"), None).await;
+	let session     = cookie(&login, "id").unwrap();
+	//	The failed lookup is reported, rather than being treated as a missing user
+	let response    = send(&router, get_request("/user"), Some(&session)).await;
+	assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+	assert_eq!(body(response).await, "User provider error: The user lookup failed");
+}
+
+//		context__add_template_vars												
+#[tokio::test]
+async fn context__add_template_vars() {
+	let auth_cx      = context(Some(TestUser { name: s!("joe") }));
+	let mut template = Template::new();
+	auth_cx.add_template_vars(&mut template).await.unwrap();
+	assert_eq!(template.get(CURRENT_USER_TEMPLATE_VAR).unwrap()["name"], "joe");
+	assert_eq!(template.get(LOGGED_IN_TEMPLATE_VAR).unwrap(),            true);
+	assert_eq!(template.get(CSRF_TEMPLATE_VAR).unwrap().as_str(),         auth_cx.csrf_token().await.unwrap().as_deref());
+	assert!(template.get(IMPERSONATOR_TEMPLATE_VAR).unwrap().is_null());
+	assert!(template.get(FLASH_TEMPLATE_VAR).unwrap().as_array().unwrap().is_empty());
+}
+
+//		context__add_template_vars__logged_out									
+#[tokio::test]
+async fn context__add_template_vars__logged_out() {
+	let mut template = Template::new();
+	context(None).add_template_vars(&mut template).await.unwrap();
+	assert!(template.get(CURRENT_USER_TEMPLATE_VAR).unwrap().is_null());
+	assert_eq!(template.get(LOGGED_IN_TEMPLATE_VAR).unwrap(), false);
+	assert!(template.get(CSRF_TEMPLATE_VAR).is_some());
+}
+
+//		context__add_template_vars__no_session									
+#[tokio::test]
+async fn context__add_template_vars__no_session() {
+	let auth_cx      = Context::without_session(Some(TestUser { name: s!("joe") }));
+	let mut template = Template::new();
+	auth_cx.flash("Discarded").await.unwrap();
+	auth_cx.add_template_vars(&mut template).await.unwrap();
+	assert_eq!(template.get(LOGGED_IN_TEMPLATE_VAR).unwrap(), true);
+	assert!(template.get(CSRF_TEMPLATE_VAR).is_none());
+	assert!(template.get(FLASH_TEMPLATE_VAR).unwrap().as_array().unwrap().is_empty());
+}
+
+//		context__flash															
+#[tokio::test]
+async fn context__flash() {
+	let auth_cx = context(None);
+	auth_cx.flash("Saved").await.unwrap();
+	auth_cx.flash(s!("Sent")).await.unwrap();
+	let mut template = Template::new();
+	auth_cx.add_template_vars(&mut template).await.unwrap();
+	assert_eq!(template.get(FLASH_TEMPLATE_VAR).unwrap(), &serde_json::json!(["Saved", "Sent"]));
+	//	Messages are only shown once
+	let mut next = Template::new();
+	auth_cx.add_template_vars(&mut next).await.unwrap();
+	assert!(next.get(FLASH_TEMPLATE_VAR).unwrap().as_array().unwrap().is_empty());
+}
+
+//		context__get_user__revoked												
+#[tokio::test]
+async fn context__get_user__revoked() {
+	let (state, router) = router();
+	let session         = cookie(&send(&router, get_request("/login/joe"), None).await, "id").unwrap();
+	assert_eq!(body(send(&router, get_request("/user"), Some(&session)).await).await, "joe");
+	//	The user's sessions are revoked directly in the store, as another
+	//	instance of the application sharing it would do
+	_ = state.revocation.revoke("\"joe\"").await.unwrap();
+	assert_eq!(body(send(&router, get_request("/user"), Some(&session)).await).await, "anonymous");
+}
+
+//		context__login__cycles_id												
+#[tokio::test]
+async fn context__login__cycles_id() {
+	let (_, router) = router();
+	let first       = cookie(&send(&router, get_request("/login/bob"), None).await, "id").unwrap();
+	let second      = cookie(&send(&router, get_request("/login/joe"), Some(&first)).await, "id").unwrap();
+	assert_ne!(first, second);
+	//	The old ID no longer identifies a session
+	assert_eq!(body(send(&router, get_request("/user"), Some(&first)).await).await,  "anonymous");
+	assert_eq!(body(send(&router, get_request("/user"), Some(&second)).await).await, "joe");
+}
+
+
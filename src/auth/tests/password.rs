@@ -0,0 +1,58 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use bcrypt::hash as bcrypt_hash;
+
+
+
+//		Tests																											
+
+//		hash_password__argon2id													
+#[test]
+fn hash_password__argon2id() {
+	let hash = hash_password("secret").unwrap();
+	assert!(hash.as_str().starts_with("$argon2id$"));
+	assert!(hash.verify("secret"));
+	assert!(!hash.verify("wrong"));
+}
+
+//		hash_password__random_salt												
+#[test]
+fn hash_password__random_salt() {
+	let hash1 = hash_password("secret").unwrap();
+	let hash2 = hash_password("secret").unwrap();
+	assert_ne!(hash1, hash2);
+}
+
+//		hashed_password__new__bcrypt											
+#[test]
+fn hashed_password__new__bcrypt() {
+	let hash = HashedPassword::new(bcrypt_hash("secret", 4).unwrap()).unwrap();
+	assert!(hash.verify("secret"));
+	assert!(!hash.verify("wrong"));
+}
+
+//		hashed_password__new__invalid											
+#[test]
+fn hashed_password__new__invalid() {
+	let err = HashedPassword::new("secret").unwrap_err();
+	assert!(matches!(err, PasswordError::InvalidHash(_)));
+}
+
+//		hashed_password__new__unsupported										
+#[test]
+fn hashed_password__new__unsupported() {
+	let err = HashedPassword::new("$pbkdf2-sha256$i=1000$c2FsdHNhbHQ$aGFzaGhhc2hoYXNoaGFzaA").unwrap_err();
+	assert_eq!(err, PasswordError::UnsupportedAlgorithm(s!("pbkdf2-sha256")));
+}
+
+//		verify_password__invalid_hash											
+#[test]
+fn verify_password__invalid_hash() {
+	assert!(verify_password("secret", "$argon2id$nonsense").is_err());
+	assert!(verify_password("secret", "$2b$nonsense").is_err());
+}
+
+
@@ -0,0 +1,139 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use axum::{
+	body::Body,
+	http::{HeaderValue, Request, header::{CONTENT_TYPE, WWW_AUTHENTICATE}},
+};
+use rubedo::sugar::s;
+use serde::{Deserialize, Serialize};
+
+
+
+//		Structs																											
+
+//		TestUser																
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct TestUser {
+	name:        String,
+	roles:       Vec<String>,
+	permissions: Vec<String>,
+}
+
+//󰭅		User																	
+impl User for TestUser {
+	type Id = String;
+	
+	//		id																	
+	fn id(&self) -> &Self::Id {
+		&self.name
+	}
+	
+	//		permissions															
+	fn permissions(&self) -> &[String] {
+		&self.permissions
+	}
+	
+	//		roles																
+	fn roles(&self) -> &[String] {
+		&self.roles
+	}
+	
+	//		to_loggable_string													
+	fn to_loggable_string(&self) -> String {
+		self.name.clone()
+	}
+}
+
+//		EditArticles															
+#[derive(Debug)]
+struct EditArticles;
+
+//󰭅		Permission																
+impl Permission for EditArticles {
+	const NAME: &'static str = "articles:edit";
+}
+
+
+
+//		Functions																										
+
+//		user																	
+fn user() -> TestUser {
+	TestUser {
+		name:        s!("joe"),
+		roles:       vec![s!("editor")],
+		permissions: vec![s!("articles:read"), s!("articles:edit")],
+	}
+}
+
+//		parts																	
+fn parts(context: Option<Context<TestUser>>) -> Parts {
+	let (mut parts, _) = Request::builder().body(Body::empty()).unwrap().into_parts();
+	if let Some(auth_cx) = context {
+		drop(parts.extensions.insert(auth_cx));
+	}
+	parts
+}
+
+
+
+//		Tests																											
+
+//		has_permission															
+#[test]
+fn has_permission() {
+	let user = user();
+	assert!( user.has_permission("articles:edit"));
+	assert!(!user.has_permission("articles:delete"));
+	assert!(!user.has_permission("editor"));
+}
+
+//		has_role																
+#[test]
+fn has_role() {
+	let user = user();
+	assert!( user.has_role("editor"));
+	assert!(!user.has_role("admin"));
+	assert!(!user.has_role("articles:edit"));
+}
+
+//		permitted__granted														
+#[tokio::test]
+async fn permitted__granted() {
+	let mut parts = parts(Some(Context::without_session(Some(user()))));
+	let permitted = Permitted::<TestUser, EditArticles>::from_request_parts(&mut parts, &()).await.unwrap();
+	assert_eq!(permitted.user, user());
+}
+
+//		permitted__denied														
+#[tokio::test]
+async fn permitted__denied() {
+	let mut user  = user();
+	user.permissions.clear();
+	let mut parts = parts(Some(Context::without_session(Some(user))));
+	let response  = Permitted::<TestUser, EditArticles>::from_request_parts(&mut parts, &()).await.unwrap_err();
+	assert_eq!(response.status(),                StatusCode::FORBIDDEN);
+	assert_eq!(response.headers()[CONTENT_TYPE], HeaderValue::from_static("application/problem+json"));
+}
+
+//		permitted__no_user														
+#[tokio::test]
+async fn permitted__no_user() {
+	let mut parts = parts(Some(Context::without_session(None)));
+	let response  = Permitted::<TestUser, EditArticles>::from_request_parts(&mut parts, &()).await.unwrap_err();
+	assert_eq!(response.status(),                    StatusCode::UNAUTHORIZED);
+	assert_eq!(response.headers()[WWW_AUTHENTICATE], HeaderValue::from_static("Bearer"));
+}
+
+//		permitted__no_context													
+#[tokio::test]
+async fn permitted__no_context() {
+	let mut parts = parts(None);
+	let response  = Permitted::<TestUser, EditArticles>::from_request_parts(&mut parts, &()).await.unwrap_err();
+	assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
+
+
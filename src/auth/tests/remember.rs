@@ -0,0 +1,118 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use axum::http::HeaderValue;
+use rubedo::sugar::s;
+
+
+
+//		Constants																										
+
+/// The time used as "now" when verifying tokens.
+const NOW: i64 = 1_000_000;
+
+
+
+//		Functions																										
+
+//		token																	
+fn token(selector: &str, user_id: &str, expires_at: i64) -> RememberToken {
+	RememberToken {
+		selector:       selector.to_owned(),
+		validator_hash: hash_validator("current"),
+		previous_hash:  Some(hash_validator("previous")),
+		rotated_at:     NOW - 30,
+		user_id:        user_id.to_owned(),
+		expires_at,
+	}
+}
+
+
+
+//		Tests																											
+
+//		memory_store__delete_for_user											
+#[tokio::test]
+async fn memory_store__delete_for_user() {
+	let store  = MemoryRememberStore::new();
+	let future = OffsetDateTime::now_utc().unix_timestamp() + 3_600;
+	store.save(&token("one", r#""joe""#, future)).await.unwrap();
+	store.save(&token("two", r#""joe""#, future)).await.unwrap();
+	store.save(&token("six", r#""bob""#, future)).await.unwrap();
+	assert_eq!(store.delete_for_user(r#""joe""#).await.unwrap(), 2);
+	assert_eq!(store.load("one").await.unwrap(),                 None);
+	assert!(store.load("six").await.unwrap().is_some());
+}
+
+//		memory_store__save_and_load												
+#[tokio::test]
+async fn memory_store__save_and_load() {
+	let store  = MemoryRememberStore::new();
+	let future = OffsetDateTime::now_utc().unix_timestamp() + 3_600;
+	let saved  = token("one", r#""joe""#, future);
+	store.save(&saved).await.unwrap();
+	assert_eq!(store.load("one").await.unwrap(), Some(saved));
+	assert_eq!(store.load("two").await.unwrap(), None);
+	store.delete("one").await.unwrap();
+	assert_eq!(store.load("one").await.unwrap(), None);
+}
+
+//		memory_store__save_purges_expired										
+#[tokio::test]
+async fn memory_store__save_purges_expired() {
+	let store = MemoryRememberStore::new();
+	let now   = OffsetDateTime::now_utc().unix_timestamp();
+	store.save(&token("old", r#""joe""#, now - 1)).await.unwrap();
+	assert!(store.load("old").await.unwrap().is_some());
+	store.save(&token("new", r#""joe""#, now + 3_600)).await.unwrap();
+	assert_eq!(store.load("old").await.unwrap(), None);
+}
+
+//		token_from_headers														
+#[test]
+fn token_from_headers() {
+	let mut headers = HeaderMap::new();
+	assert_eq!(super::token_from_headers(&headers, "remember"), None);
+	drop(headers.insert(COOKIE, HeaderValue::from_static("id=abc; remember=sel:val")));
+	assert_eq!(super::token_from_headers(&headers, "remember"), Some((s!("sel"), s!("val"))));
+	assert_eq!(super::token_from_headers(&headers, "other"),    None);
+	drop(headers.insert(COOKIE, HeaderValue::from_static("remember=selval")));
+	assert_eq!(super::token_from_headers(&headers, "remember"), None);
+	drop(headers.insert(COOKIE, HeaderValue::from_static("remember=:val")));
+	assert_eq!(super::token_from_headers(&headers, "remember"), None);
+}
+
+//		verify__current															
+#[test]
+fn verify__current() {
+	let stored = token("sel", r#""joe""#, NOW + 60);
+	assert_eq!(verify(&stored, "current", NOW, 60), Verdict::Current);
+}
+
+//		verify__expired															
+#[test]
+fn verify__expired() {
+	let stored = token("sel", r#""joe""#, NOW);
+	assert_eq!(verify(&stored, "current", NOW, 60), Verdict::Expired);
+}
+
+//		verify__previous														
+#[test]
+fn verify__previous() {
+	let stored = token("sel", r#""joe""#, NOW + 60);
+	assert_eq!(verify(&stored, "previous", NOW, 60), Verdict::Previous);
+	assert_eq!(verify(&stored, "previous", NOW, 30), Verdict::Stolen);
+}
+
+//		verify__stolen															
+#[test]
+fn verify__stolen() {
+	let mut stored = token("sel", r#""joe""#, NOW + 60);
+	assert_eq!(verify(&stored, "unknown", NOW, 60), Verdict::Stolen);
+	stored.previous_hash = None;
+	assert_eq!(verify(&stored, "previous", NOW, 60), Verdict::Stolen);
+}
+
+
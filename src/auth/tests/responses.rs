@@ -0,0 +1,69 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+
+
+
+//		Functions																										
+
+//		headers																	
+fn headers(accept: &'static str) -> HeaderMap {
+	let mut headers = HeaderMap::new();
+	drop(headers.insert(ACCEPT, HeaderValue::from_static(accept)));
+	headers
+}
+
+
+
+//		Tests																											
+
+//		negotiate__html															
+#[test]
+fn negotiate__html() {
+	assert_eq!(ResponseFormat::negotiate(&HeaderMap::new()),                                     ResponseFormat::Html);
+	assert_eq!(ResponseFormat::negotiate(&headers("*/*")),                                       ResponseFormat::Html);
+	assert_eq!(ResponseFormat::negotiate(&headers("text/html,application/xhtml+xml,*/*;q=0.8")), ResponseFormat::Html);
+	assert_eq!(ResponseFormat::negotiate(&headers("text/html, application/json;q=0.9")),         ResponseFormat::Html);
+	assert_eq!(ResponseFormat::negotiate(&headers("image/png")),                                 ResponseFormat::Html);
+}
+
+//		negotiate__json															
+#[test]
+fn negotiate__json() {
+	assert_eq!(ResponseFormat::negotiate(&headers("application/json")),                          ResponseFormat::Json);
+	assert_eq!(ResponseFormat::negotiate(&headers("application/problem+json")),                  ResponseFormat::Json);
+	assert_eq!(ResponseFormat::negotiate(&headers("Application/JSON; charset=utf-8")),           ResponseFormat::Json);
+	assert_eq!(ResponseFormat::negotiate(&headers("text/html;q=0.5, application/json")),         ResponseFormat::Json);
+	assert_eq!(ResponseFormat::negotiate(&headers("application/json, */*;q=0.1")),               ResponseFormat::Json);
+}
+
+//		for_request__override													
+#[test]
+fn for_request__override() {
+	let mut request = Request::builder().header(ACCEPT, "application/json").body(Body::empty()).unwrap();
+	assert_eq!(ResponseFormat::for_request(&request), ResponseFormat::Json);
+	_ = request.extensions_mut().insert(ResponseFormat::Html);
+	assert_eq!(ResponseFormat::for_request(&request), ResponseFormat::Html);
+}
+
+//		problem__unauthorized													
+#[test]
+fn problem__unauthorized() {
+	let response = Problem::unauthorized("Bearer");
+	assert_eq!(response.status(),                    StatusCode::UNAUTHORIZED);
+	assert_eq!(response.headers()[WWW_AUTHENTICATE], "Bearer");
+	assert_eq!(response.headers()[CONTENT_TYPE],     "application/problem+json");
+}
+
+//		problem__forbidden														
+#[test]
+fn problem__forbidden() {
+	let response = Problem::forbidden();
+	assert_eq!(response.status(),                    StatusCode::FORBIDDEN);
+	assert!(!response.headers().contains_key(WWW_AUTHENTICATE));
+	assert_eq!(response.headers()[CONTENT_TYPE],     "application/problem+json");
+}
+
+
@@ -0,0 +1,307 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use crate::{
+	app::{config::HtmlTemplates, errors::AppError, state::StateProvider as AppStateProvider},
+	auth::{
+		audit::TracingAuditSink,
+		config::Config,
+		lockout::LockoutTracker,
+		middleware::{Context, Credentials, User, UserProvider},
+		remember::MemoryRememberStore,
+		routing::RouterExt as _,
+		users::ConfigUserStore,
+	},
+};
+use axum::{
+	Router,
+	body::{Body, to_bytes},
+	extract::{Path, State},
+	http::{Request, StatusCode, header::{COOKIE, SET_COOKIE}},
+	response::Response,
+	routing::get,
+};
+use core::net::{IpAddr, Ipv4Addr, SocketAddr};
+use rubedo::sugar::s;
+use serde::Deserialize;
+use std::sync::Arc;
+use tera::{Context as Template, Tera};
+use tower::ServiceExt as _;
+use tower_sessions::MemoryStore;
+
+#[cfg(feature = "oidc")]
+use crate::auth::oidc::OidcCache;
+
+
+
+//		Structs																											
+
+//		TestCredentials															
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct TestCredentials {
+	username: String,
+	password: String,
+}
+
+//󰭅		Credentials																
+impl Credentials for TestCredentials {
+	//		to_loggable_string													
+	fn to_loggable_string(&self) -> String {
+		self.username.clone()
+	}
+}
+
+//		TestState																
+#[derive(Debug, Default)]
+struct TestState {
+	audit:      TracingAuditSink,
+	config:     Config,
+	lockout:    LockoutTracker,
+	#[cfg(feature = "oidc")]
+	oidc:       OidcCache,
+	remember:   MemoryRememberStore,
+	revocation: MemoryRevocationStore,
+	sessions:   MemoryStore,
+	templates:  HtmlTemplates,
+	tera:       Tera,
+	title:      String,
+	users:      ConfigUserStore,
+}
+
+//󰭅		AppStateProvider														
+impl AppStateProvider for TestState {
+	//		address																
+	fn address(&self) -> Option<SocketAddr> {
+		None
+	}
+	
+	//		html_templates_config												
+	fn html_templates_config(&self) -> &HtmlTemplates {
+		&self.templates
+	}
+	
+	//		host																
+	fn host(&self) -> IpAddr {
+		IpAddr::V4(Ipv4Addr::LOCALHOST)
+	}
+	
+	//		port																
+	fn port(&self) -> u16 {
+		0
+	}
+	
+	//		render																
+	async fn render<T: AsRef<str> + Send>(&self, template: T, _context: &Template) -> Result<String, AppError> {
+		Ok(template.as_ref().to_owned())
+	}
+	
+	//		set_address															
+	fn set_address(&self, _address: Option<SocketAddr>) {}
+	
+	//		tera																
+	fn tera(&self) -> &Tera {
+		&self.tera
+	}
+	
+	//		title																
+	fn title(&self) -> &String {
+		&self.title
+	}
+}
+
+//󰭅		StateProvider															
+impl StateProvider for TestState {
+	type AuditSink       = TracingAuditSink;
+	type RememberStore   = MemoryRememberStore;
+	type RevocationStore = MemoryRevocationStore;
+	type SessionStore    = MemoryStore;
+	
+	//		audit_sink															
+	fn audit_sink(&self) -> &TracingAuditSink {
+		&self.audit
+	}
+	
+	//		config																
+	fn config(&self) -> &Config {
+		&self.config
+	}
+	
+	//		lockout																
+	fn lockout(&self) -> &LockoutTracker {
+		&self.lockout
+	}
+	
+	//		oidc_cache															
+	#[cfg(feature = "oidc")]
+	fn oidc_cache(&self) -> &OidcCache {
+		&self.oidc
+	}
+	
+	//		remember_store														
+	fn remember_store(&self) -> &MemoryRememberStore {
+		&self.remember
+	}
+	
+	//		revocation_store													
+	fn revocation_store(&self) -> &MemoryRevocationStore {
+		&self.revocation
+	}
+	
+	//		session_store														
+	fn session_store(&self) -> MemoryStore {
+		self.sessions.clone()
+	}
+	
+	//		users																
+	fn users(&self) -> &ConfigUserStore {
+		&self.users
+	}
+}
+
+//		TestUser																
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct TestUser {
+	name: String,
+}
+
+//󰭅		User																	
+impl User for TestUser {
+	type Id = String;
+	
+	//		id																	
+	fn id(&self) -> &Self::Id {
+		&self.name
+	}
+	
+	//		to_loggable_string													
+	fn to_loggable_string(&self) -> String {
+		self.name.clone()
+	}
+}
+
+//󰭅		UserProvider															
+impl UserProvider for TestUser {
+	type Credentials = TestCredentials;
+	type Error       = Infallible;
+	type User        = Self;
+	
+	//		find_by_credentials													
+	async fn find_by_credentials<SP: StateProvider>(
+		_state:       &SP,
+		_credentials: &Self::Credentials,
+	) -> Result<Option<Self>, Self::Error> {
+		Ok(None)
+	}
+	
+	//		find_by_id															
+	async fn find_by_id<SP: StateProvider>(
+		_state: &SP,
+		id:     &String,
+	) -> Result<Option<Self>, Self::Error> {
+		Ok(Some(Self { name: id.clone() }))
+	}
+}
+
+
+
+//		Functions																										
+
+//		body																	
+async fn body(response: Response) -> String {
+	String::from_utf8(to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()).unwrap()
+}
+
+//		cookie																	
+fn cookie(response: &Response, name: &str) -> Option<String> {
+	response.headers().get_all(SET_COOKIE).iter()
+		.filter_map(|value| value.to_str().ok())
+		.filter_map(|value| value.split(';').next())
+		.find(|pair| pair.split_once('=').is_some_and(|(key, _)| key == name))
+		.map(ToOwned::to_owned)
+}
+
+//		current_user															
+async fn current_user(auth: Context<TestUser>) -> String {
+	auth.current_user.map_or_else(|| s!("anonymous"), |user| user.name)
+}
+
+//		get_request																
+fn get_request(uri: &str) -> Request<Body> {
+	Request::get(uri).body(Body::empty()).unwrap()
+}
+
+//		log_in																	
+async fn log_in(
+	State(state): State<Arc<TestState>>,
+	mut auth:     Context<TestUser>,
+	Path(name):   Path<String>,
+) -> StatusCode {
+	auth.login(&*state, &TestUser { name }).await.unwrap();
+	StatusCode::OK
+}
+
+//		router																	
+fn router() -> (Arc<TestState>, Router) {
+	let state  = Arc::new(TestState::default());
+	let router = Router::new()
+		.route("/login/{name}", get(log_in))
+		.route("/user",         get(current_user))
+		.add_authentication::<_, TestUser, TestUser>(&state)
+		.with_state(Arc::clone(&state))
+	;
+	(state, router)
+}
+
+//		send																	
+async fn send(router: &Router, mut request: Request<Body>, cookie: Option<&str>) -> Response {
+	if let Some(value) = cookie {
+		drop(request.headers_mut().insert(COOKIE, value.parse().unwrap()));
+	}
+	router.clone().oneshot(request).await.unwrap()
+}
+
+
+
+//		Tests																											
+
+//		generation																
+#[tokio::test]
+async fn generation() {
+	let state = TestState::default();
+	assert_eq!(super::generation(&state, "joe").await.unwrap(), 0);
+	super::revoke_sessions(&state, "joe").await.unwrap();
+	assert_eq!(super::generation(&state, "joe").await.unwrap(), 1);
+	assert_eq!(super::generation(&state, "bob").await.unwrap(), 0);
+}
+
+//		memory_revocation_store__revoke											
+#[tokio::test]
+async fn memory_revocation_store__revoke() {
+	let store = MemoryRevocationStore::new();
+	assert_eq!(store.generation("joe").await.unwrap(), 0);
+	assert_eq!(store.revoke("joe").await.unwrap(),     1);
+	assert_eq!(store.revoke("joe").await.unwrap(),     2);
+	assert_eq!(store.generation("joe").await.unwrap(), 2);
+	assert_eq!(store.generation("bob").await.unwrap(), 0);
+}
+
+//		revoke_sessions															
+#[tokio::test]
+async fn revoke_sessions() {
+	let (state, router) = router();
+	let one             = cookie(&send(&router, get_request("/login/joe"), None).await, "id").unwrap();
+	let two             = cookie(&send(&router, get_request("/login/joe"), None).await, "id").unwrap();
+	let other           = cookie(&send(&router, get_request("/login/bob"), None).await, "id").unwrap();
+	super::revoke_sessions(&*state, "joe").await.unwrap();
+	//	The user is logged out everywhere, but other users are not affected
+	assert_eq!(body(send(&router, get_request("/user"), Some(&one)).await).await,   "anonymous");
+	assert_eq!(body(send(&router, get_request("/user"), Some(&two)).await).await,   "anonymous");
+	assert_eq!(body(send(&router, get_request("/user"), Some(&other)).await).await, "bob");
+	//	Logging in again after the revocation is not affected by it
+	let three           = cookie(&send(&router, get_request("/login/joe"), None).await, "id").unwrap();
+	assert_eq!(body(send(&router, get_request("/user"), Some(&three)).await).await, "joe");
+}
+
+
@@ -0,0 +1,344 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use crate::{
+	app::{config::HtmlTemplates, errors::AppError},
+	auth::{
+		audit::{AuditEntry, AuditSink, AuthEvent},
+		config::{Config, Cookie, Expiry, SameSite},
+		lockout::LockoutTracker,
+		middleware::{Context, Credentials},
+		remember::MemoryRememberStore,
+		revocation::MemoryRevocationStore,
+		users::ConfigUserStore,
+	},
+};
+use axum::{
+	body::{Body, to_bytes},
+	extract::{Path, State},
+	http::{Request, StatusCode, header::{COOKIE, SET_COOKIE}},
+	response::Response,
+	routing::get,
+};
+use core::{
+	convert::Infallible,
+	net::{IpAddr, Ipv4Addr, SocketAddr},
+};
+use parking_lot::Mutex;
+use rubedo::sugar::s;
+use serde::Deserialize;
+use tera::{Context as Template, Tera};
+use tower::ServiceExt as _;
+use tower_sessions::MemoryStore;
+
+#[cfg(feature = "oidc")]
+use crate::auth::oidc::OidcCache;
+
+
+
+//		Structs																											
+
+//		RecordingAuditSink														
+#[derive(Debug, Default)]
+struct RecordingAuditSink {
+	events: Mutex<Vec<AuthEvent>>,
+}
+
+//󰭅		AuditSink																
+impl AuditSink for RecordingAuditSink {
+	type Error = Infallible;
+	
+	//		record																
+	async fn record(&self, entry: &AuditEntry) -> Result<(), Self::Error> {
+		self.events.lock().push(entry.event.clone());
+		Ok(())
+	}
+}
+
+//		TestCredentials															
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct TestCredentials {
+	username: String,
+	password: String,
+}
+
+//󰭅		Credentials																
+impl Credentials for TestCredentials {
+	//		to_loggable_string													
+	fn to_loggable_string(&self) -> String {
+		self.username.clone()
+	}
+}
+
+//		TestState																
+#[derive(Debug, Default)]
+struct TestState {
+	audit:      RecordingAuditSink,
+	config:     Config,
+	lockout:    LockoutTracker,
+	#[cfg(feature = "oidc")]
+	oidc:       OidcCache,
+	remember:   MemoryRememberStore,
+	revocation: MemoryRevocationStore,
+	sessions:   MemoryStore,
+	templates:  HtmlTemplates,
+	tera:       Tera,
+	title:      String,
+	users:      ConfigUserStore,
+}
+
+//󰭅		AppStateProvider														
+impl AppStateProvider for TestState {
+	//		address																
+	fn address(&self) -> Option<SocketAddr> {
+		None
+	}
+	
+	//		html_templates_config												
+	fn html_templates_config(&self) -> &HtmlTemplates {
+		&self.templates
+	}
+	
+	//		host																
+	fn host(&self) -> IpAddr {
+		IpAddr::V4(Ipv4Addr::LOCALHOST)
+	}
+	
+	//		port																
+	fn port(&self) -> u16 {
+		0
+	}
+	
+	//		render																
+	async fn render<T: AsRef<str> + Send>(&self, template: T, _context: &Template) -> Result<String, AppError> {
+		Ok(template.as_ref().to_owned())
+	}
+	
+	//		set_address															
+	fn set_address(&self, _address: Option<SocketAddr>) {}
+	
+	//		tera																
+	fn tera(&self) -> &Tera {
+		&self.tera
+	}
+	
+	//		title																
+	fn title(&self) -> &String {
+		&self.title
+	}
+}
+
+//󰭅		StateProvider															
+impl StateProvider for TestState {
+	type AuditSink       = RecordingAuditSink;
+	type RememberStore   = MemoryRememberStore;
+	type RevocationStore = MemoryRevocationStore;
+	type SessionStore    = MemoryStore;
+	
+	//		audit_sink															
+	fn audit_sink(&self) -> &RecordingAuditSink {
+		&self.audit
+	}
+	
+	//		config																
+	fn config(&self) -> &Config {
+		&self.config
+	}
+	
+	//		lockout																
+	fn lockout(&self) -> &LockoutTracker {
+		&self.lockout
+	}
+	
+	//		oidc_cache															
+	#[cfg(feature = "oidc")]
+	fn oidc_cache(&self) -> &OidcCache {
+		&self.oidc
+	}
+	
+	//		remember_store														
+	fn remember_store(&self) -> &MemoryRememberStore {
+		&self.remember
+	}
+	
+	//		revocation_store													
+	fn revocation_store(&self) -> &MemoryRevocationStore {
+		&self.revocation
+	}
+	
+	//		session_store														
+	fn session_store(&self) -> MemoryStore {
+		self.sessions.clone()
+	}
+	
+	//		users																
+	fn users(&self) -> &ConfigUserStore {
+		&self.users
+	}
+}
+
+//		TestUser																
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct TestUser {
+	name: String,
+}
+
+//󰭅		User																	
+impl User for TestUser {
+	type Id = String;
+	
+	//		id																	
+	fn id(&self) -> &Self::Id {
+		&self.name
+	}
+	
+	//		to_loggable_string													
+	fn to_loggable_string(&self) -> String {
+		self.name.clone()
+	}
+}
+
+//󰭅		UserProvider															
+impl UserProvider for TestUser {
+	type Credentials = TestCredentials;
+	type Error       = Infallible;
+	type User        = Self;
+	
+	//		find_by_credentials													
+	async fn find_by_credentials<SP: StateProvider>(
+		_state:       &SP,
+		_credentials: &Self::Credentials,
+	) -> Result<Option<Self>, Self::Error> {
+		Ok(None)
+	}
+	
+	//		find_by_id															
+	async fn find_by_id<SP: StateProvider>(
+		_state: &SP,
+		id:     &String,
+	) -> Result<Option<Self>, Self::Error> {
+		Ok(Some(Self { name: id.clone() }))
+	}
+}
+
+
+
+//		Functions																										
+
+//		body																	
+async fn body(response: Response) -> String {
+	String::from_utf8(to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()).unwrap()
+}
+
+//		cookie																	
+fn cookie(response: &Response, name: &str) -> Option<String> {
+	response.headers().get_all(SET_COOKIE).iter()
+		.filter_map(|value| value.to_str().ok())
+		.filter_map(|value| value.split(';').next())
+		.find(|pair| pair.split_once('=').is_some_and(|(key, _)| key == name))
+		.map(ToOwned::to_owned)
+}
+
+//		current_user															
+async fn current_user(auth: Context<TestUser>) -> String {
+	auth.current_user.map_or_else(|| s!("anonymous"), |user| user.name)
+}
+
+//		get_request																
+fn get_request(uri: &str) -> Request<Body> {
+	Request::get(uri).body(Body::empty()).unwrap()
+}
+
+//		log_in																	
+async fn log_in(
+	State(state): State<Arc<TestState>>,
+	mut auth:     Context<TestUser>,
+	Path(name):   Path<String>,
+) -> StatusCode {
+	auth.login(&*state, &TestUser { name }).await.unwrap();
+	StatusCode::OK
+}
+
+//		router																	
+fn router(config: Config) -> (Arc<TestState>, Router) {
+	let state  = Arc::new(TestState { config, ..Default::default() });
+	let router = Router::new()
+		.route("/login/{name}", get(log_in))
+		.route("/user",         get(current_user))
+		.add_authentication::<_, TestUser, TestUser>(&state)
+		.with_state(Arc::clone(&state))
+	;
+	(state, router)
+}
+
+//		send																	
+async fn send(router: &Router, mut request: Request<Body>, cookie: Option<&str>) -> Response {
+	if let Some(value) = cookie {
+		drop(request.headers_mut().insert(COOKIE, value.parse().unwrap()));
+	}
+	router.clone().oneshot(request).await.unwrap()
+}
+
+
+
+//		Tests																											
+
+//		add_authentication__absolute_expiry										
+#[tokio::test]
+async fn add_authentication__absolute_expiry() {
+	let (state, router) = router(Config { expiry: Expiry { absolute: Some(0), ..Default::default() }, ..Default::default() });
+	let session         = cookie(&send(&router, get_request("/login/joe"), None).await, "id").unwrap();
+	let response        = send(&router, get_request("/user"), Some(&session)).await;
+	assert_eq!(body(response).await, "anonymous");
+	assert_eq!(*state.audit.events.lock(), vec![AuthEvent::SessionExpired { user: s!("joe") }]);
+}
+
+//		add_authentication__cookie_attributes									
+#[tokio::test]
+async fn add_authentication__cookie_attributes() {
+	let (_, router) = router(Config {
+		cookie: Cookie {
+			name:      s!("sid"),
+			domain:    Some(s!("example.com")),
+			path:      s!("/app"),
+			secure:    true,
+			same_site: SameSite::Lax,
+			..Default::default()
+		},
+		..Default::default()
+	});
+	let response    = send(&router, get_request("/login/joe"), None).await;
+	let set         = response.headers()[SET_COOKIE].to_str().unwrap();
+	assert!(set.starts_with("sid="));
+	for attribute in ["Domain=example.com", "Path=/app", "Secure", "HttpOnly", "SameSite=Lax"] {
+		assert!(set.split("; ").any(|part| part == attribute), "{attribute} missing from {set}");
+	}
+	//	Without an inactivity expiry, the cookie lasts for the browser session
+	assert!(!set.contains("Max-Age="));
+}
+
+//		add_authentication__inactivity_expiry									
+#[tokio::test]
+async fn add_authentication__inactivity_expiry() {
+	let (_, router) = router(Config { expiry: Expiry { inactivity: Some(600), ..Default::default() }, ..Default::default() });
+	let login       = send(&router, get_request("/login/joe"), None).await;
+	assert!(login.headers()[SET_COOKIE].to_str().unwrap().contains("Max-Age=600"));
+	//	Each request extends the session, and so sets the cookie again
+	let session     = cookie(&login, "id").unwrap();
+	let response    = send(&router, get_request("/user"), Some(&session)).await;
+	assert!(response.headers()[SET_COOKIE].to_str().unwrap().contains("Max-Age=600"));
+}
+
+//		add_authentication__within_absolute_expiry								
+#[tokio::test]
+async fn add_authentication__within_absolute_expiry() {
+	let (state, router) = router(Config { expiry: Expiry { absolute: Some(3_600), ..Default::default() }, ..Default::default() });
+	let session         = cookie(&send(&router, get_request("/login/joe"), None).await, "id").unwrap();
+	let response        = send(&router, get_request("/user"), Some(&session)).await;
+	assert_eq!(body(response).await, "joe");
+	assert!(state.audit.events.lock().is_empty());
+}
+
+
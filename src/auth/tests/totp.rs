@@ -0,0 +1,527 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use crate::{
+	app::{config::HtmlTemplates, errors::AppError, state::StateProvider as AppStateProvider},
+	auth::{
+		audit::{AuditEntry, AuditSink},
+		config::Config,
+		lockout::LockoutTracker,
+		middleware::Credentials,
+		remember::MemoryRememberStore,
+		revocation::MemoryRevocationStore,
+		routing::RouterExt as _,
+		users::ConfigUserStore,
+	},
+};
+use axum::{
+	Router,
+	body::{Body, to_bytes},
+	extract::Path,
+	http::{Request, StatusCode, header::{CONTENT_TYPE, COOKIE, LOCATION}},
+	routing::{get, post},
+};
+use core::{
+	convert::Infallible,
+	net::{IpAddr, Ipv4Addr, SocketAddr},
+};
+use parking_lot::Mutex;
+use std::sync::LazyLock;
+use tera::Tera;
+use tower::ServiceExt as _;
+use tower_sessions::MemoryStore;
+
+#[cfg(feature = "oidc")]
+use crate::auth::oidc::OidcCache;
+
+
+
+//		Constants																										
+
+/// A recovery code given to each enrolled test user.
+const RECOVERY_CODE: &str = "abcd-efgh-ijkl-mnop";
+
+/// The secret from the test vectors in RFC 6238, base32-encoded.
+const RFC_SECRET:    &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+/// The second factor details of the enrolled test users, keyed by username.
+/// Each test uses its own usernames, so that tests running in parallel do not
+/// affect each other.
+static TWO_FACTOR: LazyLock<Mutex<HashMap<String, TwoFactor>>> = LazyLock::new(|| {
+	Mutex::new(HashMap::new())
+});
+
+
+
+//		Structs																											
+
+//		RecordingAuditSink														
+#[derive(Debug, Default)]
+struct RecordingAuditSink {
+	events: Mutex<Vec<AuthEvent>>,
+}
+
+//󰭅		AuditSink																
+impl AuditSink for RecordingAuditSink {
+	type Error = Infallible;
+	
+	//		record																
+	async fn record(&self, entry: &AuditEntry) -> Result<(), Self::Error> {
+		self.events.lock().push(entry.event.clone());
+		Ok(())
+	}
+}
+
+//		TestCredentials															
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct TestCredentials {
+	username: String,
+	password: String,
+}
+
+//󰭅		Credentials																
+impl Credentials for TestCredentials {
+	//		to_loggable_string													
+	fn to_loggable_string(&self) -> String {
+		self.username.clone()
+	}
+}
+
+//		TestState																
+#[derive(Debug, Default)]
+struct TestState {
+	audit:      RecordingAuditSink,
+	config:     Config,
+	lockout:    LockoutTracker,
+	#[cfg(feature = "oidc")]
+	oidc:       OidcCache,
+	remember:   MemoryRememberStore,
+	revocation: MemoryRevocationStore,
+	sessions:   MemoryStore,
+	templates:  HtmlTemplates,
+	tera:       Tera,
+	title:      String,
+	users:      ConfigUserStore,
+}
+
+//󰭅		AppStateProvider														
+impl AppStateProvider for TestState {
+	//		address																
+	fn address(&self) -> Option<SocketAddr> {
+		None
+	}
+	
+	//		html_templates_config												
+	fn html_templates_config(&self) -> &HtmlTemplates {
+		&self.templates
+	}
+	
+	//		host																
+	fn host(&self) -> IpAddr {
+		IpAddr::V4(Ipv4Addr::LOCALHOST)
+	}
+	
+	//		port																
+	fn port(&self) -> u16 {
+		0
+	}
+	
+	//		render																
+	async fn render<T: AsRef<str> + Send>(&self, template: T, _context: &Template) -> Result<String, AppError> {
+		Ok(template.as_ref().to_owned())
+	}
+	
+	//		set_address															
+	fn set_address(&self, _address: Option<SocketAddr>) {}
+	
+	//		tera																
+	fn tera(&self) -> &Tera {
+		&self.tera
+	}
+	
+	//		title																
+	fn title(&self) -> &String {
+		&self.title
+	}
+}
+
+//󰭅		StateProvider															
+impl StateProvider for TestState {
+	type AuditSink       = RecordingAuditSink;
+	type RememberStore   = MemoryRememberStore;
+	type RevocationStore = MemoryRevocationStore;
+	type SessionStore    = MemoryStore;
+	
+	//		audit_sink															
+	fn audit_sink(&self) -> &RecordingAuditSink {
+		&self.audit
+	}
+	
+	//		config																
+	fn config(&self) -> &Config {
+		&self.config
+	}
+	
+	//		lockout																
+	fn lockout(&self) -> &LockoutTracker {
+		&self.lockout
+	}
+	
+	//		oidc_cache															
+	#[cfg(feature = "oidc")]
+	fn oidc_cache(&self) -> &OidcCache {
+		&self.oidc
+	}
+	
+	//		remember_store														
+	fn remember_store(&self) -> &MemoryRememberStore {
+		&self.remember
+	}
+	
+	//		revocation_store													
+	fn revocation_store(&self) -> &MemoryRevocationStore {
+		&self.revocation
+	}
+	
+	//		session_store														
+	fn session_store(&self) -> MemoryStore {
+		self.sessions.clone()
+	}
+	
+	//		users																
+	fn users(&self) -> &ConfigUserStore {
+		&self.users
+	}
+}
+
+//		TestUser																
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct TestUser {
+	name: String,
+}
+
+//󰭅		TotpUserProvider														
+impl TotpUserProvider for TestUser {
+	//		find_totp_secret													
+	async fn find_totp_secret<SP: StateProvider>(
+		_state: &SP,
+		user:   &Self,
+	) -> Result<Option<TotpSecret>, Self::Error> {
+		Ok(TWO_FACTOR.lock().get(&user.name).map(|details| details.secret.clone()))
+	}
+	
+	//		record_totp_use														
+	async fn record_totp_use<SP: StateProvider>(
+		_state: &SP,
+		user:   &Self,
+		step:   u64,
+	) -> Result<(), Self::Error> {
+		if let Some(details) = TWO_FACTOR.lock().get_mut(&user.name) {
+			details.secret.last_used_step = Some(step);
+		}
+		Ok(())
+	}
+	
+	//		use_recovery_code													
+	async fn use_recovery_code<SP: StateProvider>(
+		_state: &SP,
+		user:   &Self,
+		hash:   &str,
+	) -> Result<bool, Self::Error> {
+		Ok(TWO_FACTOR.lock().get_mut(&user.name).is_some_and(|details| {
+			let before = details.recovery_codes.len();
+			details.recovery_codes.retain(|stored| stored != hash);
+			details.recovery_codes.len() < before
+		}))
+	}
+}
+
+//󰭅		User																	
+impl User for TestUser {
+	type Id = String;
+	
+	//		id																	
+	fn id(&self) -> &Self::Id {
+		&self.name
+	}
+	
+	//		to_loggable_string													
+	fn to_loggable_string(&self) -> String {
+		self.name.clone()
+	}
+}
+
+//󰭅		UserProvider															
+impl UserProvider for TestUser {
+	type Credentials = TestCredentials;
+	type Error       = Infallible;
+	type User        = Self;
+	
+	//		find_by_credentials													
+	async fn find_by_credentials<SP: StateProvider>(
+		_state:       &SP,
+		_credentials: &Self::Credentials,
+	) -> Result<Option<Self>, Self::Error> {
+		Ok(None)
+	}
+	
+	//		find_by_id															
+	async fn find_by_id<SP: StateProvider>(
+		_state: &SP,
+		id:     &String,
+	) -> Result<Option<Self>, Self::Error> {
+		Ok(Some(Self { name: id.clone() }))
+	}
+}
+
+//		TwoFactor																
+#[derive(Clone, Debug)]
+struct TwoFactor {
+	secret:         TotpSecret,
+	recovery_codes: Vec<String>,
+}
+
+
+
+//		Functions																										
+
+//		body																	
+async fn body(response: Response) -> String {
+	String::from_utf8(to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()).unwrap()
+}
+
+//		code_request															
+fn code_request(code: &str) -> Request<Body> {
+	Request::post("/login/totp")
+		.header(CONTENT_TYPE, "application/x-www-form-urlencoded")
+		.body(Body::from(format!("code={code}")))
+		.unwrap()
+}
+
+//		cookie																	
+fn cookie(response: &Response, name: &str) -> Option<String> {
+	response.headers().get_all(SET_COOKIE).iter()
+		.filter_map(|value| value.to_str().ok())
+		.filter_map(|value| value.split(';').next())
+		.find(|pair| pair.split_once('=').is_some_and(|(key, _)| key == name))
+		.map(ToOwned::to_owned)
+}
+
+//		current_code															
+fn current_code() -> String {
+	let bytes = Secret::Encoded(s!(RFC_SECRET)).to_bytes().unwrap();
+	build_totp(&Totp::default(), bytes, "").unwrap().generate_current().unwrap()
+}
+
+//		current_user															
+async fn current_user(auth: Context<TestUser>) -> String {
+	auth.current_user.map_or_else(|| s!("anonymous"), |user| user.name)
+}
+
+//		enrol_user																
+fn enrol_user(name: &str) {
+	drop(TWO_FACTOR.lock().insert(s!(name), TwoFactor {
+		secret:         rfc_secret(None),
+		recovery_codes: vec![super::hash_recovery_code(RECOVERY_CODE)],
+	}));
+}
+
+//		location																
+fn location(response: &Response) -> &str {
+	response.headers()[LOCATION].to_str().unwrap()
+}
+
+//		pending_login															
+async fn pending_login(router: &Router, name: &str) -> String {
+	let request = Request::get(format!("/start/{name}")).body(Body::empty()).unwrap();
+	cookie(&send(router, request, None).await, "id").unwrap()
+}
+
+//		rfc_config																
+fn rfc_config() -> Totp {
+	Totp {
+		digits: 8,
+		..Default::default()
+	}
+}
+
+//		rfc_secret																
+fn rfc_secret(last_used_step: Option<u64>) -> TotpSecret {
+	TotpSecret {
+		secret: s!(RFC_SECRET),
+		last_used_step,
+	}
+}
+
+//		router																	
+fn router(config: Config) -> (Arc<TestState>, Router) {
+	let state  = Arc::new(TestState { config, ..Default::default() });
+	let router = Router::new()
+		.route("/login/totp",   post(post_totp_login::<_, TestUser, TestUser>))
+		.route("/start/{name}", get(start_login))
+		.route("/user",         get(current_user))
+		.add_authentication::<_, TestUser, TestUser>(&state)
+		.with_state(Arc::clone(&state))
+	;
+	(state, router)
+}
+
+//		send																	
+async fn send(router: &Router, mut request: Request<Body>, cookie: Option<&str>) -> Response {
+	if let Some(value) = cookie {
+		drop(request.headers_mut().insert(COOKIE, value.parse().unwrap()));
+	}
+	router.clone().oneshot(request).await.unwrap()
+}
+
+//		start_login																
+async fn start_login(auth: Context<TestUser>, Path(name): Path<String>) -> StatusCode {
+	start_totp_login(&auth, &TestUser { name }, "/user", false).await.unwrap();
+	StatusCode::OK
+}
+
+
+
+//		Tests																											
+
+//		enrol																	
+#[test]
+fn enrol() {
+	let enrolment = super::enrol(&Totp::default(), "joe").unwrap();
+	assert_eq!(Secret::Encoded(enrolment.secret.clone()).to_bytes().unwrap().len(), SECRET_LENGTH);
+	assert!(enrolment.uri.starts_with("otpauth://totp/Terracotta:joe?"));
+	assert!(enrolment.uri.contains(&format!("secret={}", enrolment.secret)));
+	assert!(enrolment.uri.contains("issuer=Terracotta"));
+	assert_eq!(enrolment.recovery_codes.len(), 10);
+	assert_eq!(
+		enrolment.recovery_code_hashes,
+		enrolment.recovery_codes.iter().map(|code| super::hash_recovery_code(code)).collect::<Vec<_>>(),
+	);
+}
+
+//		enrol__invalid_account_name												
+#[test]
+fn enrol__invalid_account_name() {
+	assert!(matches!(super::enrol(&Totp::default(), "joe:bloggs"), Err(AuthError::TotpError(_))));
+}
+
+//		generate_recovery_codes													
+#[test]
+fn generate_recovery_codes() {
+	let codes = super::generate_recovery_codes(5);
+	assert_eq!(codes.len(), 5);
+	for code in &codes {
+		assert_eq!(code.len(),              19);
+		assert_eq!(code.split('-').count(), 4);
+		assert!(code.chars().all(|ch| ch == '-' || ch.is_ascii_lowercase() || ('2'..='7').contains(&ch)));
+	}
+	assert_ne!(codes[0], codes[1]);
+}
+
+//		hash_recovery_code														
+#[test]
+fn hash_recovery_code() {
+	let hash = super::hash_recovery_code("abcd-efgh-ijkl-mnop");
+	assert_eq!(super::hash_recovery_code("ABCD-EFGH-IJKL-MNOP"),  hash);
+	assert_eq!(super::hash_recovery_code(" abcdefgh ijklmnop "), hash);
+	assert_ne!(super::hash_recovery_code("abcd-efgh-ijkl-mnoq"),  hash);
+}
+
+//		post_totp_login__expired												
+#[tokio::test]
+async fn post_totp_login__expired() {
+	let (_, router) = router(Config { totp: Totp { pending_lifetime: 0, ..Default::default() }, ..Default::default() });
+	enrol_user("2fa-expired");
+	let session     = pending_login(&router, "2fa-expired").await;
+	let expired     = send(&router, code_request(&current_code()), Some(&session)).await;
+	assert_eq!(location(&expired), "/user?failed=");
+	//	The pending login has been abandoned
+	let retried     = send(&router, code_request(&current_code()), Some(&session)).await;
+	assert_eq!(location(&retried), "/");
+	let request     = Request::get("/user").body(Body::empty()).unwrap();
+	assert_eq!(body(send(&router, request, Some(&session)).await).await, "anonymous");
+}
+
+//		post_totp_login__failed													
+#[tokio::test]
+async fn post_totp_login__failed() {
+	let (state, router) = router(Config::default());
+	enrol_user("2fa-failed");
+	let session         = pending_login(&router, "2fa-failed").await;
+	let failed          = send(&router, code_request("not-a-code"), Some(&session)).await;
+	assert_eq!(location(&failed), "/login/totp?failed");
+	//	The pending login is kept, so that the user can try again
+	let valid           = send(&router, code_request(&current_code()), Some(&session)).await;
+	assert_eq!(location(&valid), "/user");
+	let request         = Request::get("/user").body(Body::empty()).unwrap();
+	let login           = cookie(&valid, "id").unwrap();
+	assert_eq!(body(send(&router, request, Some(&login)).await).await, "2fa-failed");
+	assert_eq!(*state.audit.events.lock(), vec![
+		AuthEvent::LoginFailed { user: s!("2fa-failed"), method: LoginMethod::TwoFactor },
+		AuthEvent::Login       { user: s!("2fa-failed"), method: LoginMethod::TwoFactor },
+	]);
+}
+
+//		post_totp_login__recovery_code											
+#[tokio::test]
+async fn post_totp_login__recovery_code() {
+	let (_, router) = router(Config::default());
+	enrol_user("2fa-recovery");
+	let first       = pending_login(&router, "2fa-recovery").await;
+	let used        = send(&router, code_request(RECOVERY_CODE), Some(&first)).await;
+	assert_eq!(location(&used), "/user");
+	assert!(TWO_FACTOR.lock()["2fa-recovery"].recovery_codes.is_empty());
+	//	The recovery code cannot be used again
+	let second      = pending_login(&router, "2fa-recovery").await;
+	let reused      = send(&router, code_request(RECOVERY_CODE), Some(&second)).await;
+	assert_eq!(location(&reused), "/login/totp?failed");
+}
+
+//		post_totp_login__replayed												
+#[tokio::test]
+async fn post_totp_login__replayed() {
+	let (_, router) = router(Config::default());
+	enrol_user("2fa-replayed");
+	let code        = current_code();
+	let first       = pending_login(&router, "2fa-replayed").await;
+	let used        = send(&router, code_request(&code), Some(&first)).await;
+	assert_eq!(location(&used), "/user");
+	//	The same code cannot be used again
+	let second      = pending_login(&router, "2fa-replayed").await;
+	let replayed    = send(&router, code_request(&code), Some(&second)).await;
+	assert_eq!(location(&replayed), "/login/totp?failed");
+}
+
+//		verify_code__valid														
+#[test]
+fn verify_code__valid() {
+	//	Test vectors from RFC 6238, appendix B
+	assert_eq!(verify_code(&rfc_config(), &rfc_secret(None), "94287082", 59).unwrap(),            Some(1));
+	assert_eq!(verify_code(&rfc_config(), &rfc_secret(None), "07081804", 1_111_111_109).unwrap(), Some(37_037_036));
+	//	Within the allowed skew
+	assert_eq!(verify_code(&rfc_config(), &rfc_secret(None), "94287082", 89).unwrap(),            Some(1));
+}
+
+//		verify_code__invalid													
+#[test]
+fn verify_code__invalid() {
+	assert_eq!(verify_code(&rfc_config(), &rfc_secret(None), "94287083", 59).unwrap(), None);
+	//	Outside the allowed skew
+	assert_eq!(verify_code(&rfc_config(), &rfc_secret(None), "94287082", 90).unwrap(), None);
+	assert!(matches!(
+		verify_code(&rfc_config(), &TotpSecret { secret: s!("not base32!"), last_used_step: None }, "94287082", 59),
+		Err(AuthError::TotpError(_)),
+	));
+}
+
+//		verify_code__replayed													
+#[test]
+fn verify_code__replayed() {
+	assert_eq!(verify_code(&rfc_config(), &rfc_secret(Some(0)), "94287082", 59).unwrap(), Some(1));
+	assert_eq!(verify_code(&rfc_config(), &rfc_secret(Some(1)), "94287082", 59).unwrap(), None);
+	assert_eq!(verify_code(&rfc_config(), &rfc_secret(Some(2)), "94287082", 59).unwrap(), None);
+}
+
+
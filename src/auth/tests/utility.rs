@@ -0,0 +1,89 @@
+#![allow(non_snake_case, reason = "To enable test function name organisation")]
+
+//		Packages																										
+
+use super::*;
+use rubedo::sugar::s;
+
+
+
+//		Tests																											
+
+//		build_uri																
+#[test]
+fn build_uri() {
+	let mut params = HashMap::new();
+	assert_eq!(super::build_uri("/path", &params).unwrap(), "/path");
+	drop(params.insert(s!("b"), s!("x y&z=1")));
+	drop(params.insert(s!("a"), s!("")));
+	assert_eq!(super::build_uri("/path",     &params).unwrap(), "/path?a=&b=x+y%26z%3D1");
+	assert_eq!(super::build_uri("/path?c=1", &params).unwrap(), "/path?c=1&a=&b=x+y%26z%3D1");
+}
+
+//		extract_uri_query_parts													
+#[test]
+fn extract_uri_query_parts() {
+	let mut params = HashMap::new();
+	drop(params.insert(s!("a"),   s!("")));
+	drop(params.insert(s!("b c"), s!("x y&z=1")));
+	assert_eq!(super::extract_uri_query_parts(&Uri::from_static("/path")),                      HashMap::new());
+	assert_eq!(super::extract_uri_query_parts(&Uri::from_static("/path?a&b+c=x%20y%26z%3D1")), params);
+	assert_eq!(super::extract_uri_query_parts(&super::build_uri("/path", &params).unwrap()),   params);
+}
+
+//		generate_token															
+#[test]
+fn generate_token() {
+	let token = super::generate_token(32);
+	assert_eq!(token.len(), 43);
+	assert_ne!(token, super::generate_token(32));
+	assert_eq!(super::generate_token(12).len(), 16);
+}
+
+//		redirect_target															
+#[test]
+fn redirect_target() {
+	let mut params = HashMap::new();
+	drop(params.insert(s!("failed"), s!("")));
+	assert_eq!(super::redirect_target(&Uri::from_static("/path?a=1"), &params).unwrap(),                     "/path?failed=");
+	assert_eq!(super::redirect_target(&Uri::from_static("https://example.com:8443/path"), &params).unwrap(), "https://example.com:8443/path?failed=");
+}
+
+//		safe_redirect_uri__allowed												
+#[test]
+fn safe_redirect_uri__allowed() {
+	let allowed = vec![s!("https://example.com/"), s!("http://localhost:8080")];
+	assert_eq!(safe_redirect_uri("/",                            &allowed), "/");
+	assert_eq!(safe_redirect_uri("/path/to?page=1",              &allowed), "/path/to?page=1");
+	assert_eq!(safe_redirect_uri("https://example.com/path",     &allowed), "https://example.com/path");
+	assert_eq!(safe_redirect_uri("HTTPS://EXAMPLE.COM/path",     &allowed), "https://example.com/path");
+	assert_eq!(safe_redirect_uri("http://localhost:8080/path",   &allowed), "http://localhost:8080/path");
+}
+
+//		safe_redirect_uri__rejected												
+#[test]
+fn safe_redirect_uri__rejected() {
+	let allowed = vec![s!("https://example.com")];
+	assert_eq!(safe_redirect_uri("",                             &allowed), "/");
+	assert_eq!(safe_redirect_uri("path",                         &allowed), "/");
+	assert_eq!(safe_redirect_uri("//evil.com/path",              &allowed), "/");
+	assert_eq!(safe_redirect_uri("/\\evil.com/path",             &allowed), "/");
+	assert_eq!(safe_redirect_uri("/\t/evil.com/path",            &allowed), "/");
+	assert_eq!(safe_redirect_uri("https://evil.com/path",        &allowed), "/");
+	assert_eq!(safe_redirect_uri("http://example.com/path",      &allowed), "/");
+	assert_eq!(safe_redirect_uri("https://example.com:8443/",    &allowed), "/");
+	assert_eq!(safe_redirect_uri("https://example.com@evil.com", &allowed), "/");
+	assert_eq!(safe_redirect_uri("javascript:alert(1)",          &allowed), "/");
+	assert_eq!(safe_redirect_uri("example.com",                  &allowed), "/");
+}
+
+//		tokens_match															
+#[test]
+fn tokens_match() {
+	assert!( super::tokens_match("abcdef", "abcdef"));
+	assert!(!super::tokens_match("abcdef", "abcdeg"));
+	assert!(!super::tokens_match("abcdef", "abcde"));
+	assert!(!super::tokens_match("abcdef", ""));
+}
+
+
@@ -0,0 +1,623 @@
+#![allow(clippy::unused_async, reason = "Handler functions need to be async")]
+
+//! Two-factor authentication using time-based one-time passwords.
+//! 
+//! Time-based one-time passwords (TOTP), as described by
+//! [RFC 6238](https://www.rfc-editor.org/rfc/rfc6238), are generated by
+//! authenticator apps from a secret shared with the application on
+//! enrolment. They can be required as a second factor for users for whom
+//! [`User::requires_two_factor()`] returns `true`.
+//! 
+//! # Enrolment
+//! 
+//! A user is enrolled by calling [`enrol()`], which generates a new secret, an
+//! `otpauth://` URI for the user to add to their authenticator app (typically
+//! shown as a QR code), and a set of single-use recovery codes. The secret and
+//! the hashes of the recovery codes should be stored, and the recovery codes
+//! shown to the user once. It is recommended that the user is asked to enter a
+//! code, which can be checked using [`verify_code()`], before the enrolment is
+//! saved.
+//! 
+//! # Logging in
+//! 
+//! When the user's password is accepted by
+//! [`post_login()`](super::handlers::post_login()), the login is held in the
+//! session as pending, and the user is redirected to the configured
+//! [second step path](super::config::Totp#structfield.path). This should be
+//! routed to [`get_totp_login()`], which renders the `totp` template, and to
+//! [`post_totp_login()`], which checks the code supplied. The template receives
+//! `Title`, `PageURL` (the path to post the form to), `Failed`, and
+//! `CsrfToken`, and the form needs a `code` field, which accepts either a code
+//! from the authenticator app or a recovery code.
+//! 
+//! Each code can only be used once, and failed attempts count towards the same
+//! [lockout policy](super::lockout) as passwords. The secrets, recovery codes,
+//! and last-used codes are held by the application, through a
+//! [`TotpUserProvider`].
+
+
+
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/totp.rs"]
+mod tests;
+
+
+
+//		Packages																										
+
+use super::{
+	audit::{AuthEvent, LoginMethod, record_event},
+	config::Totp,
+	errors::AuthError,
+	middleware::{Context, User, UserProvider},
+	remember::remember_login,
+	state::StateProvider,
+	utility::{extract_uri_query_parts, redirect_target, safe_redirect_uri, tokens_match},
+};
+use crate::app::errors::AppError;
+use argon2::password_hash::rand_core::{OsRng, RngCore as _};
+use axum::{
+	Extension,
+	Form,
+	extract::{ConnectInfo, State},
+	http::{Uri, header::SET_COOKIE},
+	response::{AppendHeaders, Html, IntoResponse as _, Redirect, Response},
+};
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD as BASE64};
+use core::{future::Future, iter, net::SocketAddr};
+use rubedo::sugar::s;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest as _, Sha256};
+use std::{collections::HashMap, sync::Arc};
+use tera::Context as Template;
+use totp_rs::{Algorithm, Secret, TOTP};
+use tower_sessions::cookie::time::OffsetDateTime;
+use tracing::{info, warn};
+
+
+
+//		Constants																										
+
+/// The number of random bytes in a recovery code.
+const RECOVERY_CODE_LENGTH: usize = 10;
+
+/// The number of random bytes in a generated secret. This is the length
+/// recommended by [RFC 4226](https://www.rfc-editor.org/rfc/rfc4226).
+const SECRET_LENGTH:        usize = 20;
+
+/// The key used to store a login that is awaiting its second factor.
+const SESSION_TOTP_KEY:     &str  = "_totp_login";
+
+
+
+//		Structs																											
+
+//		PendingLogin															
+/// A login that is awaiting its second factor, as held in the session.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct PendingLogin<I> {
+	//		Private properties													
+	/// The ID of the user whose password was accepted.
+	user_id:    I,
+	
+	/// The URL to redirect to after logging in.
+	uri:        String,
+	
+	/// Whether the "remember me" box was ticked.
+	remember:   bool,
+	
+	/// The time at which the password was accepted, as a Unix timestamp.
+	started_at: i64,
+}
+
+//		PostTotpLogin															
+/// The data sent by the second login step form.
+/// 
+/// This is consumed by the [`post_totp_login()`] handler.
+/// 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct PostTotpLogin {
+	//		Public properties													
+	/// The code from the user's authenticator app, or a recovery code.
+	pub code: String,
+}
+
+//		TotpEnrolment															
+/// The details of a new enrolment, as created by [`enrol()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct TotpEnrolment {
+	//		Public properties													
+	/// The secret, base32-encoded, which should be stored for the user, and
+	/// can be shown to them for manual entry into their authenticator app.
+	pub secret:               String,
+	
+	/// The `otpauth://` URI for the user to add to their authenticator app,
+	/// typically by showing it as a QR code.
+	pub uri:                  String,
+	
+	/// The recovery codes, which should be shown to the user once, and not
+	/// stored.
+	pub recovery_codes:       Vec<String>,
+	
+	/// The hashes of the recovery codes, which should be stored for the user.
+	pub recovery_code_hashes: Vec<String>,
+}
+
+//		TotpSecret																
+/// A user's TOTP secret, as held by a [`TotpUserProvider`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[expect(clippy::exhaustive_structs, reason = "Needs to be constructable by provider implementations")]
+pub struct TotpSecret {
+	//		Public properties													
+	/// The secret, base32-encoded, as given by [`TotpEnrolment::secret`].
+	pub secret:         String,
+	
+	/// The time step of the last code that was accepted, if any, as recorded
+	/// by [`TotpUserProvider::record_totp_use()`]. Codes from this step or
+	/// earlier are not accepted, so that a code cannot be used twice.
+	pub last_used_step: Option<u64>,
+}
+
+
+
+//		Traits																											
+
+//§		TotpUserProvider														
+/// A trait for providing users' TOTP secrets and recovery codes.
+/// 
+/// This is used by [`post_totp_login()`] to check the second factor supplied
+/// by a user whose password has been accepted.
+/// 
+pub trait TotpUserProvider: UserProvider {
+	//		find_totp_secret													
+	/// Finds the TOTP secret of a user.
+	/// 
+	/// Returns [`Some(TotpSecret)`](Some) if the user has enrolled, otherwise
+	/// returns [`None`].
+	/// 
+	/// # Parameters
+	/// 
+	/// * `state` - The application state.
+	/// * `user`  - The user.
+	/// 
+	/// # Errors
+	/// 
+	/// If the lookup fails, an error will be returned.
+	/// 
+	fn find_totp_secret<SP: StateProvider>(
+		state: &SP,
+		user:  &Self::User,
+	) -> impl Future<Output = Result<Option<TotpSecret>, Self::Error>> + Send;
+	
+	//		record_totp_use														
+	/// Records that a code has been accepted for a user.
+	/// 
+	/// This should update the user's
+	/// [`last_used_step`](TotpSecret::last_used_step).
+	/// 
+	/// # Parameters
+	/// 
+	/// * `state` - The application state.
+	/// * `user`  - The user.
+	/// * `step`  - The time step of the code that was accepted.
+	/// 
+	/// # Errors
+	/// 
+	/// If the update fails, an error will be returned.
+	/// 
+	fn record_totp_use<SP: StateProvider>(
+		state: &SP,
+		user:  &Self::User,
+		step:  u64,
+	) -> impl Future<Output = Result<(), Self::Error>> + Send;
+	
+	//		use_recovery_code													
+	/// Uses up one of a user's recovery codes.
+	/// 
+	/// If the user has a recovery code with the given hash, it should be
+	/// removed, so that it cannot be used again, and `true` returned.
+	/// Otherwise `false` should be returned.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `state` - The application state.
+	/// * `user`  - The user.
+	/// * `hash`  - The hash of the supplied recovery code, as produced by
+	///             [`hash_recovery_code()`].
+	/// 
+	/// # Errors
+	/// 
+	/// If the lookup or update fails, an error will be returned.
+	/// 
+	fn use_recovery_code<SP: StateProvider>(
+		state: &SP,
+		user:  &Self::User,
+		hash:  &str,
+	) -> impl Future<Output = Result<bool, Self::Error>> + Send;
+}
+
+
+
+//		Functions																										
+
+//		build_totp																
+/// Creates a TOTP generator for a secret.
+/// 
+/// # Parameters
+/// 
+/// * `config`       - The TOTP configuration.
+/// * `secret`       - The raw secret.
+/// * `account_name` - The name of the account, as shown in authenticator
+///                    apps.
+/// 
+/// # Errors
+/// 
+/// If the configuration, secret, or account name are invalid, an error will be
+/// returned.
+/// 
+fn build_totp(config: &Totp, secret: Vec<u8>, account_name: &str) -> Result<TOTP, AuthError> {
+	TOTP::new(
+		Algorithm::SHA1,
+		config.digits,
+		config.skew,
+		config.step,
+		secret,
+		Some(config.issuer.clone()),
+		account_name.to_owned(),
+	).map_err(|err| AuthError::TotpError(err.to_string()))
+}
+
+//		check_second_factor														
+/// Checks a code supplied as a second factor.
+/// 
+/// Codes with the configured number of digits are checked against the user's
+/// secret, and anything else is treated as a recovery code. The use of the
+/// code is recorded through the [`TotpUserProvider`].
+/// 
+/// # Parameters
+/// 
+/// * `state`  - The application state.
+/// * `config` - The TOTP configuration.
+/// * `user`   - The user.
+/// * `code`   - The supplied code.
+/// * `now`    - The current time, as a Unix timestamp.
+/// 
+/// # Errors
+/// 
+/// If the user's secret is invalid, or a lookup fails, an error will be
+/// returned.
+/// 
+async fn check_second_factor<SP, U, UP>(
+	state:  &SP,
+	config: &Totp,
+	user:   &U,
+	code:   &str,
+	now:    i64,
+) -> Result<bool, AuthError>
+where
+	SP: StateProvider,
+	U:  User,
+	UP: TotpUserProvider<User = U>,
+{
+	let trimmed = code.trim();
+	if trimmed.len() == config.digits && trimmed.bytes().all(|byte| byte.is_ascii_digit()) {
+		let Some(secret) = UP::find_totp_secret(state, user).await
+			.map_err(|err| AuthError::UserProviderError(Box::new(err)))?
+		else {
+			warn!("Second factor required but not enrolled for user: {}", user.to_loggable_string());
+			return Ok(false);
+		};
+		let Some(step) = verify_code(config, &secret, trimmed, u64::try_from(now).unwrap_or_default())? else {
+			return Ok(false);
+		};
+		UP::record_totp_use(state, user, step).await
+			.map_err(|err| AuthError::UserProviderError(Box::new(err)))?
+		;
+		return Ok(true);
+	}
+	let accepted = UP::use_recovery_code(state, user, &hash_recovery_code(trimmed)).await
+		.map_err(|err| AuthError::UserProviderError(Box::new(err)))?
+	;
+	if accepted {
+		info!("Recovery code used by user: {}", user.to_loggable_string());
+	}
+	Ok(accepted)
+}
+
+//		enrol																	
+/// Enrols a user for two-factor authentication.
+/// 
+/// Generates a new secret and set of recovery codes. Nothing is stored, and
+/// so the secret and recovery code hashes need to be saved for the user once
+/// they have added the secret to their authenticator app.
+/// 
+/// # Parameters
+/// 
+/// * `config`       - The TOTP configuration.
+/// * `account_name` - The name of the account, as shown in authenticator apps,
+///                    such as the user's username or email address. This must
+///                    not contain a colon.
+/// 
+/// # Errors
+/// 
+/// If the configuration or account name are invalid, an error will be
+/// returned.
+/// 
+pub fn enrol(config: &Totp, account_name: &str) -> Result<TotpEnrolment, AuthError> {
+	let mut secret = vec![0_u8; SECRET_LENGTH];
+	OsRng.fill_bytes(&mut secret);
+	let totp           = build_totp(config, secret, account_name)?;
+	let recovery_codes = generate_recovery_codes(config.recovery_codes);
+	Ok(TotpEnrolment {
+		secret:               totp.get_secret_base32(),
+		uri:                  totp.get_url(),
+		recovery_code_hashes: recovery_codes.iter().map(|code| hash_recovery_code(code)).collect(),
+		recovery_codes,
+	})
+}
+
+//		generate_recovery_codes													
+/// Generates a set of random recovery codes.
+/// 
+/// Each code is formatted as four groups of four characters, for instance
+/// `abcd-efgh-ijkl-mnop`, to make it easier to copy down.
+/// 
+/// # Parameters
+/// 
+/// * `count` - The number of codes to generate.
+/// 
+#[must_use]
+pub fn generate_recovery_codes(count: usize) -> Vec<String> {
+	iter::repeat_with(|| {
+		let mut bytes = [0_u8; RECOVERY_CODE_LENGTH];
+		OsRng.fill_bytes(&mut bytes);
+		let encoded   = Secret::Raw(bytes.to_vec()).to_encoded().to_string().to_lowercase();
+		encoded.as_bytes()
+			.chunks(4)
+			.map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+			.collect::<Vec<_>>()
+			.join("-")
+	}).take(count).collect()
+}
+
+//		get_totp_login															
+/// Shows the second login step page.
+/// 
+/// Renders the `totp` template, asking for a code, if there is a login
+/// awaiting its second factor. Otherwise, redirects to the home page.
+/// 
+/// # Parameters
+/// 
+/// * `state` - The application state.
+/// * `auth`  - The authentication context.
+/// * `uri`   - The request URI.
+/// 
+/// # Errors
+/// 
+/// If the session cannot be read, or there is an error rendering the page, an
+/// error will be returned.
+/// 
+pub async fn get_totp_login<SP, U>(
+	State(state): State<Arc<SP>>,
+	auth:         Context<U>,
+	uri:          Uri,
+) -> Result<Response, AppError>
+where
+	SP: StateProvider,
+	U:  User,
+{
+	let Some(session) = auth.session() else {
+		return Ok(Redirect::to("/").into_response());
+	};
+	if session.get::<PendingLogin<U::Id>>(SESSION_TOTP_KEY).await.map_err(AuthError::from)?.is_none() {
+		return Ok(Redirect::to("/").into_response());
+	}
+	let mut template = Template::new();
+	template.insert("Title",   &state.title());
+	template.insert("PageURL", &state.config().totp.path);
+	template.insert("Failed",  &extract_uri_query_parts(&uri).contains_key("failed"));
+	Ok(Html(auth.render(&*state, "totp", template).await?).into_response())
+}
+
+//		hash_recovery_code														
+/// Hashes a recovery code for storage and lookup.
+/// 
+/// The code is normalised first, by removing any separators and whitespace
+/// and ignoring case, so that minor differences in how it is typed do not
+/// matter.
+/// 
+/// # Parameters
+/// 
+/// * `code` - The recovery code.
+/// 
+#[must_use]
+pub fn hash_recovery_code(code: &str) -> String {
+	let normalised: String = code.chars()
+		.filter(char::is_ascii_alphanumeric)
+		.map(|ch| ch.to_ascii_lowercase())
+		.collect()
+	;
+	BASE64.encode(Sha256::digest(normalised.as_bytes()))
+}
+
+//		post_totp_login															
+/// Processes the second login step form.
+/// 
+/// Logs the user in if the code is valid, and redirects to the page that was
+/// originally requested. The code can be either a code from the user's
+/// authenticator app, or one of their recovery codes, which is then used up.
+/// If the code is not valid, the user is redirected back to the second login
+/// step with a `failed` parameter.
+/// 
+/// Failed attempts are counted in the same way as failed passwords by
+/// [`post_login()`](super::handlers::post_login()). Once the user or their IP
+/// address is locked out, or if the code is not entered within the configured
+/// [time limit](super::config::Totp#structfield.pending_lifetime), the pending
+/// login is abandoned and the user is redirected to the original page, which
+/// will show the login page with a `locked` or `failed` parameter.
+/// 
+/// # Parameters
+/// 
+/// * `state`        - The application state.
+/// * `connect_info` - The connection info, if available.
+/// * `auth`         - The authentication context.
+/// * `form`         - The second login step form.
+/// 
+/// # Errors
+/// 
+/// If the session cannot be updated, the user's secret is invalid, or a lookup
+/// fails, an error will be returned.
+/// 
+pub async fn post_totp_login<SP, U, UP>(
+	State(state): State<Arc<SP>>,
+	connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+	mut auth:     Context<U>,
+	Form(form):   Form<PostTotpLogin>,
+) -> Result<Response, AuthError>
+where
+	SP: StateProvider,
+	U:  User,
+	UP: TotpUserProvider<User = U>,
+{
+	let session = auth.session().ok_or(AuthError::NoSession)?.clone();
+	let Some(pending) = session.remove::<PendingLogin<U::Id>>(SESSION_TOTP_KEY).await? else {
+		warn!("Second login step attempted without a pending login");
+		return Ok(Redirect::to("/").into_response());
+	};
+	let config     = &state.config().totp;
+	let uri        = safe_redirect_uri(&pending.uri, &state.config().redirects.allowed_origins);
+	let mut params = extract_uri_query_parts(&uri);
+	let now        = OffsetDateTime::now_utc().unix_timestamp();
+	let expires_at = pending.started_at.saturating_add(i64::try_from(config.pending_lifetime).unwrap_or(i64::MAX));
+	if now >= expires_at {
+		info!("Second login step expired for user: {}", pending.user_id);
+		drop(params.insert(s!("failed"), s!("")));
+		return redirect(&uri, &params);
+	}
+	let Some(user) = UP::find_by_id(&*state, &pending.user_id).await
+		.map_err(|err| AuthError::UserProviderError(Box::new(err)))?
+	else {
+		drop(params.insert(s!("failed"), s!("")));
+		return redirect(&uri, &params);
+	};
+	let user_string = user.to_loggable_string();
+	let ip          = connect_info.map(|Extension(ConnectInfo(address))| address.ip());
+	let policy      = &state.config().lockout;
+	if let Some(remaining) = state.lockout().check(policy, &user_string, ip) {
+		warn!("Rejected locked out second login step for user: {} ({}s remaining)", &user_string, remaining.as_secs());
+		record_event(&*state, AuthEvent::LockedOut { user: user_string.clone(), seconds: remaining.as_secs() }, auth.client()).await;
+		drop(params.insert(s!("locked"), s!("")));
+		return redirect(&uri, &params);
+	}
+	if !check_second_factor::<SP, U, UP>(&state, config, &user, &form.code, now).await? {
+		warn!("Failed second login step for user: {}", &user_string);
+		record_event(&*state, AuthEvent::LoginFailed { user: user_string.clone(), method: LoginMethod::TwoFactor }, auth.client()).await;
+		if let Some(duration) = state.lockout().record_failure(policy, &user_string, ip) {
+			record_event(&*state, AuthEvent::LockedOut { user: user_string.clone(), seconds: duration.as_secs() }, auth.client()).await;
+			drop(params.insert(s!("locked"), s!("")));
+			return redirect(&uri, &params);
+		}
+		session.insert(SESSION_TOTP_KEY, &pending).await?;
+		return Ok(Redirect::to(&format!("{}?failed", config.path)).into_response());
+	}
+	info!("Logging in user: {}", &user_string);
+	state.lockout().record_success(&user_string);
+	auth.login(&*state, &user).await?;
+	record_event(&*state, AuthEvent::Login { user: user_string, method: LoginMethod::TwoFactor }, auth.client()).await;
+	let cookie = if pending.remember && state.config().remember.enabled {
+		Some(remember_login(&*state, &user).await?)
+	} else {
+		None
+	};
+	Ok((
+		AppendHeaders(cookie.map(|set| (SET_COOKIE, set.to_string()))),
+		redirect(&uri, &params)?,
+	).into_response())
+}
+
+//		redirect																
+/// Creates a redirect to a URI with the given query parameters.
+/// 
+/// # Parameters
+/// 
+/// * `uri`    - The URI to redirect to.
+/// * `params` - The query parameters.
+/// 
+/// # Errors
+/// 
+/// If the URI cannot be built, an error will be returned.
+/// 
+fn redirect(uri: &Uri, params: &HashMap<String, String>) -> Result<Response, AuthError> {
+	Ok(Redirect::to(&redirect_target(uri, params)?).into_response())
+}
+
+//		start_totp_login														
+/// Starts the second login step for a user whose password has been accepted.
+/// 
+/// The login is held in the session until the user supplies a code to
+/// [`post_totp_login()`].
+/// 
+/// # Parameters
+/// 
+/// * `auth`     - The authentication context.
+/// * `user`     - The user.
+/// * `uri`      - The URL to redirect to after logging in.
+/// * `remember` - Whether the "remember me" box was ticked.
+/// 
+/// # Errors
+/// 
+/// If there is no session, or the session cannot be updated, an error will be
+/// returned.
+/// 
+pub async fn start_totp_login<U: User>(
+	auth:     &Context<U>,
+	user:     &U,
+	uri:      &str,
+	remember: bool,
+) -> Result<(), AuthError> {
+	let session = auth.session().ok_or(AuthError::NoSession)?;
+	session.insert(SESSION_TOTP_KEY, &PendingLogin {
+		user_id:    user.id().clone(),
+		uri:        uri.to_owned(),
+		remember,
+		started_at: OffsetDateTime::now_utc().unix_timestamp(),
+	}).await?;
+	Ok(())
+}
+
+//		verify_code																
+/// Verifies a code from an authenticator app.
+/// 
+/// Codes are accepted from the configured number of steps either side of the
+/// current time, but not from the step of the last accepted code or earlier,
+/// so that a code cannot be used twice. Returns the step of the matching code,
+/// which should be recorded using [`TotpUserProvider::record_totp_use()`] if
+/// the code is accepted.
+/// 
+/// # Parameters
+/// 
+/// * `config` - The TOTP configuration.
+/// * `secret` - The user's secret.
+/// * `code`   - The supplied code.
+/// * `now`    - The current time, as a Unix timestamp.
+/// 
+/// # Errors
+/// 
+/// If the secret or configuration are invalid, an error will be returned.
+/// 
+pub fn verify_code(config: &Totp, secret: &TotpSecret, code: &str, now: u64) -> Result<Option<u64>, AuthError> {
+	let bytes   = Secret::Encoded(secret.secret.clone()).to_bytes().map_err(|err| AuthError::TotpError(err.to_string()))?;
+	let totp    = build_totp(config, bytes, "")?;
+	let current = now.checked_div(config.step).unwrap_or_default();
+	let skew    = u64::from(config.skew);
+	Ok((current.saturating_sub(skew)..=current.saturating_add(skew))
+		.filter(|step| secret.last_used_step.is_none_or(|last| *step > last))
+		.find(|step| tokens_match(&totp.generate(step.saturating_mul(config.step)), code))
+	)
+}
+
+
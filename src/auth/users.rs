@@ -0,0 +1,80 @@
+//! Configuration-based user store.
+
+
+
+//		Packages																										
+
+use super::password::{HashedPassword, verify_dummy};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+
+
+//		Structs																											
+
+//		ConfigUserStore															
+/// A user store loaded from configuration.
+/// 
+/// This is a simple list of usernames and their password hashes, suitable for
+/// small deployments where users are managed by editing the configuration. The
+/// hashes are validated when the configuration is loaded, and can be generated
+/// using the [`hash_password()`](super::password::hash_password()) function,
+/// or the `hash_password` example.
+/// 
+/// Usernames are case-sensitive.
+/// 
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct ConfigUserStore(HashMap<String, HashedPassword>);
+
+//󰭅		ConfigUserStore															
+impl ConfigUserStore {
+	//		new																	
+	/// Creates a new user store from a list of usernames and password hashes.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `users` - The usernames and their password hashes.
+	/// 
+	#[must_use]
+	pub const fn new(users: HashMap<String, HashedPassword>) -> Self {
+		Self(users)
+	}
+	
+	//		contains															
+	/// Checks whether a user exists.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `username` - The username to check.
+	/// 
+	#[must_use]
+	pub fn contains(&self, username: &str) -> bool {
+		self.0.contains_key(username)
+	}
+	
+	//		verify																
+	/// Verifies a user's password.
+	/// 
+	/// Returns `true` if the user exists and the password matches, otherwise
+	/// `false`. If the user does not exist, a dummy verification is performed
+	/// so that the time taken does not reveal whether the username is valid.
+	/// 
+	/// # Parameters
+	/// 
+	/// * `username` - The username.
+	/// * `password` - The password to verify.
+	/// 
+	#[must_use]
+	pub fn verify(&self, username: &str, password: &str) -> bool {
+		self.0.get(username).map_or_else(
+			|| {
+				verify_dummy(password);
+				false
+			},
+			|hash| hash.verify(password),
+		)
+	}
+}
+
+
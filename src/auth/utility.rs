@@ -2,12 +2,22 @@
 
 
 
+//		Modules																											
+
+#[cfg(test)]
+#[path = "tests/utility.rs"]
+mod tests;
+
+
+
 //		Packages																										
 
+use argon2::password_hash::rand_core::{OsRng, RngCore as _};
 use axum::http::{
 	Uri,
-	uri::InvalidUriParts as RealInvalidUriParts,
+	uri::{InvalidUriParts as RealInvalidUriParts, PathAndQuery},
 };
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD as BASE64};
 use core::{
 	error::Error,
 	fmt::{Display, Formatter, self},
@@ -67,6 +77,10 @@ pub fn extract_uri_query_parts(uri: &Uri) -> HashMap<String, String> {
 //		build_uri																
 /// Builds a URI from a path and a set of query parameters.
 /// 
+/// The parameters are percent-encoded, and sorted by name so that the result
+/// is predictable. If the path already has a query string, the parameters are
+/// appended to it.
+/// 
 /// # Parameters
 /// 
 /// * `path`   - The path to build the URI from.
@@ -83,17 +97,16 @@ where
 	V: AsRef<str> + Display,
 	H: BuildHasher,
 {
+	let mut pairs = params.iter().collect::<Vec<_>>();
+	pairs.sort_by(|a, b| a.0.as_ref().cmp(b.0.as_ref()));
+	let query     = form_urlencoded::Serializer::new(String::new()).extend_pairs(pairs).finish();
+	let base      = path.as_ref();
 	Uri::builder()
-		.path_and_query(format!(
-			"{}?{}",
-			path.as_ref(),
-			params
-				.iter()
-				.map(|(k, v)| format!("{k}={v}"))
-				.collect::<Vec<String>>()
-				.join("&")
-			,
-		))
+		.path_and_query(match (query.is_empty(), base.contains('?')) {
+			(true,  _)     => base.to_owned(),
+			(false, false) => format!("{base}?{query}"),
+			(false, true)  => format!("{base}&{query}"),
+		})
 		.build()
 		.map_err(|err| {
 			if !err.is::<RealInvalidUriParts>() {
@@ -105,4 +118,102 @@ where
 		})
 }
 
+//		generate_token															
+/// Generates a new random token.
+/// 
+/// The token is encoded using URL-safe base64 without padding, so that it can
+/// be used in cookies, headers, forms, and URLs without further encoding.
+/// 
+/// # Parameters
+/// 
+/// * `length` - The number of random bytes in the token.
+/// 
+#[must_use]
+pub fn generate_token(length: usize) -> String {
+	let mut bytes = vec![0_u8; length];
+	OsRng.fill_bytes(&mut bytes);
+	BASE64.encode(bytes)
+}
+
+//		redirect_target															
+/// Builds the target of a redirect from a URI and a set of query parameters.
+/// 
+/// This is the same as [`build_uri()`], except that the scheme and authority
+/// of the URI are kept if it has them, so that it can be used with URIs
+/// returned by [`safe_redirect_uri()`].
+/// 
+/// # Parameters
+/// 
+/// * `uri`    - The URI to redirect to.
+/// * `params` - The query parameters to add to the URI.
+/// 
+/// # Errors
+/// 
+/// If the URI cannot be built, an error will be returned.
+/// 
+pub fn redirect_target<H: BuildHasher>(uri: &Uri, params: &HashMap<String, String, H>) -> Result<String, InvalidUriParts> {
+	let built = build_uri(uri.path(), params)?;
+	let path  = built.path_and_query().map_or("/", PathAndQuery::as_str);
+	Ok(match (uri.scheme_str(), uri.authority()) {
+		(Some(scheme), Some(authority)) => format!("{scheme}://{authority}{path}"),
+		_                               => path.to_owned(),
+	})
+}
+
+//		safe_redirect_uri														
+/// Checks that a URI supplied by a client is safe to redirect to.
+/// 
+/// Relative paths on the same origin are accepted, as long as they cannot be
+/// interpreted by browsers as pointing to another host — so paths starting
+/// with `//` or containing backslashes or whitespace are rejected. Absolute
+/// URLs are only accepted if they use `http` or `https` and their origin, in
+/// the form `https://example.com` or `https://example.com:8443`, is in the
+/// list of allowed origins. Anything else is replaced with `/`, so that a
+/// login cannot be used as an open redirect.
+/// 
+/// # Parameters
+/// 
+/// * `target`          - The URI to check.
+/// * `allowed_origins` - The origins of other sites that can be redirected to.
+/// 
+#[must_use]
+pub fn safe_redirect_uri(target: &str, allowed_origins: &[String]) -> Uri {
+	target
+		.parse::<Uri>()
+		.ok()
+		.filter(|uri| {
+			!target.chars().any(|ch| ch == '\\' || ch.is_whitespace() || ch.is_control())
+				&& match (uri.scheme_str(), uri.authority()) {
+					(None, None)                    => target.starts_with('/') && !target.starts_with("//"),
+					(Some(scheme), Some(authority)) => {
+						let origin = format!("{scheme}://{authority}");
+						matches!(scheme, "http" | "https")
+							&& allowed_origins.iter().any(|allowed| allowed.trim_end_matches('/').eq_ignore_ascii_case(&origin))
+					},
+					_                               => false,
+				}
+		})
+		.unwrap_or_else(|| {
+			warn!("Rejected unsafe redirect target: {target}");
+			Uri::from_static("/")
+		})
+}
+
+//		tokens_match															
+/// Compares two tokens in constant time.
+/// 
+/// The time taken depends only upon the length of the tokens, and not upon
+/// their contents, so that it does not reveal how much of a guess is correct.
+/// 
+/// # Parameters
+/// 
+/// * `expected` - The expected token.
+/// * `supplied` - The supplied token.
+/// 
+#[must_use]
+pub fn tokens_match(expected: &str, supplied: &str) -> bool {
+	expected.len() == supplied.len()
+		&& expected.bytes().zip(supplied.bytes()).fold(0_u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
 
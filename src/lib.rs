@@ -68,6 +68,7 @@ mod feature_based_tests {
 	use assert_json_diff as _;
 	use rubedo as _;
 	use serde_json as _;
+	use tower as _;
 }
 
 
@@ -25,7 +25,7 @@ use super::{
 use axum::{
 	Json,
 	extract::{Query, State},
-	extract::ws::{Message, WebSocketUpgrade, WebSocket},
+	extract::ws::{CloseFrame, Message, WebSocketUpgrade, WebSocket, close_code},
 	response::Response,
 };
 use chrono::{NaiveDateTime, SubsecRound as _, Utc};
@@ -320,6 +320,10 @@ pub async fn get_stats_feed<SP: StateProvider>(
 /// the statistics object for that one type will be returned. This is in order
 /// to keep the transmitted data as efficient as possible.
 /// 
+/// When the statistics processor is [stopped](super::worker::stop()), the
+/// connection will be closed with a "going away" close frame, so that it does
+/// not hold up a graceful shutdown.
+/// 
 /// # Parameters
 /// 
 /// * `state` - The application state.
@@ -334,13 +338,15 @@ pub async fn ws_stats_feed<SP: StateProvider>(
 ) {
 	//		Preparation															
 	info!("WebSocket connection established");
-	//	Subscribe to the broadcast channel
-	let mut rx = if let Some(ref broadcaster) = state.state().read().await.broadcaster {
-		broadcaster.subscribe()
-	} else {
+	//	Subscribe to the broadcast and shutdown channels
+	let stats_state = state.state().read().await;
+	let (Some(broadcaster), Some(shutdown)) = (stats_state.broadcaster.as_ref(), stats_state.shutdown.as_ref()) else {
 		warn!("Broadcast channel not available");
 		return;
 	};
+	let mut rx      = broadcaster.subscribe();
+	let mut halt    = shutdown.subscribe();
+	drop(stats_state);
 	//	Set up a timer to send pings at regular intervals
 	let mut timer     = interval(Duration::from_secs(state.config().ws_ping_interval as u64));
 	let mut timeout   = interval(Duration::from_secs(state.config().ws_ping_timeout  as u64));
@@ -350,6 +356,19 @@ pub async fn ws_stats_feed<SP: StateProvider>(
 	//	Message processing loop
 	#[expect(clippy::pattern_type_mismatch, reason = "Tokio code")]
 	loop { select! {
+		//		Shutdown														
+		//	Close the connection when the server is shutting down
+		_ = halt.changed() => {
+			info!("Closing WebSocket connection due to shutdown");
+			if let Err(err) = ws.send(Message::Close(Some(CloseFrame {
+				code:   close_code::AWAY,
+				reason: "Server shutting down".into(),
+			}))).await {
+				warn!("Failed to send close frame over WebSocket: {err}");
+			}
+			break;
+		},
+		
 		//		Ping															
 		//	Send a ping at regular intervals
 		_ = timer.tick() => {
@@ -19,6 +19,7 @@ use tokio::{
 	sync::{
 		RwLock as AsyncRwLock,
 		broadcast::{Receiver as Listener, Sender as Broadcaster},
+		watch::Sender as WatchSender,
 	},
 };
 use velcro::hash_map;
@@ -58,6 +59,11 @@ pub struct State {
 	/// to this channel to receive the latest statistics for a given period on
 	/// a real-time basis.
 	pub listener:    Option<Listener<AllStatsForPeriod>>,
+	
+	/// The shutdown channel. When this is set to `true`, the statistics
+	/// processor will stop, and any WebSocket statistics feeds will be closed.
+	/// Interested parties can subscribe to it to be notified of shutdown.
+	pub shutdown:    Option<WatchSender<bool>>,
 }
 
 //		Stats																	
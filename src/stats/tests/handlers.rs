@@ -110,6 +110,7 @@ fn prepare_state(start: NaiveDateTime) -> AppState {
 			queue:       None,
 			broadcaster: None,
 			listener:    None,
+			shutdown:    None,
 		}),
 	};
 	state.config.stats.periods = hash_map!{
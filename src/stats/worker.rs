@@ -17,10 +17,10 @@ use std::{
 use tokio::{
 	select,
 	spawn,
-	sync::broadcast,
+	sync::{broadcast, watch},
 	time::{interval, sleep},
 };
-use tracing::error;
+use tracing::{error, info};
 
 #[cfg(feature = "utoipa")]
 use utoipa::ToSchema;
@@ -217,6 +217,8 @@ pub struct ResponseMetrics {
 /// Although this periodic wake-up does incur a very slight overhead, it is
 /// extremely small, and ensures that the statistics are always up-to-date.
 /// 
+/// The thread will exit when [`stop()`] is called.
+/// 
 /// # Parameters
 /// 
 /// * `receiver` - The receiving end of the queue.
@@ -226,13 +228,15 @@ pub async fn start<SP: StateProvider>(state: &Arc<SP>) {
 	if !state.config().enabled {
 		return;
 	}
-	let appstate            = Arc::clone(state);
-	let (sender, receiver)  = flume::unbounded();
-	let (tx, rx)            = broadcast::channel(10);
-	let mut stats_state     = appstate.state().write().await;
-	stats_state.queue       = Some(sender);
-	stats_state.broadcaster = Some(tx);
-	stats_state.listener    = Some(rx);
+	let appstate             = Arc::clone(state);
+	let (sender, receiver)   = flume::unbounded();
+	let (tx, rx)             = broadcast::channel(10);
+	let (shutdown, mut halt) = watch::channel(false);
+	let mut stats_state      = appstate.state().write().await;
+	stats_state.queue        = Some(sender);
+	stats_state.broadcaster  = Some(tx);
+	stats_state.listener     = Some(rx);
+	stats_state.shutdown     = Some(shutdown);
 	//	Fixed time period of the current second
 	let mut current_second  = Utc::now().naive_utc().trunc_subsecs(0);
 	//	Cumulative stats for the current second
@@ -271,6 +275,11 @@ pub async fn start<SP: StateProvider>(state: &Arc<SP>) {
 		//	Queue processing loop
 		let mut timer = interval(Duration::from_secs(1));
 		loop { select!{
+			//	Stop processing when shutdown is requested
+			_ = halt.changed() => {
+				info!("Statistics processor shutting down");
+				break;
+			}
 			_ = timer.tick() => {
 				//	Ensure last period is wrapped up
 				stats_processor(
@@ -303,6 +312,26 @@ pub async fn start<SP: StateProvider>(state: &Arc<SP>) {
 	}));
 }
 
+//		stop																	
+/// Stops the statistics processor.
+/// 
+/// This signals the thread started by [`start()`] to exit, and also tells any
+/// open [WebSocket feeds](crate::stats::handlers::ws_stats_feed()) to close.
+/// It is intended to be called as part of a graceful shutdown, so that
+/// long-lived WebSocket connections do not prevent the server from draining.
+/// 
+/// If the statistics processor has not been started, this does nothing.
+/// 
+/// # Parameters
+/// 
+/// * `state` - The application state.
+/// 
+pub async fn stop<SP: StateProvider>(state: &SP) {
+	if let Some(ref shutdown) = state.state().read().await.shutdown {
+		_ = shutdown.send_replace(true);
+	}
+}
+
 //		stats_processor															
 /// Processes a single response time.
 /// 